@@ -0,0 +1,185 @@
+// Optional raw-PCM tee of the capture and playback streams into timestamped WAV files, for
+// diagnosing audio glitches offline without having to reproduce them interactively. Mirrors
+// cubeb-coreaudio's `audio-dump` build feature. All file I/O happens on a dedicated background
+// thread fed through a bounded channel: `push_samples_*`/`mark_event` never block, and if the
+// writer falls behind (the channel is full) the newest chunk is simply dropped rather than ever
+// stalling the caller's audio callback.
+//
+// Note: `alvr_session::AudioConfig` (the setting this would normally be toggled from) isn't part
+// of this crate, so `AudioDumpConfig` stands in with the same shape. `AudioDumpConfig::default()`
+// is disabled and dumps into the system temp directory; `alxr-common` takes only `enabled` from
+// its own config and keeps the default directory.
+
+use alvr_common::prelude::*;
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::mpsc as smpsc,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Generous relative to one batch of audio: the writer thread only ever falls behind under disk
+// contention, and dropping a dump chunk costs nothing but diagnostic fidelity.
+const DUMP_CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioDumpConfig {
+    pub enabled: bool,
+    pub directory: PathBuf,
+}
+
+impl Default for AudioDumpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: std::env::temp_dir(),
+        }
+    }
+}
+
+enum DumpMessage {
+    Samples(Vec<i16>),
+    Event(&'static str),
+}
+
+/// Tees one stream (capture or playback) into a `<label>_<unix_time>.wav` file, plus a sibling
+/// `.log` noting underrun/overrun events at the sample offset they occurred. Cheaply `Clone`
+/// (just the sending half of the channel to the writer thread), so a caller that rebuilds its
+/// audio callback across a device reconnect can keep feeding the same dump file instead of
+/// starting a new one per rebuild.
+#[derive(Clone)]
+pub struct AudioDumper {
+    sender: smpsc::SyncSender<DumpMessage>,
+}
+
+impl AudioDumper {
+    /// Returns `None` (and dumps nothing) when `config.enabled` is false, so call sites can hold
+    /// an `Option<AudioDumper>` and skip every hook with one check.
+    pub fn new(label: &str, config: &AudioDumpConfig, channels_count: u16, sample_rate: u32) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let wav_path = config.directory.join(format!("{label}_{unix_time}.wav"));
+        let log_path = config.directory.join(format!("{label}_{unix_time}.log"));
+
+        let (sender, receiver) = smpsc::sync_channel(DUMP_CHANNEL_CAPACITY);
+
+        let spawned = thread::Builder::new()
+            .name(format!("audio-dump-{label}"))
+            .spawn(move || {
+                if let Err(e) = run_writer(&wav_path, &log_path, channels_count, sample_rate, receiver)
+                {
+                    error!("Audio dump writer failed: {e}");
+                }
+            });
+
+        match spawned {
+            Ok(_) => Some(Self { sender }),
+            Err(e) => {
+                error!("Failed to spawn audio dump writer thread: {e}");
+                None
+            }
+        }
+    }
+
+    /// Tees already-interleaved i16 PCM, as produced by `record_audio_loop` post-conversion.
+    pub fn push_samples_i16(&self, samples: &[i16]) {
+        self.sender
+            .try_send(DumpMessage::Samples(samples.to_vec()))
+            .ok();
+    }
+
+    /// Tees interleaved f32 samples, as handed to `StreamingSource` on the playback path,
+    /// converting to i16 the same way the rest of this crate does when writing PCM.
+    pub fn push_samples_f32(&self, samples: &[f32]) {
+        let converted = samples
+            .iter()
+            .map(|&s| (s.clamp(-1., 1.) * i16::MAX as f32) as i16)
+            .collect();
+        self.sender.try_send(DumpMessage::Samples(converted)).ok();
+    }
+
+    pub fn mark_underrun(&self) {
+        self.sender.try_send(DumpMessage::Event("underrun")).ok();
+    }
+
+    pub fn mark_overrun(&self) {
+        self.sender.try_send(DumpMessage::Event("overrun")).ok();
+    }
+}
+
+fn run_writer(
+    wav_path: &std::path::Path,
+    log_path: &std::path::Path,
+    channels_count: u16,
+    sample_rate: u32,
+    receiver: smpsc::Receiver<DumpMessage>,
+) -> StrResult {
+    let mut wav_file = trace_err!(File::create(wav_path))?;
+    let mut log_file = trace_err!(File::create(log_path))?;
+    write_wav_header_placeholder(&mut wav_file, channels_count, sample_rate)?;
+
+    let mut samples_written: u64 = 0;
+    while let Ok(message) = receiver.recv() {
+        match message {
+            DumpMessage::Samples(samples) => {
+                for sample in &samples {
+                    trace_err!(wav_file.write_all(&sample.to_le_bytes()))?;
+                }
+                samples_written += samples.len() as u64;
+            }
+            DumpMessage::Event(kind) => {
+                trace_err!(writeln!(log_file, "{kind} at sample {samples_written}"))?;
+            }
+        }
+    }
+
+    finalize_wav_header(&mut wav_file, samples_written * 2)
+}
+
+fn write_wav_header_placeholder(
+    file: &mut File,
+    channels_count: u16,
+    sample_rate: u32,
+) -> StrResult {
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = channels_count * (BITS_PER_SAMPLE / 8);
+    let byte_rate = sample_rate * block_align as u32;
+
+    trace_err!(file.write_all(b"RIFF"))?;
+    trace_err!(file.write_all(&0u32.to_le_bytes()))?; // RIFF chunk size, patched in `finalize_wav_header`
+    trace_err!(file.write_all(b"WAVE"))?;
+    trace_err!(file.write_all(b"fmt "))?;
+    trace_err!(file.write_all(&16u32.to_le_bytes()))?; // fmt chunk size
+    trace_err!(file.write_all(&1u16.to_le_bytes()))?; // PCM
+    trace_err!(file.write_all(&channels_count.to_le_bytes()))?;
+    trace_err!(file.write_all(&sample_rate.to_le_bytes()))?;
+    trace_err!(file.write_all(&byte_rate.to_le_bytes()))?;
+    trace_err!(file.write_all(&block_align.to_le_bytes()))?;
+    trace_err!(file.write_all(&BITS_PER_SAMPLE.to_le_bytes()))?;
+    trace_err!(file.write_all(b"data"))?;
+    trace_err!(file.write_all(&0u32.to_le_bytes()))?; // data chunk size, patched in `finalize_wav_header`
+
+    Ok(())
+}
+
+// The exact byte sizes aren't known until every sample has been written, so the header is first
+// written with zeroed placeholders above, then patched here by seeking back once the writer
+// thread's channel closes (the stream ended).
+fn finalize_wav_header(file: &mut File, data_bytes: u64) -> StrResult {
+    let riff_chunk_size = 36 + data_bytes as u32;
+
+    trace_err!(file.seek(SeekFrom::Start(4)))?;
+    trace_err!(file.write_all(&riff_chunk_size.to_le_bytes()))?;
+    trace_err!(file.seek(SeekFrom::Start(40)))?;
+    trace_err!(file.write_all(&(data_bytes as u32).to_le_bytes()))?;
+
+    Ok(())
+}
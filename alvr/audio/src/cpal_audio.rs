@@ -5,10 +5,8 @@ use cpal::{
     BufferSize, Device, Sample, SampleFormat, StreamConfig, SupportedStreamConfig,
     traits::{DeviceTrait, HostTrait, StreamTrait},
 };
-use parking_lot::Mutex;
 use rodio::Source;
 use std::{
-    collections::VecDeque,
     sync::{Arc, mpsc as smpsc},
     thread,
 };
@@ -29,7 +27,10 @@ use windows::Win32::{
     UI::Shell::PropertiesSystem::IPropertyStore,
 };
 
-use crate::{AudioDeviceType, AudioDevicesList, get_next_frame_batch, receive_samples_loop};
+use crate::{
+    AudioDeviceType, AudioDevicesList, SampleRing, opus_codec,
+    receive_samples_loop,
+};
 
 lazy_static! {
     static ref VIRTUAL_MICROPHONE_PAIRS: Vec<(String, String)> = vec![
@@ -71,6 +72,13 @@ pub struct CpalAudioDevice {
     inner: Device,
 
     device_type: AudioDeviceType,
+
+    // Only `AudioDeviceId::Default` can silently change endpoint underneath a running stream
+    // (unplugging a headset, the OS switching the default sink); an explicitly-selected device by
+    // name/index is expected to stay put. `record_audio_loop`/`play_audio_loop` use this to decide
+    // whether to spawn a default-device-change watcher at all.
+    is_default: bool,
+    linux_backend: LinuxAudioBackend,
 }
 
 #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
@@ -80,6 +88,8 @@ impl CpalAudioDevice {
         id: AudioDeviceId,
         device_type: AudioDeviceType,
     ) -> StrResult<Self> {
+        let is_default = matches!(id, AudioDeviceId::Default);
+
         #[cfg(target_os = "linux")]
         let host = match linux_backend {
             LinuxAudioBackend::Alsa => cpal::host_from_id(cpal::HostId::Alsa).unwrap(),
@@ -159,6 +169,9 @@ impl CpalAudioDevice {
             inner: device,
 
             device_type,
+
+            is_default,
+            linux_backend,
         })
     }
 
@@ -175,6 +188,151 @@ impl CpalAudioDevice {
     }
 }
 
+/// Sent to the thread that owns a cpal stream: either shut down for good, or (only ever sent for
+/// an `AudioDeviceId::Default` device) tear the stream down and rebuild it against whatever the
+/// system default endpoint now is, the way cubeb-coreaudio's `device_change` module reacts to the
+/// default output/input endpoint changing mid-stream.
+enum StreamControl {
+    Shutdown,
+    Rebuild,
+}
+
+/// Watches for the system default endpoint (matching `device_type`) to change and nudges
+/// `control_tx` with `StreamControl::Rebuild` whenever it does. Only meaningful for
+/// `AudioDeviceId::Default`; callers never spawn this for an explicitly-selected device.
+#[cfg(windows)]
+fn spawn_default_device_watcher(
+    device_type: AudioDeviceType,
+    control_tx: smpsc::Sender<StreamControl>,
+) -> thread::JoinHandle<()> {
+    use windows::Win32::{
+        Media::Audio::{EDataFlow, ERole, IMMNotificationClient, IMMNotificationClient_Impl},
+        System::Com::StructuredStorage::PROPERTYKEY,
+    };
+    use windows::core::{PCWSTR, Result as WinResult};
+
+    let target_flow = if device_type.is_output() {
+        windows::Win32::Media::Audio::eRender
+    } else {
+        windows::Win32::Media::Audio::eCapture
+    };
+
+    #[windows::core::implement(IMMNotificationClient)]
+    struct DefaultDeviceWatcher {
+        target_flow: EDataFlow,
+        control_tx: smpsc::Sender<StreamControl>,
+    }
+
+    impl IMMNotificationClient_Impl for DefaultDeviceWatcher_Impl {
+        fn OnDeviceStateChanged(&self, _device_id: &PCWSTR, _new_state: u32) -> WinResult<()> {
+            Ok(())
+        }
+        fn OnDeviceAdded(&self, _device_id: &PCWSTR) -> WinResult<()> {
+            Ok(())
+        }
+        fn OnDeviceRemoved(&self, _device_id: &PCWSTR) -> WinResult<()> {
+            Ok(())
+        }
+        fn OnDefaultDeviceChanged(
+            &self,
+            flow: EDataFlow,
+            role: ERole,
+            _default_device_id: &PCWSTR,
+        ) -> WinResult<()> {
+            // `eConsole` is what the rest of this file already targets for every other endpoint
+            // lookup (`default_output_device`/`default_input_device` follow the same role).
+            if flow == self.target_flow && role == windows::Win32::Media::Audio::eConsole {
+                let _ = self.control_tx.send(StreamControl::Rebuild);
+            }
+            Ok(())
+        }
+        fn OnPropertyValueChanged(
+            &self,
+            _device_id: &PCWSTR,
+            _key: &PROPERTYKEY,
+        ) -> WinResult<()> {
+            Ok(())
+        }
+    }
+
+    thread::spawn(move || unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let Ok(enumerator) =
+            CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)
+        else {
+            return;
+        };
+
+        let client: IMMNotificationClient = DefaultDeviceWatcher {
+            target_flow,
+            control_tx,
+        }
+        .into();
+
+        if enumerator
+            .RegisterEndpointNotificationCallback(&client)
+            .is_err()
+        {
+            return;
+        }
+
+        // `client`/`enumerator` must stay alive for as long as notifications should keep
+        // arriving; `OnDefaultDeviceChanged` above is invoked directly by the audio engine, no
+        // message pump needed on this thread.
+        loop {
+            thread::park();
+        }
+    })
+}
+
+/// Polling fallback for platforms without an endpoint-notification API: re-checks the default
+/// device's name every `POLL_INTERVAL` and nudges `control_tx` whenever it differs from the last
+/// one seen, the same comparison `is_same_device` does.
+#[cfg(not(windows))]
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn spawn_default_device_watcher(
+    linux_backend: LinuxAudioBackend,
+    device_type: AudioDeviceType,
+    control_tx: smpsc::Sender<StreamControl>,
+) -> thread::JoinHandle<()> {
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+    thread::spawn(move || {
+        #[cfg(target_os = "linux")]
+        let host = match linux_backend {
+            LinuxAudioBackend::Alsa => cpal::host_from_id(cpal::HostId::Alsa).unwrap(),
+            LinuxAudioBackend::Jack => cpal::host_from_id(cpal::HostId::Jack).unwrap(),
+            LinuxAudioBackend::PipeWire => unreachable!(),
+        };
+        #[cfg(not(target_os = "linux"))]
+        let host = cpal::default_host();
+
+        let default_device_name = || {
+            if device_type.is_output() {
+                host.default_output_device()
+            } else {
+                host.default_input_device()
+            }
+            .and_then(|d| d.name().ok())
+        };
+
+        let mut last_name = default_device_name();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let current_name = default_device_name();
+            if current_name != last_name {
+                last_name = current_name;
+                if control_tx.send(StreamControl::Rebuild).is_err() {
+                    // Owning thread is gone; nothing left to watch for.
+                    return;
+                }
+            }
+        }
+    })
+}
+
 #[cfg(windows)]
 fn get_windows_device(device: &CpalAudioDevice) -> StrResult<IMMDevice> {
     let device_name = trace_err!(device.inner.name())?;
@@ -277,121 +435,229 @@ impl<'a> Drop for MuteGuard<'a> {
 }
 
 #[cfg_attr(not(windows), allow(unused_variables))]
+#[allow(clippy::too_many_arguments)]
 pub async fn record_audio_loop(
     device: CpalAudioDevice,
     channels_count: u16,
     mute: bool,
     mut sender: StreamSender<()>,
+    codec: opus_codec::AudioCodecConfig,
+    resample_quality: crate::resampler::ResampleQuality,
+    mic_processing_config: crate::mic_processing::MicProcessingConfig,
+    mic_monitor_enabled: bool,
+    audio_dump_config: crate::audio_dump::AudioDumpConfig,
 ) -> StrResult {
-    let config = get_stream_config(&device)?;
-
-    if config.channels() > 2 {
-        return fmt_e!(
-            "Audio devices with more than 2 channels are not supported. {}",
-            "Please turn off surround audio."
-        );
-    }
-
-    let stream_config = StreamConfig {
-        channels: config.channels(),
-        sample_rate: config.sample_rate(),
-        buffer_size: BufferSize::Default,
-    };
+    // `None` unless monitoring is enabled; every hook below is a single `if let Some(...)` away
+    // from being a no-op, the same pattern `dumper` already uses.
+    let mic_monitor = mic_monitor_enabled.then(crate::mixer::mic_monitor_ring);
+    let initial_config = get_stream_config(&device)?;
+    let initial_sample_rate = initial_config.sample_rate().0;
 
     // data_sender/receiver is the bridge between tokio and std thread
     let (data_sender, mut data_receiver) = tmpsc::unbounded_channel::<StrResult<Vec<_>>>();
-    let (_shutdown_notifier, shutdown_receiver) = smpsc::channel::<()>();
+    let (control_tx, control_rx) = smpsc::channel::<StreamControl>();
     let (recycle_sender, recycle_receiver) = smpsc::channel::<Vec<u8>>();
 
+    // `None` unless dumping is enabled; every hook below is a single `if let Some(...)` away from
+    // being a no-op, so the normal (disabled) path costs nothing but this one check per callback.
+    let dumper = crate::audio_dump::AudioDumper::new(
+        "capture",
+        &audio_dump_config,
+        channels_count,
+        initial_sample_rate,
+    );
+
+    // Only a device resolved from `AudioDeviceId::Default` can change endpoint underneath us; an
+    // explicitly-selected device has nothing to watch for.
+    if device.is_default {
+        #[cfg(windows)]
+        spawn_default_device_watcher(device.device_type.clone(), control_tx.clone());
+        #[cfg(not(windows))]
+        spawn_default_device_watcher(
+            device.linux_backend,
+            device.device_type.clone(),
+            control_tx.clone(),
+        );
+    }
+
     let thread_callback = {
         let data_sender = data_sender.clone();
-        move || {
-            #[cfg(windows)]
-            let _mute_guard = if mute && device.device_type.is_output() {
-                set_mute_windows_device(&device, true).ok();
-                Some(MuteGuard { device: &device })
-            } else {
-                None
-            };
+        move || -> StrResult<Vec<u8>> {
+            let mut device = device;
+            let mut config = initial_config;
+            // Only the physical microphone path gets the conditioning chain; the virtual-mic
+            // loopback direction captures whatever the game/OS is already playing, which NS/AGC
+            // would only distort.
+            let is_mic_input = matches!(device.device_type, AudioDeviceType::Input);
+            // Buffer recycling only survives as long as the original stream does: a rebuild drops
+            // the old callback closure (and whatever `Receiver` it captured) along with the old
+            // stream. `.take()` hands it to the first iteration's closure and leaves every
+            // closure built after a rebuild to just allocate fresh instead, a one-time cost on the
+            // rare reconnect path rather than a steady-state regression.
+            let mut recycle_receiver = Some(recycle_receiver);
+
+            loop {
+                // A device whose channel count matches `channels_count` passes straight through;
+                // any other layout (mono/stereo/quad/5.1/7.1) goes through the ITU downmix matrix
+                // instead of the old hard rejection, so surround devices
+                // no longer have to be switched to stereo in the OS before ALVR can use them.
+                let downmix = if config.channels() == channels_count {
+                    None
+                } else {
+                    let input_channels = config.channels();
+                    Some(
+                        crate::downmix::DownmixMatrix::standard(
+                            input_channels as usize,
+                            channels_count as usize,
+                        )
+                        .ok_or_else(|| {
+                            format!(
+                                "Don't know how to mix a {input_channels}-channel device down to {channels_count} channels"
+                            )
+                        })?,
+                    )
+                };
+
+                let stream_config = StreamConfig {
+                    channels: config.channels(),
+                    sample_rate: config.sample_rate(),
+                    buffer_size: BufferSize::Default,
+                };
+
+                // The device doesn't necessarily run at `initial_sample_rate` (the rate the
+                // stream was negotiated at, and the only one the opus encoder/receiver below
+                // knows about); a default-device rebuild in particular can swap in a device
+                // locked to a different native rate. Converting here, before any encoding, keeps
+                // every downstream consumer oblivious to which device rate is actually in use.
+                let mut capture_resampler = (config.sample_rate().0 != initial_sample_rate).then(
+                    || {
+                        crate::resampler::ChannelResampler::with_quality(
+                            channels_count as usize,
+                            config.sample_rate().0,
+                            initial_sample_rate,
+                            resample_quality,
+                        )
+                    },
+                );
 
-            let stream = trace_err!(device.inner.build_input_stream_raw(
-                &stream_config,
-                config.sample_format(),
-                {
-                    let data_sender = data_sender.clone();
-                    move |data, _| {
-                        let mut new_data = recycle_receiver.try_recv().unwrap_or_default();
-                        new_data.clear();
-
-                        let input_channels = config.channels();
-                        let output_channels = channels_count;
-                        let data_bytes = data.bytes();
-
-                        if config.sample_format() == SampleFormat::F32 {
-                            let frames = data_bytes.len() / (4 * input_channels as usize);
-                            let required_capacity = frames * output_channels as usize * 2;
-                            if new_data.capacity() < required_capacity {
-                                new_data.reserve(required_capacity - new_data.len());
-                            }
+                // Runs before `capture_resampler`, at the device's own rate: conditioning the
+                // waveform before it gets filtered by the resampler's anti-alias stage is closer
+                // to how the hardware would see it than conditioning the already-resampled audio.
+                let mut mic_processor = is_mic_input.then(|| {
+                    crate::mic_processing::MicProcessor::new(
+                        channels_count as usize,
+                        config.sample_rate().0,
+                        mic_processing_config,
+                    )
+                });
+
+                #[cfg(windows)]
+                let _mute_guard = if mute && device.device_type.is_output() {
+                    set_mute_windows_device(&device, true).ok();
+                    Some(MuteGuard { device: &device })
+                } else {
+                    None
+                };
+
+                let recycle_receiver_for_closure = recycle_receiver.take();
+                let dumper = dumper.clone();
+                let mic_monitor = mic_monitor.clone();
+                let stream = trace_err!(device.inner.build_input_stream_raw(
+                    &stream_config,
+                    config.sample_format(),
+                    {
+                        let data_sender = data_sender.clone();
+                        move |data, _| {
+                            let mut new_data = recycle_receiver_for_closure
+                                .as_ref()
+                                .and_then(|r| r.try_recv().ok())
+                                .unwrap_or_default();
+                            new_data.clear();
+
+                            let input_channels = config.channels() as usize;
+                            let output_channels = channels_count as usize;
+                            let data_bytes = data.bytes();
+                            let bytes_per_sample = if config.sample_format() == SampleFormat::F32 {
+                                4
+                            } else {
+                                2
+                            };
+
+                            let frames = data_bytes.len() / (bytes_per_sample * input_channels);
+                            new_data.reserve(frames * output_channels * 2);
+
+                            let mut input_frame = vec![0f32; input_channels];
+                            let mut output_frame = Vec::with_capacity(output_channels);
+                            for frame in data_bytes.chunks_exact(bytes_per_sample * input_channels)
+                            {
+                                for (c, sample) in
+                                    frame.chunks_exact(bytes_per_sample).enumerate()
+                                {
+                                    input_frame[c] = if bytes_per_sample == 4 {
+                                        f32::from_ne_bytes([
+                                            sample[0], sample[1], sample[2], sample[3],
+                                        ])
+                                    } else {
+                                        i16::from_ne_bytes([sample[0], sample[1]])
+                                            .to_sample::<f32>()
+                                    };
+                                }
 
-                            #[inline(always)]
-                            fn to_i16_bytes(b: &[u8]) -> [u8; 2] {
-                                f32::from_ne_bytes([b[0], b[1], b[2], b[3]])
-                                    .to_sample::<i16>()
-                                    .to_ne_bytes()
-                            }
+                                if let Some(downmix) = &downmix {
+                                    downmix.apply_frame(&input_frame, &mut output_frame);
+                                } else {
+                                    output_frame.clear();
+                                    output_frame.extend_from_slice(&input_frame);
+                                }
 
-                            if input_channels == 1 && output_channels == 2 {
-                                for chunk in data_bytes.chunks_exact(4) {
-                                    let s = to_i16_bytes(chunk);
-                                    new_data.extend_from_slice(&s);
-                                    new_data.extend_from_slice(&s);
+                                for &sample in &output_frame {
+                                    new_data
+                                        .extend_from_slice(&sample.to_sample::<i16>().to_ne_bytes());
                                 }
-                            } else if input_channels == 2 && output_channels == 1 {
-                                // Average both channels for proper stereo-to-mono downmix
-                                for chunk in data_bytes.chunks_exact(8) {
-                                    let l = f32::from_ne_bytes([
-                                        chunk[0], chunk[1], chunk[2], chunk[3],
-                                    ]);
-                                    let r = f32::from_ne_bytes([
-                                        chunk[4], chunk[5], chunk[6], chunk[7],
-                                    ]);
-                                    let mixed = ((l + r) * 0.5).to_sample::<i16>();
-                                    new_data.extend_from_slice(&mixed.to_ne_bytes());
+                            }
+
+                            if let Some(mic_processor) = &mut mic_processor {
+                                let processed = mic_processor.process_interleaved(
+                                    &opus_codec::pcm_s16le_to_f32(&new_data),
+                                );
+                                new_data.clear();
+                                for sample in processed {
+                                    let clamped =
+                                        (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+                                    new_data.extend_from_slice(&clamped.to_ne_bytes());
                                 }
-                            } else {
-                                for chunk in data_bytes.chunks_exact(4) {
-                                    let s = to_i16_bytes(chunk);
-                                    new_data.extend_from_slice(&s);
+                            }
+
+                            if let Some(resampler) = &mut capture_resampler {
+                                let resampled = resampler.process_interleaved(
+                                    &opus_codec::pcm_s16le_to_f32(&new_data),
+                                );
+                                new_data.clear();
+                                for sample in resampled {
+                                    let clamped =
+                                        (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+                                    new_data.extend_from_slice(&clamped.to_ne_bytes());
                                 }
                             }
-                        } else {
-                            let frames = data_bytes.len() / (2 * input_channels as usize);
-                            let required_capacity = frames * output_channels as usize * 2;
-                            if new_data.capacity() < required_capacity {
-                                new_data.reserve(required_capacity - new_data.len());
+
+                            if let Some(dumper) = &dumper {
+                                let samples: Vec<i16> = new_data
+                                    .chunks_exact(2)
+                                    .map(|b| i16::from_ne_bytes([b[0], b[1]]))
+                                    .collect();
+                                dumper.push_samples_i16(&samples);
                             }
 
-                            if input_channels == 1 && output_channels == 2 {
-                                for chunk in data_bytes.chunks_exact(2) {
-                                    new_data.extend_from_slice(chunk);
-                                    new_data.extend_from_slice(chunk);
-                                }
-                            } else if input_channels == 2 && output_channels == 1 {
-                                // Average both channels for proper stereo-to-mono downmix
-                                for chunk in data_bytes.chunks_exact(4) {
-                                    let l = i16::from_ne_bytes([chunk[0], chunk[1]]);
-                                    let r = i16::from_ne_bytes([chunk[2], chunk[3]]);
-                                    // Use i32 to avoid overflow, then divide
-                                    let mixed = ((l as i32 + r as i32) / 2) as i16;
-                                    new_data.extend_from_slice(&mixed.to_ne_bytes());
+                            // Mono only, matching the mic capture path's always-1 `channels_count`
+                            // (see `mic_monitor_ring`'s doc comment); the virtual-mic loopback
+                            // direction doesn't monitor itself.
+                            if let Some(mic_monitor) = &mic_monitor {
+                                if is_mic_input {
+                                    mic_monitor.push(&opus_codec::pcm_s16le_to_f32(&new_data));
                                 }
-                            } else {
-                                new_data.extend_from_slice(data_bytes);
                             }
-                        }
 
-                        data_sender.send(Ok(new_data)).ok();
+                            data_sender.send(Ok(new_data)).ok();
                     }
                 },
                 {
@@ -407,9 +673,23 @@ pub async fn record_audio_loop(
 
             trace_err!(stream.play())?;
 
-            shutdown_receiver.recv().ok();
+            match control_rx.recv() {
+                Ok(StreamControl::Rebuild) => {
+                    drop(stream);
 
-            Ok(vec![])
+                    device = trace_err!(CpalAudioDevice::new(
+                        device.linux_backend,
+                        AudioDeviceId::Default,
+                        device.device_type.clone(),
+                    ))?;
+                    config = get_stream_config(&device)?;
+
+                    info!("Default audio device changed, rebuilding cpal stream");
+                    continue;
+                }
+                Ok(StreamControl::Shutdown) | Err(_) => return Ok(vec![]),
+            }
+            }
         }
     };
 
@@ -422,24 +702,56 @@ pub async fn record_audio_loop(
         }
     });
 
+    // `None` when `codec` is `Pcm`, in which case each chunk is forwarded as soon as cpal
+    // delivers it, same as before. Opus needs exact `frame_size`-sample frames, so those get
+    // batched up by `batcher` first (cpal's callback chunk size is whatever the OS hands us).
+    let mut opus_encoder = match codec {
+        opus_codec::AudioCodecConfig::Opus(opus_config) => Some(opus_codec::Encoder::new(
+            channels_count as usize,
+            initial_sample_rate,
+            opus_config,
+        )?),
+        opus_codec::AudioCodecConfig::Pcm => None,
+    };
+    let mut batcher = opus_encoder
+        .as_ref()
+        .map(|encoder| opus_codec::FrameBatcher::new(encoder.frame_size() * encoder.channels_count()));
+
     while let Some(maybe_data) = data_receiver.recv().await {
         let data = maybe_data?;
-        let mut buffer = sender.new_buffer(&(), data.len())?;
-        buffer.get_mut().extend(&data);
-        sender.send_buffer(buffer).await.ok();
-        recycle_sender.send(data).ok();
+
+        if let (Some(encoder), Some(batcher)) = (&mut opus_encoder, &mut batcher) {
+            for frame in batcher.push(&opus_codec::pcm_s16le_to_f32(&data)) {
+                let packet = encoder.encode(&frame)?;
+                let mut buffer = sender.new_buffer(&(), packet.len())?;
+                buffer.get_mut().extend(&packet);
+                sender.send_buffer(buffer).await.ok();
+            }
+            recycle_sender.send(data).ok();
+        } else {
+            let mut buffer = sender.new_buffer(&(), data.len())?;
+            buffer.get_mut().extend(&data);
+            sender.send_buffer(buffer).await.ok();
+            recycle_sender.send(data).ok();
+        }
     }
 
     Ok(())
 }
 
 struct StreamingSource {
-    sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+    // "game_audio" track always wraps this same ring (the one `receive_samples_loop` feeds); a
+    // "mic_monitor" track is mixed in on top of it when mic monitoring is enabled. Kept as a
+    // separate field (on top of owning it via `mixer`) purely for the dumper's cheap
+    // underrun-prediction check below, which only cares about the game-audio side.
+    sample_buffer: Arc<SampleRing>,
+    mixer: crate::mixer::Mixer,
     current_batch: Vec<f32>,
     current_batch_cursor: usize,
     channels_count: usize,
     sample_rate: u32,
     batch_frames_count: usize,
+    dumper: Option<crate::audio_dump::AudioDumper>,
 }
 
 impl Source for StreamingSource {
@@ -466,12 +778,21 @@ impl Iterator for StreamingSource {
     #[inline]
     fn next(&mut self) -> Option<f32> {
         if self.current_batch_cursor == 0 {
-            get_next_frame_batch(
-                &mut *self.sample_buffer.lock(),
-                self.channels_count,
-                self.batch_frames_count,
-                &mut self.current_batch,
-            );
+            // Cheap proxy for an imminent underrun: a ring that can't even cover the upcoming
+            // batch is about to make `get_next_frame_batch` pad with silence. Checked before the
+            // call (which would otherwise already have consumed whatever was left).
+            if let Some(dumper) = &self.dumper {
+                if self.sample_buffer.len() < self.batch_frames_count * self.channels_count {
+                    dumper.mark_underrun();
+                }
+            }
+
+            self.mixer
+                .next_batch(self.batch_frames_count, &mut self.current_batch);
+
+            if let Some(dumper) = &self.dumper {
+                dumper.push_samples_f32(&self.current_batch);
+            }
         }
 
         let sample = self.current_batch[self.current_batch_cursor];
@@ -483,12 +804,19 @@ impl Iterator for StreamingSource {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn play_audio_loop(
     device: CpalAudioDevice,
     channels_count: u16,
     sample_rate: u32,
     config: AudioConfig,
     receiver: StreamReceiver<()>,
+    codec: opus_codec::AudioCodecConfig,
+    fade_curve_kind: crate::fade_curve::FadeCurveKind,
+    resample_quality: crate::resampler::ResampleQuality,
+    mic_monitor_gain: Option<f32>,
+    control_sender: Option<tokio::sync::mpsc::UnboundedSender<alvr_sockets::ClientControlPacket>>,
+    audio_dump_config: crate::audio_dump::AudioDumpConfig,
 ) -> StrResult {
     // Size of a chunk of frames. It corresponds to the duration if a fade-in/out in frames.
     let batch_frames_count = sample_rate as usize * config.batch_ms as usize / 1000;
@@ -497,30 +825,112 @@ pub async fn play_audio_loop(
     let average_buffer_frames_count =
         sample_rate as usize * config.average_buffering_ms as usize / 1000;
 
-    let sample_buffer = Arc::new(Mutex::new(VecDeque::new()));
+    // The device's actual native output rate, which may differ from `sample_rate` (the rate the
+    // server encoded the stream at) if the device is locked to a rate the server didn't expect.
+    let initial_output_sample_rate = get_sample_rate(&device)?;
+
+    let ring_capacity = crate::playback_ring_capacity(
+        average_buffer_frames_count,
+        batch_frames_count,
+        channels_count as usize,
+    );
+    let sample_buffer = Arc::new(SampleRing::new(ring_capacity));
+
+    // `None` unless dumping is enabled; every hook is a single `if let Some(...)` away from being
+    // a no-op, so the normal (disabled) path costs nothing but this one check per batch.
+    let dumper = crate::audio_dump::AudioDumper::new(
+        "playback",
+        &audio_dump_config,
+        channels_count,
+        sample_rate,
+    );
+
+    let (control_tx, control_rx) = smpsc::channel::<StreamControl>();
+
+    // A default-device rebuild can hand us a device running at a different native rate than the
+    // one we started with; `receive_samples_loop` rereads this every iteration (see its doc
+    // comment) and rebuilds its resampler when it changes, the same as a PipeWire reconnect does.
+    let output_sample_rate =
+        Arc::new(std::sync::atomic::AtomicU32::new(initial_output_sample_rate));
+
+    // Only a device resolved from `AudioDeviceId::Default` can change endpoint underneath us; an
+    // explicitly-selected device has nothing to watch for.
+    if device.is_default {
+        #[cfg(windows)]
+        spawn_default_device_watcher(device.device_type.clone(), control_tx.clone());
+        #[cfg(not(windows))]
+        spawn_default_device_watcher(
+            device.linux_backend,
+            device.device_type.clone(),
+            control_tx.clone(),
+        );
+    }
 
     // Store the stream in a thread (because !Send)
-    let (_shutdown_notifier, shutdown_receiver) = smpsc::channel::<()>();
     thread::spawn({
         let sample_buffer = Arc::clone(&sample_buffer);
+        let output_sample_rate = Arc::clone(&output_sample_rate);
         move || -> StrResult {
-            let stream = trace_err!(
-                rodio::OutputStreamBuilder::from_device(device.inner.clone())
-                    .and_then(|b| b.open_stream())
-            )?;
-
-            let source = StreamingSource {
-                sample_buffer,
-                current_batch: Vec::with_capacity(batch_frames_count * channels_count as usize),
-                current_batch_cursor: 0,
-                channels_count: channels_count as _,
-                sample_rate,
-                batch_frames_count,
-            };
-            stream.mixer().add(source);
-
-            shutdown_receiver.recv().ok();
-            Ok(())
+            let mut device = device;
+
+            loop {
+                let stream = trace_err!(
+                    rodio::OutputStreamBuilder::from_device(device.inner.clone())
+                        .and_then(|b| b.open_stream())
+                )?;
+
+                // "game_audio" wraps the same `sample_buffer` `receive_samples_loop` feeds; an
+                // optional "mic_monitor" track wraps `mixer::mic_monitor_ring()` at the
+                // configured gain, so the user hears their own mic over the game audio. A single
+                // track mixes down to exactly what `get_next_frame_batch` would have produced
+                // directly, so this costs nothing extra when mic monitoring is off.
+                let mut mixer = crate::mixer::Mixer::new(channels_count as usize);
+                mixer.add_track(crate::mixer::MixerTrack {
+                    name: "game_audio".to_owned(),
+                    sample_buffer: Arc::clone(&sample_buffer),
+                    channels_count: channels_count as usize,
+                    gain: 1.0,
+                });
+                if let Some(gain) = mic_monitor_gain {
+                    mixer.add_track(crate::mixer::MixerTrack {
+                        name: "mic_monitor".to_owned(),
+                        sample_buffer: crate::mixer::mic_monitor_ring(),
+                        channels_count: 1,
+                        gain,
+                    });
+                }
+
+                let source = StreamingSource {
+                    sample_buffer: Arc::clone(&sample_buffer),
+                    mixer,
+                    current_batch: Vec::with_capacity(batch_frames_count * channels_count as usize),
+                    current_batch_cursor: 0,
+                    channels_count: channels_count as _,
+                    sample_rate,
+                    dumper: dumper.clone(),
+                    batch_frames_count,
+                };
+                stream.mixer().add(source);
+
+                match control_rx.recv() {
+                    Ok(StreamControl::Rebuild) => {
+                        drop(stream);
+
+                        device = trace_err!(CpalAudioDevice::new(
+                            device.linux_backend,
+                            AudioDeviceId::Default,
+                            device.device_type.clone(),
+                        ))?;
+                        if let Ok(rate) = get_sample_rate(&device) {
+                            output_sample_rate.store(rate, std::sync::atomic::Ordering::Relaxed);
+                        }
+
+                        info!("Default audio device changed, rebuilding cpal stream");
+                        continue;
+                    }
+                    Ok(StreamControl::Shutdown) | Err(_) => return Ok(()),
+                }
+            }
         }
     });
 
@@ -530,6 +940,12 @@ pub async fn play_audio_loop(
         channels_count as _,
         batch_frames_count,
         average_buffer_frames_count,
+        sample_rate,
+        output_sample_rate,
+        codec,
+        fade_curve_kind,
+        resample_quality,
+        control_sender,
     )
     .await
 }
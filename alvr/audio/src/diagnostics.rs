@@ -0,0 +1,175 @@
+// Lightweight, lock-free audio pipeline diagnostics, in the spirit of how a fast real-time mixer's
+// dump-state records sink overruns and track underruns without the audio callback ever touching a
+// mutex: every counter here is a plain atomic updated with `fetch_add`, and `snapshot()`/
+// `maybe_report_stats()` are the only things that do real work. Neither is called from
+// `get_next_frame_batch` itself (the real-time callback); `maybe_report_stats` is called from
+// `receive_samples_loop` once per packet, and throttles its own sends internally.
+
+use alvr_common::lazy_static;
+use alvr_sockets::{AudioStatsPacket, ClientControlPacket};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc::UnboundedSender;
+
+const HISTOGRAM_BUCKET_FRAMES: u64 = 64;
+const HISTOGRAM_BUCKETS: usize = 32;
+
+// Borrowed from AudioFlinger's "pause direct output when underrunning" heuristic: a single dry
+// `get_next_frame_batch` call is normal jitter, but this many in a row without a full batch in
+// between means the network genuinely can't keep up, and `receive_samples_loop` should stop
+// trickling audio out and do a controlled refill instead.
+const SUSTAINED_STARVATION_THRESHOLD: u64 = 3;
+
+// Cap on how far `bias_buffer_target_up` can push the dynamic buffer target above
+// `average_buffer_frames_count`, so a flaky network raises latency instead of unbounded memory.
+const MAX_BUFFER_TARGET_BIAS_FRAMES: u64 = 2048;
+
+// How often `maybe_report_stats` actually sends, in audio packets: often enough for the
+// dashboard/session layer to chart buffer health responsively, rare enough not to compete with
+// the audio stream itself for control-channel bandwidth.
+const STATS_REPORT_INTERVAL_PACKETS: u64 = 90;
+
+static UNDERRUNS: AtomicU64 = AtomicU64::new(0);
+static OVERFLOWS: AtomicU64 = AtomicU64::new(0);
+static SAMPLES_DROPPED: AtomicU64 = AtomicU64::new(0);
+static PACKET_LOSS_EVENTS: AtomicU64 = AtomicU64::new(0);
+static CROSS_FADES_RENDERED: AtomicU64 = AtomicU64::new(0);
+static CONSECUTIVE_UNDERRUNS: AtomicU64 = AtomicU64::new(0);
+static BUFFER_TARGET_BIAS_FRAMES: AtomicU64 = AtomicU64::new(0);
+static PACKETS_SINCE_REPORT: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    // Histogram of `buffer_frames_size` readings, bucketed in `HISTOGRAM_BUCKET_FRAMES`-wide
+    // bins (the last bucket catches everything at or above its lower bound), so users can see
+    // whether `average_buffering_ms` is tuned well without recording every single sample.
+    static ref HISTOGRAM: Vec<AtomicU64> =
+        (0..HISTOGRAM_BUCKETS).map(|_| AtomicU64::new(0)).collect();
+}
+
+/// Called from `get_next_frame_batch` whenever the callback found fewer than
+/// `batch_frames_count` frames buffered (a playback underrun).
+pub fn record_underrun() {
+    UNDERRUNS.fetch_add(1, Ordering::Relaxed);
+    CONSECUTIVE_UNDERRUNS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Called from `get_next_frame_batch` whenever the callback found a full batch buffered, so a
+/// past run of underruns stops counting as "sustained" once playback is healthy again.
+pub fn record_frame_batch_ok() {
+    CONSECUTIVE_UNDERRUNS.store(0, Ordering::Relaxed);
+}
+
+/// True once `get_next_frame_batch` has come up dry `SUSTAINED_STARVATION_THRESHOLD` times in a
+/// row, rather than hitting one isolated miss. `receive_samples_loop` uses this to tell a
+/// genuine starvation apart from normal jitter before paying for a controlled refill.
+pub fn sustained_starvation() -> bool {
+    CONSECUTIVE_UNDERRUNS.load(Ordering::Relaxed) >= SUSTAINED_STARVATION_THRESHOLD
+}
+
+/// Nudges the dynamic buffer target up by `step_frames` (capped at
+/// `MAX_BUFFER_TARGET_BIAS_FRAMES`). Called from `receive_samples_loop` each time a
+/// sustained-starvation refill completes, so a client that keeps starving ends up with more
+/// headroom instead of repeating the same recovery forever.
+pub fn bias_buffer_target_up(step_frames: usize) {
+    let _ = BUFFER_TARGET_BIAS_FRAMES.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bias| {
+        Some((bias + step_frames as u64).min(MAX_BUFFER_TARGET_BIAS_FRAMES))
+    });
+}
+
+/// The current upward bias to add on top of `average_buffer_frames_count`; see
+/// `bias_buffer_target_up`.
+pub fn buffer_target_bias_frames() -> usize {
+    BUFFER_TARGET_BIAS_FRAMES.load(Ordering::Relaxed) as usize
+}
+
+/// Called from `receive_samples_loop` when the buffer-overflow path drops `dropped_samples`
+/// interleaved samples to bring the buffer back down towards `average_buffer_frames_count`.
+pub fn record_overflow(dropped_samples: usize) {
+    OVERFLOWS.fetch_add(1, Ordering::Relaxed);
+    SAMPLES_DROPPED.fetch_add(dropped_samples as u64, Ordering::Relaxed);
+}
+
+/// Called from `receive_samples_loop` whenever a packet reports loss upstream.
+pub fn record_packet_loss() {
+    PACKET_LOSS_EVENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Called from `receive_samples_loop` whenever a fade-in or cross-fade recovery is rendered.
+pub fn record_cross_fade() {
+    CROSS_FADES_RENDERED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Called from `receive_samples_loop` with the current `buffer_frames_size` after every packet.
+pub fn record_buffer_size(buffer_frames_size: usize) {
+    let bucket = ((buffer_frames_size as u64) / HISTOGRAM_BUCKET_FRAMES)
+        .min(HISTOGRAM_BUCKETS as u64 - 1) as usize;
+    HISTOGRAM[bucket].fetch_add(1, Ordering::Relaxed);
+}
+
+/// A point-in-time snapshot of the audio pipeline's health, ready to serialize straight to the
+/// dashboard/session layer.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct AudioStats {
+    pub underruns: u64,
+    pub overflows: u64,
+    pub samples_dropped: u64,
+    pub packet_loss_events: u64,
+    pub cross_fades_rendered: u64,
+    /// `buffer_size_histogram[i]` counts how many times `buffer_frames_size` fell in
+    /// `[i * histogram_bucket_frames, (i + 1) * histogram_bucket_frames)` frames; the last
+    /// bucket catches everything at or above its lower bound.
+    pub buffer_size_histogram: Vec<u64>,
+    pub histogram_bucket_frames: u64,
+}
+
+impl AudioStats {
+    /// Condenses this snapshot down to the handful of fields the server-facing
+    /// `ClientControlPacket::AudioStats` cares about. `buffer_frames` is passed in separately
+    /// since it's a live reading (`receive_samples_loop`'s current `buffer_frames_size`), not
+    /// something these cumulative counters track.
+    pub fn to_control_packet(&self, buffer_frames: usize) -> AudioStatsPacket {
+        AudioStatsPacket {
+            underruns: self.underruns,
+            overruns: self.overflows,
+            buffer_frames: buffer_frames as u64,
+        }
+    }
+}
+
+/// Snapshots every counter for the dashboard/session layer to poll. Never called from the
+/// real-time callback; the receive loop only reaches it through `maybe_report_stats`, which
+/// throttles how often it runs.
+pub fn snapshot() -> AudioStats {
+    AudioStats {
+        underruns: UNDERRUNS.load(Ordering::Relaxed),
+        overflows: OVERFLOWS.load(Ordering::Relaxed),
+        samples_dropped: SAMPLES_DROPPED.load(Ordering::Relaxed),
+        packet_loss_events: PACKET_LOSS_EVENTS.load(Ordering::Relaxed),
+        cross_fades_rendered: CROSS_FADES_RENDERED.load(Ordering::Relaxed),
+        buffer_size_histogram: HISTOGRAM
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect(),
+        histogram_bucket_frames: HISTOGRAM_BUCKET_FRAMES,
+    }
+}
+
+/// Surfaces buffer health over the control channel: sends a `ClientControlPacket::AudioStats`
+/// (via `AudioStats::to_control_packet`) to `control_sender` roughly every
+/// `STATS_REPORT_INTERVAL_PACKETS` packets. Meant to be called once per `receive_samples_loop`
+/// iteration with its current `buffer_frames_size`; always a no-op when `control_sender` is
+/// `None`, which is the case until whatever owns the real control channel (see
+/// `receive_samples_loop`'s doc comment) starts passing one in.
+pub fn maybe_report_stats(control_sender: Option<&UnboundedSender<ClientControlPacket>>, buffer_frames: usize) {
+    let Some(sender) = control_sender else {
+        return;
+    };
+
+    if PACKETS_SINCE_REPORT.fetch_add(1, Ordering::Relaxed) + 1 < STATS_REPORT_INTERVAL_PACKETS {
+        return;
+    }
+    PACKETS_SINCE_REPORT.store(0, Ordering::Relaxed);
+
+    let packet = snapshot().to_control_packet(buffer_frames);
+    sender.send(ClientControlPacket::AudioStats(packet)).ok();
+}
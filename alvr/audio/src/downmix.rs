@@ -0,0 +1,68 @@
+// Data-driven channel downmix, in the spirit of cubeb-coreaudio's `mixer` module remapping
+// arbitrary `ChannelLayout`s: a coefficient matrix keyed on the input channel count lets
+// `cpal_audio::record_audio_loop` capture any common speaker layout (mono/stereo/quad/5.1/7.1)
+// instead of hard-rejecting anything beyond stereo.
+
+/// `weights[out_channel][in_channel]`; applying the matrix to one frame is
+/// `output[out] = clamp(sum(weights[out][in] * input[in]), -1.0, 1.0)`.
+pub struct DownmixMatrix {
+    weights: Vec<Vec<f32>>,
+}
+
+impl DownmixMatrix {
+    /// Builds the standard ITU downmix matrix for the given channel layout, or `None` if this
+    /// `(input_channels, output_channels)` pair isn't one of the layouts below. Only mono and
+    /// stereo outputs are supported, matching every output device ALVR actually targets.
+    pub fn standard(input_channels: usize, output_channels: usize) -> Option<Self> {
+        // The ITU-standard center/surround downmix coefficient (1/sqrt(2) ~= 0.707): center and
+        // surround channels are folded into L/R at this attenuated weight so the mix clips less
+        // often; with every channel hot at once the sum still exceeds full scale, and
+        // `apply_frame` clamps it.
+        const C: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+        // Every supported multichannel source downmixes to stereo first; a mono request then
+        // just averages that stereo pair, the same way a mono line-out would sum L+R.
+        let stereo = match input_channels {
+            1 => vec![vec![1.0], vec![1.0]],
+            2 => vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+            // Quad: FL FR BL BR
+            4 => vec![vec![1.0, 0.0, C, 0.0], vec![0.0, 1.0, 0.0, C]],
+            // 5.1: FL FR C LFE SL SR (LFE dropped, as is standard for a downmix)
+            6 => vec![
+                vec![1.0, 0.0, C, 0.0, C, 0.0],
+                vec![0.0, 1.0, C, 0.0, 0.0, C],
+            ],
+            // 7.1: FL FR C LFE SL SR BL BR (LFE dropped)
+            8 => vec![
+                vec![1.0, 0.0, C, 0.0, C, 0.0, C, 0.0],
+                vec![0.0, 1.0, C, 0.0, 0.0, C, 0.0, C],
+            ],
+            _ => return None,
+        };
+
+        let weights = match output_channels {
+            2 => stereo,
+            1 => vec![
+                stereo[0]
+                    .iter()
+                    .zip(&stereo[1])
+                    .map(|(l, r)| 0.5 * (l + r))
+                    .collect(),
+            ],
+            _ => return None,
+        };
+
+        Some(Self { weights })
+    }
+
+    /// Mixes one `input_frame` (`input_channels` samples) down into `output_frame`
+    /// (`output_channels` samples, resized as needed), clamping each output sample to avoid
+    /// clipping when several input channels sum to more than full scale.
+    pub fn apply_frame(&self, input_frame: &[f32], output_frame: &mut Vec<f32>) {
+        output_frame.clear();
+        for row in &self.weights {
+            let sum: f32 = row.iter().zip(input_frame).map(|(w, s)| w * s).sum();
+            output_frame.push(sum.clamp(-1.0, 1.0));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// PI controller that keeps `sample_buffer`'s fill level near `average_buffer_frames_count` by
+// nudging the `resampler::ChannelResampler`'s rate ratio a tiny amount, instead of letting the
+// slow divergence between the server's PCM source clock and the client DAC clock eventually drain
+// or overflow the buffer and force an audible resync. Modeled on Android's AudioResamplerDyn: the
+// correction is clamped to a fraction of a percent (so the pitch shift stays inaudible) and
+// slew-limited (so it never steps abruptly), and only ever corrects drift — it's not a substitute
+// for the existing fade-in/cross-fade recovery that handles outright underruns and overflows.
+pub struct DriftController {
+    target_frames: f64,
+    kp: f64,
+    ki: f64,
+    integral: f64,
+    integral_limit: f64,
+    max_correction: f64,
+    max_step: f64,
+    current_correction: f64,
+}
+
+impl DriftController {
+    pub fn new(target_frames: usize) -> Self {
+        Self {
+            target_frames: target_frames as f64,
+            kp: 1e-4,
+            ki: 1e-6,
+            integral: 0.0,
+            // Keep the integral term from winding up far past what `max_correction` can use.
+            integral_limit: 0.005 / 1e-6,
+            max_correction: 0.004,
+            max_step: 0.0005,
+            current_correction: 0.0,
+        }
+    }
+
+    /// Feeds the latest `buffer_frames_size` reading and returns the new rate correction to apply
+    /// via `ChannelResampler::set_rate_correction`. A buffer that's fuller than the target plays
+    /// back very slightly faster (draining it back down); a buffer that's emptier plays back very
+    /// slightly slower.
+    pub fn update(&mut self, buffer_frames_size: usize) -> f64 {
+        let error = buffer_frames_size as f64 - self.target_frames;
+
+        self.integral = (self.integral + error).clamp(-self.integral_limit, self.integral_limit);
+
+        let desired = (self.kp * error + self.ki * self.integral)
+            .clamp(-self.max_correction, self.max_correction);
+
+        let step = (desired - self.current_correction).clamp(-self.max_step, self.max_step);
+        self.current_correction += step;
+        self.current_correction
+    }
+}
@@ -0,0 +1,276 @@
+// Volume-shaping curves for the fade-in/fade-out/cross-fade ramps in `receive_samples_loop`,
+// replacing the previous hard linear `f / batch_frames_count` ramp (which can produce audible
+// clicks at recovery boundaries). A curve is a handful of normalized control points `(t, gain)`
+// with `t, gain ∈ [0, 1]`, interpolated per the selected `Interpolation` kind.
+//
+// `alvr_session::AudioConfig` (the settings struct this would normally be a selectable field on)
+// isn't part of this crate, so `FadeCurveKind` stands in with the same shape: `receive_samples_loop`
+// takes one as a parameter instead of hardcoding `equal_power_rise`/`equal_power_fall`, the same way
+// `opus_codec::AudioCodecConfig` is threaded in rather than read from settings directly. Callers
+// (e.g. `alxr_common`'s `APP_CONFIG`) build the `FadeCurveKind` from whatever local setting stands
+// in for `AudioConfig` until the real field exists upstream.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlPoint {
+    pub t: f32,
+    pub gain: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    Cubic,
+    MonotoneCubic,
+}
+
+/// A volume-shaping curve: normalized control points interpolated per `Interpolation`. For
+/// `Cubic`, a natural cubic spline is fit through the points; for `MonotoneCubic`, Fritsch-Carlson
+/// tangent clamping keeps the curve from overshooting past 1.0 between control points.
+#[derive(Clone, Debug)]
+pub struct FadeCurve {
+    points: Vec<ControlPoint>,
+    interpolation: Interpolation,
+    // dy/dt tangent at each control point; unused (empty) for Step/Linear.
+    tangents: Vec<f32>,
+}
+
+/// Which rise/fall pair `receive_samples_loop` should render its fade-in/fade-out/cross-fade
+/// ramps with. Mirrors `resampler::ResampleQuality`'s role as a stand-in `AudioConfig` field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FadeCurveKind {
+    /// The pre-existing hard `f / batch_frames_count` ramp, kept around for users who'd rather
+    /// match old behavior than take the (tiny) extra per-sample curve-evaluation cost.
+    Linear,
+    /// Default: keeps the summed energy of an overlapping rise+fall pair constant, avoiding the
+    /// dip/peak a linear cross-fade produces.
+    EqualPower,
+}
+
+impl Default for FadeCurveKind {
+    fn default() -> Self {
+        FadeCurveKind::EqualPower
+    }
+}
+
+impl FadeCurveKind {
+    pub fn rise(self) -> FadeCurve {
+        match self {
+            FadeCurveKind::Linear => FadeCurve::linear_rise(),
+            FadeCurveKind::EqualPower => FadeCurve::equal_power_rise(),
+        }
+    }
+
+    pub fn fall(self) -> FadeCurve {
+        match self {
+            FadeCurveKind::Linear => FadeCurve::linear_fall(),
+            FadeCurveKind::EqualPower => FadeCurve::equal_power_fall(),
+        }
+    }
+}
+
+impl FadeCurve {
+    pub fn new(mut points: Vec<ControlPoint>, interpolation: Interpolation) -> Self {
+        points.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+        let tangents = match interpolation {
+            Interpolation::Cubic => natural_cubic_tangents(&points),
+            Interpolation::MonotoneCubic => monotone_cubic_tangents(&points),
+            Interpolation::Step | Interpolation::Linear => Vec::new(),
+        };
+        Self {
+            points,
+            interpolation,
+            tangents,
+        }
+    }
+
+    /// The previous hard-coded ramp shape: a straight line from silence to full volume.
+    pub fn linear_rise() -> Self {
+        Self::new(
+            vec![
+                ControlPoint { t: 0.0, gain: 0.0 },
+                ControlPoint { t: 1.0, gain: 1.0 },
+            ],
+            Interpolation::Linear,
+        )
+    }
+
+    /// The pre-existing hard-coded ramp shape, falling: a straight line from full volume to
+    /// silence. See `linear_rise`.
+    pub fn linear_fall() -> Self {
+        Self::new(
+            vec![
+                ControlPoint { t: 0.0, gain: 1.0 },
+                ControlPoint { t: 1.0, gain: 0.0 },
+            ],
+            Interpolation::Linear,
+        )
+    }
+
+    /// Equal-power rise: `sin(t * pi/2)`, approximated with Monotone-Cubic control points so the
+    /// same general curve-evaluation path handles it. Paired with `equal_power_fall`, the summed
+    /// energy of an overlapping rise+fall pair stays constant (`sin^2 + cos^2 == 1`).
+    pub fn equal_power_rise() -> Self {
+        Self::sampled_equal_power(false)
+    }
+
+    /// Equal-power fall: `cos(t * pi/2)`. See `equal_power_rise`.
+    pub fn equal_power_fall() -> Self {
+        Self::sampled_equal_power(true)
+    }
+
+    fn sampled_equal_power(falling: bool) -> Self {
+        const SAMPLES: usize = 8;
+        let points = (0..=SAMPLES)
+            .map(|i| {
+                let t = i as f32 / SAMPLES as f32;
+                let phase = t * std::f32::consts::FRAC_PI_2;
+                let gain = if falling { phase.cos() } else { phase.sin() };
+                ControlPoint { t, gain }
+            })
+            .collect();
+        Self::new(points, Interpolation::MonotoneCubic)
+    }
+
+    /// Evaluates the curve at normalized position `t` (clamped to `[0, 1]`).
+    pub fn eval(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        let points = &self.points;
+        if points.len() == 1 {
+            return points[0].gain;
+        }
+
+        let seg = points
+            .windows(2)
+            .position(|w| t <= w[1].t)
+            .unwrap_or(points.len() - 2);
+        let (p0, p1) = (points[seg], points[seg + 1]);
+        let span = (p1.t - p0.t).max(1e-9);
+        let local_t = ((t - p0.t) / span).clamp(0.0, 1.0);
+
+        match self.interpolation {
+            Interpolation::Step => p0.gain,
+            Interpolation::Linear => p0.gain + (p1.gain - p0.gain) * local_t,
+            Interpolation::Cubic | Interpolation::MonotoneCubic => hermite(
+                p0.gain,
+                p1.gain,
+                self.tangents[seg] * span,
+                self.tangents[seg + 1] * span,
+                local_t,
+            ),
+        }
+    }
+}
+
+// Cubic Hermite interpolation between two points with tangents m0/m1 already scaled to the
+// segment's own t-span, at normalized position `x` in [0, 1].
+fn hermite(p0: f32, p1: f32, m0: f32, m1: f32, x: f32) -> f32 {
+    let x2 = x * x;
+    let x3 = x2 * x;
+    let h00 = 2.0 * x3 - 3.0 * x2 + 1.0;
+    let h10 = x3 - 2.0 * x2 + x;
+    let h01 = -2.0 * x3 + 3.0 * x2;
+    let h11 = x3 - x2;
+    h00 * p0 + h10 * m0 + h01 * p1 + h11 * m1
+}
+
+// Natural cubic spline tangents (zero second derivative at the endpoints), solved via Thomas'
+// algorithm on the standard tridiagonal system for arbitrarily spaced points.
+fn natural_cubic_tangents(points: &[ControlPoint]) -> Vec<f32> {
+    let n = points.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+
+    let slopes: Vec<f32> = (0..n - 1)
+        .map(|i| (points[i + 1].gain - points[i].gain) / (points[i + 1].t - points[i].t).max(1e-9))
+        .collect();
+
+    let mut a = vec![0.0; n];
+    let mut b = vec![0.0; n];
+    let mut c = vec![0.0; n];
+    let mut d = vec![0.0; n];
+
+    b[0] = 2.0;
+    c[0] = 1.0;
+    d[0] = 3.0 * slopes[0];
+
+    for i in 1..n - 1 {
+        let h_prev = (points[i].t - points[i - 1].t).max(1e-9);
+        let h_next = (points[i + 1].t - points[i].t).max(1e-9);
+        a[i] = h_next;
+        b[i] = 2.0 * (h_prev + h_next);
+        c[i] = h_prev;
+        d[i] = 3.0 * (h_prev * slopes[i] + h_next * slopes[i - 1]);
+    }
+
+    a[n - 1] = 1.0;
+    b[n - 1] = 2.0;
+    d[n - 1] = 3.0 * slopes[n - 2];
+
+    thomas_solve(&a, &b, &c, &d)
+}
+
+// Fritsch-Carlson monotone cubic tangents: clamps the averaged secant slopes so the interpolated
+// curve never overshoots past neighboring control points, important so cross-fade gains never
+// exceed 1.0.
+fn monotone_cubic_tangents(points: &[ControlPoint]) -> Vec<f32> {
+    let n = points.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+
+    let slopes: Vec<f32> = (0..n - 1)
+        .map(|i| (points[i + 1].gain - points[i].gain) / (points[i + 1].t - points[i].t).max(1e-9))
+        .collect();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = slopes[0];
+    tangents[n - 1] = slopes[n - 2];
+    for i in 1..n - 1 {
+        tangents[i] = if slopes[i - 1] * slopes[i] <= 0.0 {
+            0.0
+        } else {
+            (slopes[i - 1] + slopes[i]) / 2.0
+        };
+    }
+
+    for i in 0..n - 1 {
+        let s = slopes[i];
+        if s == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        let alpha = tangents[i] / s;
+        let beta = tangents[i + 1] / s;
+        let dist = alpha * alpha + beta * beta;
+        if dist > 9.0 {
+            let tau = 3.0 / dist.sqrt();
+            tangents[i] = tau * alpha * s;
+            tangents[i + 1] = tau * beta * s;
+        }
+    }
+
+    tangents
+}
+
+fn thomas_solve(a: &[f32], b: &[f32], c: &[f32], d: &[f32]) -> Vec<f32> {
+    let n = b.len();
+    let mut cp = vec![0.0; n];
+    let mut dp = vec![0.0; n];
+    cp[0] = c[0] / b[0];
+    dp[0] = d[0] / b[0];
+    for i in 1..n {
+        let m = b[i] - a[i] * cp[i - 1];
+        cp[i] = c[i] / m;
+        dp[i] = (d[i] - a[i] * dp[i - 1]) / m;
+    }
+
+    let mut x = vec![0.0; n];
+    x[n - 1] = dp[n - 1];
+    for i in (0..n - 1).rev() {
+        x[i] = dp[i] - cp[i] * x[i + 1];
+    }
+    x
+}
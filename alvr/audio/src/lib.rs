@@ -1,15 +1,36 @@
 use alvr_common::prelude::*;
 use alvr_sockets::{StreamReceiver, StreamSender};
-use parking_lot::Mutex;
 use serde::Serialize;
-use std::{collections::VecDeque, sync::Arc};
+use std::sync::Arc;
+
+pub mod audio_dump;
 
 #[cfg(not(target_os = "android"))]
 mod cpal_audio;
 
+pub mod diagnostics;
+
+mod downmix;
+
+mod drift_control;
+
+pub mod fade_curve;
+
+pub mod mic_processing;
+
+pub mod mixer;
+
+pub mod opus_codec;
+
 #[cfg(target_os = "linux")]
 mod pipewire_audio;
 
+pub mod resampler;
+
+pub mod ring_buffer;
+
+pub use ring_buffer::SampleRing;
+
 #[derive(Serialize)]
 pub struct AudioDevicesList {
     pub output: Vec<String>,
@@ -120,48 +141,149 @@ pub fn get_devices_list(
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn record_audio_loop(
     device: AudioDevice,
     channels_count: u16,
     mute: bool,
     sender: StreamSender<()>,
+    codec: opus_codec::AudioCodecConfig,
+    resample_quality: resampler::ResampleQuality,
+    mic_processing_config: mic_processing::MicProcessingConfig,
+    mic_monitor_enabled: bool,
+    audio_dump_config: audio_dump::AudioDumpConfig,
 ) -> StrResult {
     #[allow(unused_variables)]
-    let (channels_count, mute, sender) = (channels_count, mute, sender);
+    let (
+        channels_count,
+        mute,
+        sender,
+        codec,
+        resample_quality,
+        mic_processing_config,
+        mic_monitor_enabled,
+        audio_dump_config,
+    ) = (
+        channels_count,
+        mute,
+        sender,
+        codec,
+        resample_quality,
+        mic_processing_config,
+        mic_monitor_enabled,
+        audio_dump_config,
+    );
 
     match device {
         #[cfg(not(target_os = "android"))]
         AudioDevice::Cpal(d) => {
-            cpal_audio::record_audio_loop(d, channels_count, mute, sender).await
+            cpal_audio::record_audio_loop(
+                d,
+                channels_count,
+                mute,
+                sender,
+                codec,
+                resample_quality,
+                mic_processing_config,
+                mic_monitor_enabled,
+                audio_dump_config,
+            )
+            .await
         }
+        // Mic monitoring isn't implemented for the PipeWire backend yet; `record_audio_loop`
+        // simply never feeds `mixer::mic_monitor_ring()` on this path. Audio dumping is wired in
+        // the same way as cpal_audio's, though.
         #[cfg(target_os = "linux")]
         AudioDevice::PipeWire(d) => {
-            pipewire_audio::record_audio_loop(d, channels_count, mute, sender).await
+            pipewire_audio::record_audio_loop(
+                d,
+                channels_count,
+                mute,
+                sender,
+                codec,
+                audio_dump_config,
+            )
+            .await
         }
         #[cfg(target_os = "android")]
         AudioDevice::None => std::future::pending().await,
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn play_audio_loop(
     device: AudioDevice,
     channels_count: u16,
     sample_rate: u32,
     config: alvr_session::AudioConfig,
     receiver: StreamReceiver<()>,
+    codec: opus_codec::AudioCodecConfig,
+    fade_curve_kind: fade_curve::FadeCurveKind,
+    resample_quality: resampler::ResampleQuality,
+    mic_monitor_gain: Option<f32>,
+    control_sender: Option<tokio::sync::mpsc::UnboundedSender<alvr_sockets::ClientControlPacket>>,
+    audio_dump_config: audio_dump::AudioDumpConfig,
 ) -> StrResult {
     #[allow(unused_variables)]
-    let (channels_count, sample_rate, config, receiver) =
-        (channels_count, sample_rate, config, receiver);
+    let (
+        channels_count,
+        sample_rate,
+        config,
+        receiver,
+        codec,
+        fade_curve_kind,
+        resample_quality,
+        mic_monitor_gain,
+        control_sender,
+        audio_dump_config,
+    ) = (
+        channels_count,
+        sample_rate,
+        config,
+        receiver,
+        codec,
+        fade_curve_kind,
+        resample_quality,
+        mic_monitor_gain,
+        control_sender,
+        audio_dump_config,
+    );
 
     match device {
         #[cfg(not(target_os = "android"))]
         AudioDevice::Cpal(d) => {
-            cpal_audio::play_audio_loop(d, channels_count, sample_rate, config, receiver).await
+            cpal_audio::play_audio_loop(
+                d,
+                channels_count,
+                sample_rate,
+                config,
+                receiver,
+                codec,
+                fade_curve_kind,
+                resample_quality,
+                mic_monitor_gain,
+                control_sender,
+                audio_dump_config,
+            )
+            .await
         }
+        // Mic monitoring isn't implemented for the PipeWire backend yet; see the matching note on
+        // `record_audio_loop` above. Audio dumping is wired in the same way as cpal_audio's.
         #[cfg(target_os = "linux")]
         AudioDevice::PipeWire(d) => {
-            pipewire_audio::play_audio_loop(d, channels_count, sample_rate, config, receiver).await
+            pipewire_audio::play_audio_loop(
+                d,
+                channels_count,
+                sample_rate,
+                config,
+                receiver,
+                codec,
+                fade_curve_kind,
+                resample_quality,
+                control_sender,
+                audio_dump_config,
+            )
+            .await
         }
         #[cfg(target_os = "android")]
         AudioDevice::None => std::future::pending().await,
@@ -207,132 +329,263 @@ impl ToF32 for i16 {
     }
 }
 
-// Audio callback. This is designed to be as less complex as possible. Still, when needed, this
-// callback can render a fade-out autonomously.
+// Audio callback. This is designed to be as less complex as possible: a single lock-free copy out
+// of `sample_buffer` followed by an atomic read-index bump, so a slow network receive can never
+// make this callback wait.
 #[inline]
 pub fn get_next_frame_batch(
-    sample_buffer: &mut VecDeque<f32>,
+    sample_buffer: &SampleRing,
     channels_count: usize,
     batch_frames_count: usize,
     output_buffer: &mut Vec<f32>,
 ) {
-    output_buffer.clear();
+    output_buffer.resize(batch_frames_count * channels_count, 0.);
 
-    if sample_buffer.len() / channels_count >= batch_frames_count {
-        output_buffer.extend(sample_buffer.drain(0..batch_frames_count * channels_count));
+    let available = sample_buffer.pop_into(output_buffer);
+    if available < output_buffer.len() {
         // fade-ins and cross-fades are rendered in the receive loop directly inside sample_buffer.
+        diagnostics::record_underrun();
     } else {
-        output_buffer.resize(batch_frames_count * channels_count, 0.);
+        diagnostics::record_frame_batch_ok();
     }
 }
 
+/// Capacity (in samples, pre-power-of-two-rounding) for the playback `SampleRing` shared between
+/// `receive_samples_loop` (producer) and `get_next_frame_batch` (consumer). Generous headroom over
+/// the overflow threshold (`2 * average + 1 batch`, see the overflow branch in
+/// `receive_samples_loop`) used here so a legitimate backlog never gets silently truncated by the
+/// ring itself; shared by every backend (`cpal_audio`, `pipewire_audio`) so they can't drift apart.
+pub fn playback_ring_capacity(
+    average_buffer_frames_count: usize,
+    batch_frames_count: usize,
+    channels_count: usize,
+) -> usize {
+    (4 * average_buffer_frames_count + 4 * batch_frames_count) * channels_count
+}
+
 // The receive loop is resposible for ensuring smooth transitions in case of disruptions (buffer
 // underflow, overflow, packet loss). In case the computation takes too much time, the audio
 // callback will gracefully handle an interruption, and the callback timing and sound wave
 // continuity will not be affected.
+//
+// `input_sample_rate` is the rate the incoming packets were encoded at (the server's audio
+// rate); `output_sample_rate` is the rate `sample_buffer` is consumed at (the playback device's
+// native rate), read fresh each iteration so a backend that renegotiates mid-session (PipeWire
+// reconnecting after device invalidation, see `pipewire_audio`'s reconnect loop) can change it out
+// from under us; `resampler` is rebuilt whenever it does. Incoming samples always pass through a
+// `resampler::ChannelResampler`, even when the two rates nominally match: the server's PCM source
+// clock and the client DAC clock still drift apart slowly in practice, and a
+// `drift_control::DriftController` continuously nudges the resampler's rate ratio by a fraction of
+// a percent to keep `sample_buffer`'s fill level near `average_buffer_frames_count`, so playback
+// can run indefinitely without the buffer eventually draining or overflowing into an audible
+// resync.
+//
+// Isolated underruns are jitter and get papered over by `get_next_frame_batch` emitting silence
+// for a callback or two, but `diagnostics::sustained_starvation()` (several in a row, AudioFlinger
+// style) means the network genuinely can't keep up: this loop then does a controlled refill,
+// accumulating in `recovery_sample_buffer` up to the dynamic target before resuming, the same way
+// it already does after packet loss, rather than cross-fading back in every single callback. Each
+// sustained-starvation refill also biases the dynamic target upward via
+// `diagnostics::bias_buffer_target_up`, so a client stuck on a flaky link settles at a bigger
+// cushion instead of repeating the same recovery forever.
+//
+// `codec` selects how `packet.buffer` is decoded before any of the above; see
+// `opus_codec::AudioCodecConfig`'s doc comment for why it isn't sourced from
+// `alvr_session::AudioConfig` yet. `fade_curve_kind` selects the rise/fall pair the same way; see
+// `fade_curve::FadeCurveKind`'s doc comment. `resample_quality` picks the `resampler`'s filter
+// tier the same way again; see `resampler::ResampleQuality`'s doc comment.
+//
+// `control_sender`, when given, is handed to `diagnostics::maybe_report_stats` once per packet so
+// buffer health gets surfaced as a `ClientControlPacket::AudioStats` over the control channel.
+// With `None` no stats are sent; `alxr_common`'s audio loops always pass `None`.
+#[allow(clippy::too_many_arguments)]
 pub async fn receive_samples_loop(
     mut receiver: StreamReceiver<()>,
-    sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+    sample_buffer: Arc<SampleRing>,
     channels_count: usize,
     batch_frames_count: usize,
     average_buffer_frames_count: usize,
+    input_sample_rate: u32,
+    output_sample_rate: Arc<std::sync::atomic::AtomicU32>,
+    codec: opus_codec::AudioCodecConfig,
+    fade_curve_kind: fade_curve::FadeCurveKind,
+    resample_quality: resampler::ResampleQuality,
+    control_sender: Option<tokio::sync::mpsc::UnboundedSender<alvr_sockets::ClientControlPacket>>,
 ) -> StrResult {
     // Pre-allocate for cross-fade operations (batch_frames_count * channels_count samples)
     let mut recovery_sample_buffer = Vec::with_capacity(batch_frames_count * channels_count);
+
+    let fade_in_curve = fade_curve_kind.rise();
+    let fade_out_curve = fade_curve_kind.fall();
+
+    let mut current_output_sample_rate =
+        output_sample_rate.load(std::sync::atomic::Ordering::Relaxed);
+
+    let mut resampler = resampler::ChannelResampler::with_quality(
+        channels_count,
+        input_sample_rate,
+        current_output_sample_rate,
+        resample_quality,
+    );
+    let mut drift_controller = drift_control::DriftController::new(average_buffer_frames_count);
+
+    // `None` when `codec` is `Pcm`, in which case `decode` below falls back to the original raw
+    // i16 interleaved decode.
+    let mut opus_decoder = match codec {
+        opus_codec::AudioCodecConfig::Opus(opus_config) => Some(opus_codec::Decoder::new(
+            channels_count,
+            input_sample_rate,
+            opus_config,
+        )?),
+        opus_codec::AudioCodecConfig::Pcm => None,
+    };
+
+    let mut decode = move |buffer: &[u8]| -> StrResult<Vec<f32>> {
+        if let Some(decoder) = &mut opus_decoder {
+            decoder.decode(Some(buffer))
+        } else {
+            Ok(buffer
+                .chunks_exact(2)
+                .map(|c| i16::from_ne_bytes([c[0], c[1]]).to_f32())
+                .collect())
+        }
+    };
+
     loop {
         let packet = receiver.recv().await?;
-        let mut sample_buffer_ref = sample_buffer.lock();
 
+        // Picked up a renegotiated rate (e.g. `pipewire_audio` reconnected onto a device running
+        // at a different native rate): rebuild the resampler rather than let its output drift out
+        // of tune with the actual device clock.
+        let new_output_sample_rate = output_sample_rate.load(std::sync::atomic::Ordering::Relaxed);
+        if new_output_sample_rate != 0 && new_output_sample_rate != current_output_sample_rate {
+            info!(
+                "Audio output rate changed {current_output_sample_rate} -> {new_output_sample_rate} Hz, rebuilding resampler"
+            );
+            current_output_sample_rate = new_output_sample_rate;
+            resampler = resampler::ChannelResampler::with_quality(
+                channels_count,
+                input_sample_rate,
+                current_output_sample_rate,
+                resample_quality,
+            );
+        }
+
+        // Re-read every iteration: `bias_buffer_target_up` can grow this between packets as
+        // sustained-starvation recoveries accumulate.
+        let effective_target_frames =
+            average_buffer_frames_count + diagnostics::buffer_target_bias_frames();
+        let starving = diagnostics::sustained_starvation();
+
+        // All of the bookkeeping below runs only on the producer side: it reads back the ring's
+        // own not-yet-consumed tail (via `peek_front`/`drain_into`) into `recovery_sample_buffer`,
+        // a private pre-mix scratch region, renders the fade/cross-fade there, and only then
+        // publishes the finished result with `push`/`overwrite_front`. The consumer
+        // (`get_next_frame_batch`) never sees any of this; it only ever calls `pop_into`.
         if packet.had_packet_loss {
             info!("Audio packet loss!");
+            diagnostics::record_packet_loss();
 
-            if sample_buffer_ref.len() / channels_count < batch_frames_count {
-                sample_buffer_ref.clear();
+            if sample_buffer.len() / channels_count < batch_frames_count {
+                sample_buffer.clear();
             } else {
                 // clear remaining samples
-                sample_buffer_ref.drain(batch_frames_count * channels_count..);
+                sample_buffer.truncate_published(batch_frames_count * channels_count);
             }
 
             recovery_sample_buffer.clear();
         }
 
-        if sample_buffer_ref.len() / channels_count < batch_frames_count {
-            recovery_sample_buffer.extend(sample_buffer_ref.drain(..));
+        if sample_buffer.len() / channels_count < batch_frames_count {
+            sample_buffer.drain_into(&mut recovery_sample_buffer);
         }
 
-        if sample_buffer_ref.len() == 0 || packet.had_packet_loss {
-            recovery_sample_buffer.extend(
-                packet
-                    .buffer
-                    .chunks_exact(2)
-                    .map(|c| i16::from_ne_bytes([c[0], c[1]]).to_f32()),
-            );
+        if sample_buffer.is_empty() || packet.had_packet_loss || starving {
+            recovery_sample_buffer
+                .extend(resampler.process_interleaved(&decode(&packet.buffer)?));
 
             if recovery_sample_buffer.len() / channels_count
-                > average_buffer_frames_count + batch_frames_count
+                > effective_target_frames + batch_frames_count
             {
                 // Fade-in
                 for f in 0..batch_frames_count {
-                    let volume = f as f32 / batch_frames_count as f32;
+                    let gain = fade_in_curve.eval(f as f32 / batch_frames_count as f32);
                     for c in 0..channels_count {
-                        recovery_sample_buffer[f * channels_count + c] *= volume;
+                        recovery_sample_buffer[f * channels_count + c] *= gain;
                     }
                 }
 
                 if packet.had_packet_loss
-                    && sample_buffer_ref.len() / channels_count == batch_frames_count
+                    && sample_buffer.len() / channels_count == batch_frames_count
                 {
-                    // Add a fade-out to make a cross-fade.
+                    // Add a fade-out to make a cross-fade against the still-unconsumed tail.
+                    let mut tail = vec![0.; batch_frames_count * channels_count];
+                    sample_buffer.peek_front(&mut tail);
                     for f in 0..batch_frames_count {
-                        let volume = 1. - f as f32 / batch_frames_count as f32;
+                        let gain = fade_out_curve.eval(f as f32 / batch_frames_count as f32);
                         for c in 0..channels_count {
                             recovery_sample_buffer[f * channels_count + c] +=
-                                sample_buffer_ref[f * channels_count + c] * volume;
+                                tail[f * channels_count + c] * gain;
                         }
                     }
 
-                    sample_buffer_ref.clear();
+                    sample_buffer.clear();
                 }
 
-                sample_buffer_ref.extend(recovery_sample_buffer.drain(..));
+                sample_buffer.push(&recovery_sample_buffer);
+                recovery_sample_buffer.clear();
+                diagnostics::record_cross_fade();
                 info!("Audio recovered");
+
+                if starving {
+                    // The refill still wasn't enough headroom to keep up with this link; lean on
+                    // a bigger cushion next time instead of hitting the same recovery again.
+                    diagnostics::bias_buffer_target_up(batch_frames_count);
+                }
             }
         } else {
-            sample_buffer_ref.extend(
-                packet
-                    .buffer
-                    .chunks_exact(2)
-                    .map(|c| i16::from_ne_bytes([c[0], c[1]]).to_f32()),
-            );
+            let decoded = resampler.process_interleaved(&decode(&packet.buffer)?);
+            sample_buffer.push(&decoded);
         }
 
         // todo: use smarter policy with EventTiming
-        let buffer_frames_size = sample_buffer_ref.len() / channels_count;
-        if buffer_frames_size > 2 * average_buffer_frames_count + batch_frames_count {
+        let buffer_frames_size = sample_buffer.len() / channels_count;
+        diagnostics::record_buffer_size(buffer_frames_size);
+        diagnostics::maybe_report_stats(control_sender.as_ref(), buffer_frames_size);
+
+        // Slowly correct for server/client clock drift by nudging the resampler's rate ratio
+        // towards whatever keeps the buffer hovering around its target fill level.
+        let drift_correction = drift_controller.update(buffer_frames_size);
+        resampler.set_rate_correction(drift_correction);
+
+        if buffer_frames_size > 2 * effective_target_frames + batch_frames_count {
             info!("Audio buffer overflow! size: {buffer_frames_size}");
 
             // Ensure we keep at least batch_frames_count for the cross-fade
-            let target_frames = average_buffer_frames_count.max(batch_frames_count);
+            let target_frames = effective_target_frames.max(batch_frames_count);
             let drain_count = (buffer_frames_size - target_frames) * channels_count;
+            diagnostics::record_overflow(drain_count);
+
             recovery_sample_buffer.clear();
-            recovery_sample_buffer.extend(
-                sample_buffer_ref
-                    .iter()
-                    .take(batch_frames_count * channels_count)
-                    .copied(),
-            );
+            recovery_sample_buffer.resize(batch_frames_count * channels_count, 0.);
+            sample_buffer.peek_front(&mut recovery_sample_buffer);
 
-            sample_buffer_ref.drain(0..drain_count);
+            sample_buffer.discard_front(drain_count);
 
-            // Render a cross-fade.
+            // Render a cross-fade between the old (about to be dropped) and new front batches.
+            let mut new_front = vec![0.; batch_frames_count * channels_count];
+            sample_buffer.peek_front(&mut new_front);
             for f in 0..batch_frames_count {
-                let volume = f as f32 / batch_frames_count as f32;
+                let t = f as f32 / batch_frames_count as f32;
+                let (rise_gain, fall_gain) = (fade_in_curve.eval(t), fade_out_curve.eval(t));
                 for c in 0..channels_count {
                     let index = f * channels_count + c;
-                    sample_buffer_ref[index] = sample_buffer_ref[index] * volume
-                        + recovery_sample_buffer[index] * (1. - volume);
+                    new_front[index] =
+                        new_front[index] * rise_gain + recovery_sample_buffer[index] * fall_gain;
                 }
             }
+            sample_buffer.overwrite_front(&new_front);
+            diagnostics::record_cross_fade();
         }
     }
 }
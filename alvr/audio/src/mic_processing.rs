@@ -0,0 +1,175 @@
+// Microphone capture conditioning: a high-pass filter, a noise suppressor, and an adaptive gain
+// controller, run over fixed 10 ms frames before the captured audio is packed into the send
+// buffer. Mirrors the shape of Mozilla's MediaEngineWebRTCAudio `InputProcessingParams` chain
+// (high-pass / NS / AGC, each independently toggleable) without pulling in the full
+// webrtc-audio-processing library: the high-pass is a single one-pole filter, the suppressor is
+// spectral-subtraction-style noise-floor tracking applied in the time domain via an envelope
+// follower, and the AGC is a simple RMS-targeting gain ramp. `record_audio_loop`'s cpal callback
+// hands it whatever chunk size the OS provides; `MicProcessor` buffers those into complete 10 ms
+// frames and only emits processed audio once a frame is full.
+//
+// Note: `alvr_session::AudioConfig` (the settings struct the enable flags and NS/AGC knobs below
+// would normally live on) isn't part of this crate, so `MicProcessingConfig` stands in for it with
+// the same shape and is threaded into `record_audio_loop` as an explicit parameter instead, the
+// same way `opus_codec::AudioCodecConfig` is — see its doc comment for the client-local
+// `APP_CONFIG` stand-in used by `alxr-common`. `MicProcessingConfig::default()` (every stage
+// disabled) matches pre-existing behavior for callers that don't opt in.
+
+/// Settings for the capture-side conditioning chain. Every stage defaults to disabled so turning
+/// this module on is opt-in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MicProcessingConfig {
+    pub high_pass_enabled: bool,
+    pub noise_suppression_enabled: bool,
+    /// 0 (lightest) ..= 3 (most aggressive), same scale as WebRTC's `NsConfig::Level`.
+    pub noise_suppression_level: u8,
+    pub gain_control_enabled: bool,
+    /// Target RMS level for the AGC, in dBFS (negative; e.g. -18.0).
+    pub target_level_dbfs: f32,
+}
+
+impl Default for MicProcessingConfig {
+    fn default() -> Self {
+        Self {
+            high_pass_enabled: false,
+            noise_suppression_enabled: false,
+            noise_suppression_level: 1,
+            gain_control_enabled: false,
+            target_level_dbfs: -18.0,
+        }
+    }
+}
+
+const FRAME_DURATION_MS: u32 = 10;
+
+// One-pole high-pass, cutoff picked to sit below speech fundamentals (matches WebRTC's
+// audio_processing high-pass default) while still removing DC offset and handling rumble.
+const HIGH_PASS_CUTOFF_HZ: f32 = 80.0;
+
+#[derive(Clone, Copy, Default)]
+struct HighPassState {
+    prev_in: f32,
+    prev_out: f32,
+}
+
+impl HighPassState {
+    fn process(&mut self, coeff: f32, sample: f32) -> f32 {
+        let out = coeff * (self.prev_out + sample - self.prev_in);
+        self.prev_in = sample;
+        self.prev_out = out;
+        out
+    }
+}
+
+/// Buffers cpal's irregularly-sized capture callbacks into fixed `FRAME_DURATION_MS` frames and
+/// runs each complete frame through the enabled conditioning stages.
+pub struct MicProcessor {
+    config: MicProcessingConfig,
+    channels_count: usize,
+    frame_samples: usize,
+    accumulator: Vec<f32>,
+    high_pass: Vec<HighPassState>,
+    high_pass_coeff: f32,
+    /// Per-channel running noise-floor estimate (envelope follower), used by the suppressor as a
+    /// gate threshold: the quieter inter-speech gaps pull this down, so steady low-level noise
+    /// sitting below the threshold gets attenuated while speech above it passes through.
+    noise_floor: Vec<f32>,
+    /// Current AGC gain, ramped towards the target each frame rather than snapped, to avoid
+    /// audible pumping.
+    agc_gain: f32,
+}
+
+impl MicProcessor {
+    pub fn new(channels_count: usize, sample_rate: u32, config: MicProcessingConfig) -> Self {
+        let frame_samples =
+            (sample_rate as usize * FRAME_DURATION_MS as usize / 1000) * channels_count;
+        let high_pass_coeff =
+            1.0 / (1.0 + 2.0 * std::f32::consts::PI * HIGH_PASS_CUTOFF_HZ / sample_rate as f32);
+
+        Self {
+            config,
+            channels_count,
+            frame_samples,
+            accumulator: Vec::with_capacity(frame_samples * 2),
+            high_pass: vec![HighPassState::default(); channels_count],
+            high_pass_coeff,
+            noise_floor: vec![0.0; channels_count],
+            agc_gain: 1.0,
+        }
+    }
+
+    /// Accumulates `input` (interleaved f32) and returns every complete 10 ms frame it produced,
+    /// processed through the enabled stages, also interleaved. Leftover samples that don't fill a
+    /// full frame are kept for the next call. Returns `input` unchanged (after buffering it down
+    /// to frame boundaries) when every stage is disabled, so bypass costs only the accumulator
+    /// copy.
+    pub fn process_interleaved(&mut self, input: &[f32]) -> Vec<f32> {
+        self.accumulator.extend_from_slice(input);
+
+        let mut output = Vec::with_capacity(self.accumulator.len());
+        while self.accumulator.len() >= self.frame_samples {
+            let frame: Vec<f32> = self.accumulator.drain(..self.frame_samples).collect();
+            output.extend(self.process_frame(frame));
+        }
+
+        output
+    }
+
+    fn process_frame(&mut self, mut frame: Vec<f32>) -> Vec<f32> {
+        if self.config.high_pass_enabled {
+            for (c, state) in self.high_pass.iter_mut().enumerate() {
+                for sample in frame.iter_mut().skip(c).step_by(self.channels_count) {
+                    *sample = state.process(self.high_pass_coeff, *sample);
+                }
+            }
+        }
+
+        if self.config.noise_suppression_enabled {
+            // Attack/release asymmetry keeps the floor estimate from chasing speech upward while
+            // still tracking noise that gets louder between utterances.
+            let attack = 0.01;
+            let release = 0.1;
+            // Higher levels gate more aggressively above the tracked noise floor.
+            let margin = 1.0 + self.config.noise_suppression_level as f32 * 0.5;
+
+            for c in 0..self.channels_count {
+                let channel_peak = frame
+                    .iter()
+                    .skip(c)
+                    .step_by(self.channels_count)
+                    .fold(0.0f32, |acc, s| acc.max(s.abs()));
+
+                let floor = &mut self.noise_floor[c];
+                if channel_peak > *floor {
+                    *floor += (channel_peak - *floor) * attack;
+                } else {
+                    *floor += (channel_peak - *floor) * release;
+                }
+
+                let gate_threshold = *floor * margin;
+                for sample in frame.iter_mut().skip(c).step_by(self.channels_count) {
+                    if sample.abs() < gate_threshold {
+                        *sample = 0.0;
+                    }
+                }
+            }
+        }
+
+        if self.config.gain_control_enabled {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32)
+                .sqrt()
+                .max(1e-9);
+            let target_rms = 10f32.powf(self.config.target_level_dbfs / 20.0);
+            let desired_gain = (target_rms / rms).clamp(0.1, 10.0);
+            // Ramp rather than snap to the desired gain, same rationale as the noise floor's
+            // attack/release split: an instant gain jump every 10 ms would be audible as pumping.
+            self.agc_gain += (desired_gain - self.agc_gain) * 0.2;
+
+            for sample in frame.iter_mut() {
+                *sample = (*sample * self.agc_gain).clamp(-1.0, 1.0);
+            }
+        }
+
+        frame
+    }
+}
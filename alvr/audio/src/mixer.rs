@@ -0,0 +1,125 @@
+// Multi-track audio mixer: sums several independently-fed sample buffers (the server's
+// game-audio stream plus a locally-monitored microphone) down to the single interleaved buffer
+// the real output device reads from. Each `MixerTrack` is fed the same way `receive_samples_loop`
+// already feeds a single `sample_buffer` today; a track that doesn't have enough frames buffered
+// for the next batch contributes silence (via `get_next_frame_batch`'s own underrun handling)
+// instead of stalling the rest of the mix. `cpal_audio::play_audio_loop` wires a "game_audio"
+// track directly onto its own `sample_buffer` plus an optional "mic_monitor" track onto
+// `mic_monitor_ring()`, when mic monitoring is enabled; see that ring's doc comment.
+
+use alvr_common::lazy_static;
+use crate::{get_next_frame_batch, SampleRing};
+use std::sync::Arc;
+
+lazy_static! {
+    // `cpal_audio::record_audio_loop` (producer, when mic monitoring is enabled) and
+    // `cpal_audio::play_audio_loop` (consumer, via a "mic_monitor" `MixerTrack`) are spawned as
+    // independent tasks with no direct channel between them (see `alxr-common::audio`'s
+    // `record_audio_loop`/`play_audio_loop`, each its own free function) - this is the same
+    // process-wide hand-off `alxr_common::PEER_RESERVED` uses for the analogous Opus-negotiation
+    // problem. Mono and fixed-size: the mic capture path is always single-channel, and
+    // `Mixer::next_batch`'s up-mix already handles fanning a mono track out to a stereo output.
+    static ref MIC_MONITOR_RING: Arc<SampleRing> = Arc::new(SampleRing::new(2 * 48_000));
+}
+
+/// The shared ring `record_audio_loop` pushes conditioned mic samples into and `play_audio_loop`
+/// mixes into the game-audio output, when mic monitoring (sidetone) is enabled. See
+/// `MIC_MONITOR_RING`'s doc comment for why this is a process-wide singleton instead of a
+/// parameter threaded between the two loops.
+pub fn mic_monitor_ring() -> Arc<SampleRing> {
+    Arc::clone(&MIC_MONITOR_RING)
+}
+
+/// A single named input to the `Mixer`. `sample_buffer` is meant to be shared with a
+/// `receive_samples_loop` (or any other producer) the same way `play_audio_loop` wires one up
+/// today; `gain` is applied while accumulating this track into the mix.
+pub struct MixerTrack {
+    pub name: String,
+    pub sample_buffer: Arc<SampleRing>,
+    pub channels_count: usize,
+    pub gain: f32,
+}
+
+impl MixerTrack {
+    pub fn new(
+        name: impl Into<String>,
+        channels_count: usize,
+        ring_capacity: usize,
+        gain: f32,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            sample_buffer: Arc::new(SampleRing::new(ring_capacity)),
+            channels_count,
+            gain,
+        }
+    }
+}
+
+/// Mixes several `MixerTrack`s down to a single interleaved output of `output_channels_count`
+/// channels, frame-accurately: every track is drained for exactly `batch_frames_count` frames
+/// per call, scaled by its own gain, and summed, with the final sum clamped to `[-1.0, 1.0]` to
+/// avoid clipping artifacts when several tracks are loud at once.
+pub struct Mixer {
+    tracks: Vec<MixerTrack>,
+    output_channels_count: usize,
+}
+
+impl Mixer {
+    pub fn new(output_channels_count: usize) -> Self {
+        Self {
+            tracks: Vec::new(),
+            output_channels_count,
+        }
+    }
+
+    pub fn add_track(&mut self, track: MixerTrack) {
+        self.tracks.push(track);
+    }
+
+    pub fn remove_track(&mut self, name: &str) {
+        self.tracks.retain(|track| track.name != name);
+    }
+
+    pub fn track(&self, name: &str) -> Option<&MixerTrack> {
+        self.tracks.iter().find(|track| track.name == name)
+    }
+
+    pub fn set_gain(&mut self, name: &str, gain: f32) {
+        if let Some(track) = self.tracks.iter_mut().find(|track| track.name == name) {
+            track.gain = gain;
+        }
+    }
+
+    /// Pulls the next `batch_frames_count`-frame batch from every track, mixes them down to
+    /// `output_channels_count` channels, and writes the clamped result into `output_buffer`.
+    /// A track whose channel count doesn't match `output_channels_count` is centered (mono
+    /// contributes equally to every output channel; extra channels beyond the output's are
+    /// dropped rather than averaged in, unlike the `DownmixMatrix` that `record_audio_loop` uses).
+    pub fn next_batch(&self, batch_frames_count: usize, output_buffer: &mut Vec<f32>) {
+        output_buffer.clear();
+        output_buffer.resize(batch_frames_count * self.output_channels_count, 0.0);
+
+        let mut track_batch = Vec::new();
+        for track in &self.tracks {
+            get_next_frame_batch(
+                &track.sample_buffer,
+                track.channels_count,
+                batch_frames_count,
+                &mut track_batch,
+            );
+
+            for f in 0..batch_frames_count {
+                for c in 0..self.output_channels_count {
+                    let src_c = c.min(track.channels_count - 1);
+                    output_buffer[f * self.output_channels_count + c] +=
+                        track_batch[f * track.channels_count + src_c] * track.gain;
+                }
+            }
+        }
+
+        for sample in output_buffer.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// Optional Opus compression layer for the audio streams, so mic/game-audio packets don't have to
+// ship raw i16/f32 PCM over the congested link the video stream also uses. `receive_samples_loop`
+// decodes game audio with this before it ever reaches the resampler; the per-backend
+// `record_audio_loop`s encode mic audio with it right before `send_buffer`.
+//
+// Note: `alvr_session::AudioConfig` (the settings struct frame size/bitrate/application would
+// normally live on) isn't part of this crate, so `AudioCodecConfig` can't be threaded through
+// settings yet. Every call site defaults to `AudioCodecConfig::Pcm`, and the client only switches
+// a direction over to `AudioCodecConfig::Opus` when both the local `Options`/`APP_CONFIG` flag is
+// on AND the peer's handshake response advertised support for it (see `alxr-common::audio`'s
+// `mic_codec_config`/`game_audio_codec_config` and `alxr_sockets::OPUS_AUDIO_FLAG`) — enabling
+// this on only one side falls back to PCM for that direction instead of shipping frames the peer
+// can't decode.
+
+use alvr_common::prelude::*;
+
+/// Converts interleaved 16-bit little-endian PCM (the wire format every backend's capture thread
+/// already produces) to interleaved `f32`, the common currency the rest of `alvr_audio` and the
+/// `Encoder`/`FrameBatcher` below operate on.
+pub fn pcm_s16le_to_f32(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(2)
+        .map(|c| i16::from_ne_bytes([c[0], c[1]]) as f32 / i16::MAX as f32)
+        .collect()
+}
+
+/// Mirrors libopus's `OPUS_APPLICATION_*` modes: each tunes the encoder's internal tradeoffs for
+/// a different kind of source material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpusApplication {
+    /// `OPUS_APPLICATION_VOIP`: tuned for speech, for the mic capture path.
+    VoIp,
+    /// `OPUS_APPLICATION_AUDIO`: tuned for music/general sound, for the game audio path.
+    Audio,
+}
+
+impl OpusApplication {
+    fn to_opus(self) -> opus::Application {
+        match self {
+            Self::VoIp => opus::Application::Voip,
+            Self::Audio => opus::Application::Audio,
+        }
+    }
+}
+
+/// Frame size, bitrate and application mode for one direction of the audio stream. See this
+/// module's doc comment for why this isn't (yet) a field on `alvr_session::AudioConfig`.
+#[derive(Clone, Copy, Debug)]
+pub struct OpusCodecConfig {
+    pub frame_ms: u32,
+    pub bitrate: i32,
+    pub application: OpusApplication,
+}
+
+impl OpusCodecConfig {
+    /// 20ms frames at a conservative speech bitrate, for the mic capture path.
+    pub fn voip_default() -> Self {
+        Self {
+            frame_ms: 20,
+            bitrate: 24_000,
+            application: OpusApplication::VoIp,
+        }
+    }
+
+    /// 20ms frames at a bitrate that keeps game audio transparent, for the game audio path.
+    pub fn game_audio_default() -> Self {
+        Self {
+            frame_ms: 20,
+            bitrate: 96_000,
+            application: OpusApplication::Audio,
+        }
+    }
+
+    fn frame_size(&self, sample_rate: u32) -> usize {
+        sample_rate as usize * self.frame_ms as usize / 1000
+    }
+}
+
+/// Either direction can be independently compressed or left as raw interleaved PCM; see this
+/// module's doc comment for why the choice isn't threaded through settings yet.
+#[derive(Clone, Copy, Debug)]
+pub enum AudioCodecConfig {
+    Pcm,
+    Opus(OpusCodecConfig),
+}
+
+impl Default for AudioCodecConfig {
+    fn default() -> Self {
+        Self::Pcm
+    }
+}
+
+/// Encodes interleaved `f32` samples into Opus packets, one `frame_size` batch at a time.
+pub struct Encoder {
+    inner: opus::Encoder,
+    channels_count: usize,
+    frame_size: usize,
+    scratch: Vec<i16>,
+}
+
+impl Encoder {
+    pub fn new(channels_count: usize, sample_rate: u32, config: OpusCodecConfig) -> StrResult<Self> {
+        let channels = match channels_count {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            _ => return fmt_e!("Opus only supports mono or stereo, got {channels_count} channels"),
+        };
+
+        let mut inner = trace_err!(opus::Encoder::new(sample_rate, channels, config.application.to_opus()))?;
+        trace_err!(inner.set_bitrate(opus::Bitrate::Bits(config.bitrate)))?;
+
+        Ok(Self {
+            inner,
+            channels_count,
+            frame_size: config.frame_size(sample_rate),
+            scratch: Vec::new(),
+        })
+    }
+
+    /// `samples` must contain exactly `frame_size * channels_count` interleaved samples; callers
+    /// batch mic/game audio into fixed-size frames before reaching this (see
+    /// `OpusCodecConfig::frame_size`).
+    pub fn encode(&mut self, samples: &[f32]) -> StrResult<Vec<u8>> {
+        self.scratch.clear();
+        self.scratch
+            .extend(samples.iter().map(|s| (s.clamp(-1., 1.) * i16::MAX as f32) as i16));
+
+        let mut packet = vec![0u8; 4000]; // generous upper bound on a single Opus frame
+        let len = trace_err!(self.inner.encode(&self.scratch, &mut packet))?;
+        packet.truncate(len);
+        Ok(packet)
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    pub fn channels_count(&self) -> usize {
+        self.channels_count
+    }
+}
+
+/// Decodes Opus packets back into interleaved `f32` samples. A missing packet (network loss, or
+/// a decode error on a corrupt one) is fed through as packet-loss concealment instead of falling
+/// back to silence, the same way `receive_samples_loop`'s cross-fade recovery avoids a hard cut.
+pub struct Decoder {
+    inner: opus::Decoder,
+    channels_count: usize,
+    frame_size: usize,
+    scratch: Vec<i16>,
+}
+
+impl Decoder {
+    pub fn new(channels_count: usize, sample_rate: u32, config: OpusCodecConfig) -> StrResult<Self> {
+        let channels = match channels_count {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            _ => return fmt_e!("Opus only supports mono or stereo, got {channels_count} channels"),
+        };
+
+        let inner = trace_err!(opus::Decoder::new(sample_rate, channels))?;
+
+        Ok(Self {
+            inner,
+            channels_count,
+            frame_size: config.frame_size(sample_rate),
+            scratch: Vec::new(),
+        })
+    }
+
+    /// `packet` is `None` when the network dropped it, or `Some` with a received packet, which is
+    /// decoded normally. When it is `None` or fails to decode, this asks libopus for a
+    /// packet-loss-concealment frame instead of writing silence.
+    pub fn decode(&mut self, packet: Option<&[u8]>) -> StrResult<Vec<f32>> {
+        self.scratch.clear();
+        self.scratch
+            .resize(self.frame_size * self.channels_count, 0);
+
+        let decoded_frames = match packet {
+            Some(packet) => match self.inner.decode(packet, &mut self.scratch, false) {
+                Ok(frames) => frames,
+                // A corrupt packet gets the same PLC treatment as a dropped one.
+                Err(_) => trace_err!(self.inner.decode(&[], &mut self.scratch, false))?,
+            },
+            None => trace_err!(self.inner.decode(&[], &mut self.scratch, false))?,
+        };
+
+        self.scratch.truncate(decoded_frames * self.channels_count);
+        Ok(self
+            .scratch
+            .iter()
+            .map(|&s| s as f32 / i16::MAX as f32)
+            .collect())
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+}
+
+/// Accumulates arbitrarily-sized PCM chunks (cpal/PipeWire hand capture callbacks whatever size
+/// the OS gives them) into fixed `frame_samples`-long frames, since unlike the raw-PCM path the
+/// `Encoder` needs one exact frame size per packet.
+pub struct FrameBatcher {
+    scratch: Vec<f32>,
+    frame_samples: usize,
+}
+
+impl FrameBatcher {
+    pub fn new(frame_samples: usize) -> Self {
+        Self {
+            scratch: Vec::with_capacity(frame_samples * 2),
+            frame_samples,
+        }
+    }
+
+    /// Appends `samples` and drains as many complete `frame_samples`-long frames as are now
+    /// available, leaving any remainder buffered for the next call.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        self.scratch.extend_from_slice(samples);
+
+        let mut frames = Vec::new();
+        while self.scratch.len() >= self.frame_samples {
+            frames.push(self.scratch.drain(..self.frame_samples).collect());
+        }
+        frames
+    }
+}
@@ -10,20 +10,33 @@
 //! Communication between the async runtime and PipeWire threads uses:
 //! - `pw::channel` for shutdown signaling (async -> PipeWire)
 //! - `tokio::sync::mpsc` for audio data (PipeWire -> async, capture only)
-//! - `Arc<Mutex<VecDeque<f32>>>` for shared sample buffer (playback only)
-
-use std::{cell::RefCell, collections::VecDeque, io::Cursor, mem, rc::Rc, sync::Arc, thread};
+//! - a lock-free SPSC `SampleRing` for the shared sample buffer (playback only)
+
+use std::{
+    cell::RefCell,
+    io::Cursor,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    thread,
+};
 
 use alvr_common::prelude::*;
 use alvr_session::AudioConfig;
 use alvr_sockets::{StreamReceiver, StreamSender};
-use parking_lot::Mutex;
 use pipewire::{
     self as pw,
     context::ContextRc,
     main_loop::MainLoopRc,
     spa::{
-        param::audio::{AudioFormat, AudioInfoRaw},
+        param::{
+            ParamType,
+            audio::{AudioFormat, AudioInfoRaw},
+            format::{MediaSubtype, MediaType},
+            format_utils,
+        },
         pod::{Object, Pod, Property, Value, serialize::PodSerializer},
         sys::{SPA_PARAM_EnumFormat, SPA_TYPE_OBJECT_Format},
         utils::Direction,
@@ -32,7 +45,10 @@ use pipewire::{
 };
 use tokio::sync::mpsc as tmpsc;
 
-use crate::{AudioDeviceType, AudioDevicesList, get_next_frame_batch, receive_samples_loop};
+use crate::{
+    AudioDeviceType, AudioDevicesList, SampleRing, get_next_frame_batch, opus_codec,
+    receive_samples_loop,
+};
 
 /// Zero-sized shutdown signal sent to PipeWire threads.
 struct Shutdown;
@@ -51,23 +67,103 @@ impl Drop for ShutdownSender {
     }
 }
 
+/// Reported over a status channel back to the async `record_audio_loop`/`play_audio_loop` task so
+/// reconnect activity shows up in logs without the RT thread itself doing any logging I/O.
+enum ReconnectEvent {
+    Reconnecting { attempt: u32 },
+    Recovered,
+    GaveUp,
+}
+
+/// How many consecutive errored `StreamRc`s `run_reconnecting_session` rebuilds before giving up
+/// and returning an error (mirrors cpal's WASAPI path treating `AUDCLNT_E_DEVICE_INVALIDATED` as
+/// recoverable: a sink/source going away transiently, e.g. a hot-plug or default-device switch,
+/// shouldn't permanently kill the backend).
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const RECONNECT_BACKOFF: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Drives a PipeWire stream session that can be torn down and rebuilt in place on error, instead
+/// of the whole backend dying with it.
+///
+/// `build_stream` is called once up front and again for every reconnect attempt; it must return a
+/// connected `StreamRc` together with the listener guard that keeps its callbacks alive, and its
+/// `state_changed` handler must call `mainloop.quit()` on `StreamState::Error` (as every
+/// `state_changed` handler in this module already does) rather than anything fancier — this
+/// function is what decides whether that was a reconnect-worthy error or a real shutdown.
+///
+/// We don't have a finer-grained error classification than `StreamState::Error(String)` to work
+/// with here (no WASAPI-style `AUDCLNT_E_DEVICE_INVALIDATED` equivalent is exposed through this
+/// API), so every error is treated as transient and retried with backoff; only
+/// `MAX_RECONNECT_ATTEMPTS` consecutive failures is treated as fatal. A rebuilt stream that stays
+/// up for at least `RECOVERY_THRESHOLD` is considered to have genuinely recovered (rather than
+/// having failed again immediately), which resets the attempt counter back to zero.
+const RECOVERY_THRESHOLD: std::time::Duration = std::time::Duration::from_secs(5);
+
+fn run_reconnecting_session(
+    mainloop: &MainLoopRc,
+    shutdown_requested: &Rc<std::cell::Cell<bool>>,
+    status_tx: &tmpsc::UnboundedSender<ReconnectEvent>,
+    // The listener guard's concrete type isn't named here (it's whatever `.register()` returns);
+    // `build_stream` only needs to keep it alive for as long as its `StreamRc`, so it's erased.
+    mut build_stream: impl FnMut() -> StrResult<(StreamRc, Box<dyn std::any::Any>)>,
+) -> StrResult {
+    let mut attempt = 0u32;
+    let (mut stream, mut _listener) = build_stream()?;
+    let mut built_at = std::time::Instant::now();
+
+    loop {
+        mainloop.run();
+
+        if shutdown_requested.get() {
+            stream.disconnect().ok();
+            return Ok(());
+        }
+
+        if attempt > 0 && built_at.elapsed() >= RECOVERY_THRESHOLD {
+            let _ = status_tx.send(ReconnectEvent::Recovered);
+            attempt = 0;
+        }
+
+        attempt += 1;
+        if attempt > MAX_RECONNECT_ATTEMPTS {
+            stream.disconnect().ok();
+            let _ = status_tx.send(ReconnectEvent::GaveUp);
+            return fmt_e!(
+                "PipeWire stream failed {MAX_RECONNECT_ATTEMPTS} reconnect attempts in a row, giving up"
+            );
+        }
+
+        let _ = status_tx.send(ReconnectEvent::Reconnecting { attempt });
+        warn!("PipeWire stream error, reconnect attempt {attempt}/{MAX_RECONNECT_ATTEMPTS}");
+        thread::sleep(RECONNECT_BACKOFF);
+
+        (stream, _listener) = build_stream()?;
+        built_at = std::time::Instant::now();
+    }
+}
+
+/// Whether a capture stream should record a real microphone or a sink's monitor (desktop/game
+/// audio loopback), mirroring how `cpal_audio`'s `VirtualMicrophoneInput` is the signal that a
+/// capture is really meant to source from what the PC is playing rather than a physical mic.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CaptureMode {
+    Microphone,
+    Loopback,
+}
+
 /// Represents a PipeWire audio device.
 ///
-/// Note: PipeWire handles actual device routing at the session manager level
-/// (e.g., WirePlumber). This struct stores the requested device identifier,
-/// but actual routing is configured externally via tools like pavucontrol or qpwgraph.
+/// `name` is the `node.name` of a real PipeWire node as returned by `get_devices_list`, or
+/// `"Default"` to let the session manager autoconnect. Connecting is done by setting
+/// `PW_KEY_TARGET_OBJECT` to `name` in the stream properties (see `target_object_props`), so
+/// picking a device from the ALVR UI no longer requires an external tool like pavucontrol.
 pub struct PipeWireAudioDevice {
     name: String,
-    #[allow(dead_code)]
-    device_type: AudioDeviceType,
+    capture_mode: CaptureMode,
 }
 
 impl PipeWireAudioDevice {
     /// Creates a new PipeWire audio device wrapper.
-    ///
-    /// The device ID is stored for reference, but actual device routing is handled
-    /// by PipeWire's session manager. Streams connect to the default device and
-    /// users can reroute via external tools.
     pub fn new(id: alvr_session::AudioDeviceId, device_type: AudioDeviceType) -> StrResult<Self> {
         let name = match id {
             alvr_session::AudioDeviceId::Default => "Default".to_string(),
@@ -75,7 +171,12 @@ impl PipeWireAudioDevice {
             alvr_session::AudioDeviceId::Index(idx) => format!("Device {idx}"),
         };
 
-        Ok(Self { name, device_type })
+        let capture_mode = match device_type {
+            AudioDeviceType::VirtualMicrophoneInput => CaptureMode::Loopback,
+            _ => CaptureMode::Microphone,
+        };
+
+        Ok(Self { name, capture_mode })
     }
 
     /// Returns the device name.
@@ -87,29 +188,168 @@ impl PipeWireAudioDevice {
     pub fn is_same_device(&self, other: &Self) -> bool {
         self.name == other.name
     }
+
+    /// `node.name` to target via `PW_KEY_TARGET_OBJECT`, or `None` to let the session manager
+    /// autoconnect to its default.
+    fn target_node_name(&self) -> Option<&str> {
+        (self.name != "Default").then_some(self.name.as_str())
+    }
+
+    /// Whether `record_audio_loop` should capture a sink's monitor (what it's playing) instead of
+    /// a real microphone source.
+    fn is_loopback(&self) -> bool {
+        self.capture_mode == CaptureMode::Loopback
+    }
+}
+
+/// One `Audio/Source` or `Audio/Sink` node discovered on the PipeWire registry.
+struct PipeWireNodeInfo {
+    /// `node.name`; this is what gets passed back via `PipeWireAudioDevice::name` and used as the
+    /// `PW_KEY_TARGET_OBJECT` value when connecting.
+    name: String,
+    is_sink: bool,
+}
+
+/// Media classes of nodes we offer as pickable devices: sinks, sources and virtual sources. A
+/// sink's monitor is not listed as a node of its own; loopback capture targets the sink itself
+/// (see `record_audio_loop`'s `capture_mode`).
+fn is_audio_device_media_class(media_class: &str) -> bool {
+    matches!(
+        media_class,
+        "Audio/Source" | "Audio/Sink" | "Audio/Source/Virtual"
+    )
+}
+
+/// Walks the PipeWire registry once to enumerate the real sink/source nodes, mirroring cpal's
+/// `Device`/`enumerate` API. Uses a short-lived main loop/registry listener: a `core.sync` +
+/// `done` round-trip guarantees every node that already existed on the server has been reported
+/// before the loop quits and `devices` is read back.
+fn enumerate_nodes() -> StrResult<Vec<PipeWireNodeInfo>> {
+    pw::init();
+
+    let mainloop =
+        MainLoopRc::new(None).map_err(|e| format!("Failed to create PipeWire main loop: {e}"))?;
+    let context = ContextRc::new(&mainloop, None)
+        .map_err(|e| format!("Failed to create PipeWire context: {e}"))?;
+    let core = context
+        .connect_rc(None)
+        .map_err(|e| format!("Failed to connect to PipeWire: {e}"))?;
+    let registry = core
+        .get_registry_rc()
+        .map_err(|e| format!("Failed to get PipeWire registry: {e}"))?;
+
+    let devices = Rc::new(RefCell::new(Vec::<PipeWireNodeInfo>::new()));
+
+    let _registry_listener = registry
+        .add_listener_local()
+        .global({
+            let devices = Rc::clone(&devices);
+            move |global| {
+                let Some(props) = global.props else {
+                    return;
+                };
+                let Some(media_class) = props.get("media.class") else {
+                    return;
+                };
+                if !is_audio_device_media_class(media_class) {
+                    return;
+                }
+                let Some(name) = props.get("node.name") else {
+                    return;
+                };
+
+                devices.borrow_mut().push(PipeWireNodeInfo {
+                    name: name.to_string(),
+                    is_sink: media_class == "Audio/Sink",
+                });
+            }
+        })
+        .register();
+
+    let pending = core
+        .sync(0)
+        .map_err(|e| format!("Failed to sync with PipeWire core: {e}"))?;
+    let _core_listener = core
+        .add_listener_local()
+        .done({
+            let mainloop = mainloop.clone();
+            move |id, seq| {
+                if id == pw::core::PW_ID_CORE && seq == pending {
+                    mainloop.quit();
+                }
+            }
+        })
+        .register();
+
+    mainloop.run();
+
+    Ok(Rc::try_unwrap(devices)
+        .map(RefCell::into_inner)
+        .unwrap_or_default())
 }
 
 /// Returns the list of available audio devices.
 ///
-/// Currently returns only "Default" since PipeWire handles device routing
-/// at the session manager level. Users configure routing externally.
-#[inline(always)]
+/// Walks the live PipeWire registry for real sink/source node names, prefixed with "Default" so
+/// session-manager autoconnect is still available as a choice.
 pub fn get_devices_list() -> StrResult<AudioDevicesList> {
-    Ok(AudioDevicesList {
-        output: vec!["Default".to_string()],
-        input: vec!["Default".to_string()],
-    })
+    let nodes = enumerate_nodes()?;
+
+    let mut output = vec!["Default".to_string()];
+    let mut input = vec!["Default".to_string()];
+    for node in nodes {
+        if node.is_sink {
+            output.push(node.name);
+        } else {
+            input.push(node.name);
+        }
+    }
+
+    Ok(AudioDevicesList { output, input })
 }
 
-/// Returns the sample rate for the given device.
-///
-/// Returns 48000 Hz as the default, which is widely supported and matches
-/// PipeWire's typical default configuration.
+/// Rate PipeWire actually negotiated on the most recent stream of either direction, read back by
+/// `param_changed`. 0 means "nothing negotiated one yet", in which case `get_sample_rate` falls
+/// back to the 48 kHz default.
+static LAST_NEGOTIATED_RATE: AtomicU32 = AtomicU32::new(0);
+
+/// Returns the sample rate for the given device: whatever PipeWire most recently negotiated for
+/// an ALXR stream, or 48000 Hz (PipeWire's typical default) if nothing has negotiated yet.
 #[inline(always)]
 pub fn get_sample_rate(_device: &PipeWireAudioDevice) -> StrResult<u32> {
-    Ok(48000)
+    match LAST_NEGOTIATED_RATE.load(Ordering::Relaxed) {
+        0 => Ok(48000),
+        rate => Ok(rate),
+    }
+}
+
+/// What the stream actually ended up running at, as reported by PipeWire's `param_changed`
+/// (`SPA_PARAM_Format`) callback. The byte-packing math in each `process` callback reads its
+/// `format` instead of assuming the first offered sample format was accepted; `rate` is published
+/// through `LAST_NEGOTIATED_RATE`, and both callbacks still pack with the channel count they
+/// requested.
+#[derive(Clone, Copy, Debug)]
+struct NegotiatedFormat {
+    format: AudioFormat,
+    rate: u32,
+    channels: u32,
+}
+
+impl NegotiatedFormat {
+    fn bytes_per_sample(self) -> usize {
+        match self.format {
+            AudioFormat::S16LE => 2,
+            _ => 4, // F32LE, and anything else we didn't explicitly offer
+        }
+    }
 }
 
+/// Candidate formats offered to PipeWire in preference order (one `EnumFormat` POD per entry), so
+/// the graph can pick whichever it can actually deliver instead of being forced into one hardcoded
+/// choice. `param_changed` below reads back whichever one (and rate/channel count) was chosen.
+const CAPTURE_FORMAT_PREFERENCE: &[AudioFormat] = &[AudioFormat::S16LE, AudioFormat::F32LE];
+const PLAYBACK_FORMAT_PREFERENCE: &[AudioFormat] = &[AudioFormat::F32LE, AudioFormat::S16LE];
+
 /// Build an audio format POD for PipeWire stream negotiation.
 ///
 /// Creates a serialized POD object containing audio format parameters
@@ -139,22 +379,87 @@ fn build_audio_format_pod(
     Ok(cursor.position() as usize)
 }
 
+/// Builds one `EnumFormat` POD per entry of `format_preference` into `buffers` (which must have at
+/// least as many elements), returning the `Pod`s ready to hand to `StreamRc::connect`.
+fn build_format_preference_pods<'a>(
+    format_preference: &[AudioFormat],
+    sample_rate: u32,
+    channels: u32,
+    buffers: &'a mut [[u8; 1024]],
+) -> Result<Vec<Pod<'a>>, String> {
+    let mut pods = Vec::with_capacity(format_preference.len());
+    for (buffer, &format) in buffers.iter_mut().zip(format_preference) {
+        let size = build_audio_format_pod(buffer, format, sample_rate, channels)?;
+        pods.push(Pod::from_bytes(&buffer[..size]).ok_or("Failed to create Pod from bytes")?);
+    }
+    Ok(pods)
+}
+
+/// Parses a `param_changed` event's `SPA_PARAM_Format` object back into a `NegotiatedFormat`,
+/// skipping anything that isn't a raw audio format (PipeWire also fires `param_changed` for
+/// unrelated param types).
+fn parse_negotiated_format(id: u32, param: Option<&Pod>) -> Option<NegotiatedFormat> {
+    if id != ParamType::Format.as_raw() {
+        return None;
+    }
+    let param = param?;
+
+    let (media_type, media_subtype) = format_utils::parse_format(param).ok()?;
+    if media_type != MediaType::Audio || media_subtype != MediaSubtype::Raw {
+        return None;
+    }
+
+    let mut audio_info = AudioInfoRaw::new();
+    audio_info.parse(param).ok()?;
+
+    Some(NegotiatedFormat {
+        format: audio_info.format(),
+        rate: audio_info.rate(),
+        channels: audio_info.channels(),
+    })
+}
+
 /// Record audio using PipeWire.
 ///
-/// Captures audio from the default input device and sends it through the provided sender.
+/// Captures audio from the input node `device` names (or the default input device when it names
+/// none) and sends it through the provided sender, unless
+/// `device` resolved to `CaptureMode::Loopback` (see `PipeWireAudioDevice::new`), in which case it
+/// captures the target sink's monitor (desktop/game audio) instead.
 pub async fn record_audio_loop(
     device: PipeWireAudioDevice,
     channels_count: u16,
     _mute: bool,
     mut sender: StreamSender<()>,
+    codec: opus_codec::AudioCodecConfig,
+    audio_dump_config: crate::audio_dump::AudioDumpConfig,
 ) -> StrResult {
     let sample_rate = get_sample_rate(&device)?;
+    let target_node = device.target_node_name().map(str::to_owned);
+    let loopback = device.is_loopback();
+
+    // `None` unless dumping is enabled; see the equivalent note in `cpal_audio::record_audio_loop`.
+    let dumper = crate::audio_dump::AudioDumper::new(
+        "capture",
+        &audio_dump_config,
+        channels_count,
+        sample_rate,
+    );
 
     let (data_tx, mut data_rx) = tmpsc::unbounded_channel::<StrResult<Vec<u8>>>();
     let (shutdown_tx, shutdown_rx) = pw::channel::channel::<Shutdown>();
+    let (status_tx, mut status_rx) = tmpsc::unbounded_channel::<ReconnectEvent>();
 
     let handle = thread::spawn(move || {
-        if let Err(e) = run_capture_loop(channels_count, sample_rate, data_tx, shutdown_rx) {
+        if let Err(e) = run_capture_loop(
+            channels_count,
+            sample_rate,
+            target_node,
+            loopback,
+            data_tx,
+            shutdown_rx,
+            status_tx,
+            dumper,
+        ) {
             error!("PipeWire capture error: {e}");
         }
     });
@@ -162,27 +467,70 @@ pub async fn record_audio_loop(
     // Guard ensures shutdown is sent even if this async task is cancelled
     let shutdown_tx = ShutdownSender(Some(shutdown_tx));
 
+    // Drains `status_rx` into logs for as long as the capture thread runs; ends on its own once
+    // `status_tx` (held by that thread) drops.
+    let status_logger = tokio::spawn(async move {
+        while let Some(event) = status_rx.recv().await {
+            match event {
+                ReconnectEvent::Reconnecting { attempt } => {
+                    warn!("PipeWire capture reconnecting (attempt {attempt})")
+                }
+                ReconnectEvent::Recovered => info!("PipeWire capture reconnected"),
+                ReconnectEvent::GaveUp => error!("PipeWire capture gave up reconnecting"),
+            }
+        }
+    });
+
+    // `None` when `codec` is `Pcm`; see the equivalent note in `cpal_audio::record_audio_loop`.
+    let mut opus_encoder = match codec {
+        opus_codec::AudioCodecConfig::Opus(opus_config) => Some(opus_codec::Encoder::new(
+            channels_count as usize,
+            sample_rate,
+            opus_config,
+        )?),
+        opus_codec::AudioCodecConfig::Pcm => None,
+    };
+    let mut batcher = opus_encoder
+        .as_ref()
+        .map(|encoder| opus_codec::FrameBatcher::new(encoder.frame_size() * encoder.channels_count()));
+
     while let Some(result) = data_rx.recv().await {
         let data = result?;
-        let mut buffer = sender.new_buffer(&(), data.len())?;
-        buffer.get_mut().extend(&data);
-        sender.send_buffer(buffer).await.ok();
+
+        if let (Some(encoder), Some(batcher)) = (&mut opus_encoder, &mut batcher) {
+            for frame in batcher.push(&opus_codec::pcm_s16le_to_f32(&data)) {
+                let packet = encoder.encode(&frame)?;
+                let mut buffer = sender.new_buffer(&(), packet.len())?;
+                buffer.get_mut().extend(&packet);
+                sender.send_buffer(buffer).await.ok();
+            }
+        } else {
+            let mut buffer = sender.new_buffer(&(), data.len())?;
+            buffer.get_mut().extend(&data);
+            sender.send_buffer(buffer).await.ok();
+        }
     }
 
     drop(shutdown_tx);
     handle.join().ok();
+    status_logger.abort();
     Ok(())
 }
 
 /// Runs the PipeWire capture loop on a dedicated thread.
 ///
-/// This function blocks until shutdown is signaled or an error occurs.
-/// Audio data is sent through `data_tx` as raw bytes in S16LE format.
+/// This function blocks until shutdown is signaled or reconnect attempts are exhausted (see
+/// `run_reconnecting_session`). Audio data is sent through `data_tx` as raw bytes in S16LE format.
+#[allow(clippy::too_many_arguments)]
 fn run_capture_loop(
     channels_count: u16,
     sample_rate: u32,
+    target_node: Option<String>,
+    loopback: bool,
     data_tx: tmpsc::UnboundedSender<StrResult<Vec<u8>>>,
     shutdown_rx: pw::channel::Receiver<Shutdown>,
+    status_tx: tmpsc::UnboundedSender<ReconnectEvent>,
+    dumper: Option<crate::audio_dump::AudioDumper>,
 ) -> StrResult {
     // Initialize PipeWire library for this thread
     pw::init();
@@ -195,142 +543,281 @@ fn run_capture_loop(
         .connect_rc(None)
         .map_err(|e| format!("Failed to connect to PipeWire: {e}"))?;
 
-    // Stream properties for session manager routing and identification
-    let props = pw::properties::properties! {
-        *pw::keys::MEDIA_TYPE => "Audio",
-        *pw::keys::MEDIA_CATEGORY => "Capture",
-        *pw::keys::MEDIA_ROLE => "Communication",
-        *pw::keys::NODE_NAME => "ALXR Audio Capture",
-        *pw::keys::APP_NAME => "ALXR",
-    };
-
-    let stream = StreamRc::new(core, "alxr-audio-capture", props)
-        .map_err(|e| format!("Failed to create PipeWire stream: {e}"))?;
-
-    // Attach shutdown receiver to quit main loop when signaled
+    // Attached once, to the long-lived mainloop: a reconnect rebuilds the `StreamRc` in place and
+    // re-enters `mainloop.run()`, but the shutdown source stays registered across every attempt.
+    let shutdown_requested = Rc::new(std::cell::Cell::new(false));
     let _shutdown = shutdown_rx.attach(mainloop.loop_(), {
         let mainloop = mainloop.clone();
-        move |_| mainloop.quit()
+        let shutdown_requested = Rc::clone(&shutdown_requested);
+        move |_| {
+            shutdown_requested.set(true);
+            mainloop.quit();
+        }
     });
 
     let data_tx = Rc::new(data_tx);
 
-    let _listener = stream
-        .add_local_listener::<()>()
-        .state_changed({
-            let mainloop = mainloop.clone();
-            move |_, _, old, new| {
-                debug!("PipeWire capture: {old:?} -> {new:?}");
-                if matches!(new, StreamState::Error(_)) {
-                    error!("PipeWire capture stream entered error state");
-                    mainloop.quit();
-                }
+    // Downstream (`record_audio_loop`'s opus encoder / raw PCM path) always expects S16LE bytes,
+    // regardless of what PipeWire actually negotiated; `process` below converts into that shape
+    // using whatever `param_changed` last reported. Kept across reconnects so a transient drop
+    // doesn't forget the last known-good format.
+    let negotiated = Rc::new(RefCell::new(NegotiatedFormat {
+        format: AudioFormat::S16LE,
+        rate: sample_rate,
+        channels: channels_count.into(),
+    }));
+
+    let build_stream = {
+        let core = core.clone();
+        let mainloop = mainloop.clone();
+        let target_node = target_node.clone();
+        let data_tx = Rc::clone(&data_tx);
+        let negotiated = Rc::clone(&negotiated);
+        let dumper = dumper.clone();
+        move || -> StrResult<(StreamRc, Box<dyn std::any::Any>)> {
+            // Stream properties for session manager routing and identification
+            let mut props = pw::properties::properties! {
+                *pw::keys::MEDIA_TYPE => "Audio",
+                *pw::keys::MEDIA_CATEGORY => "Capture",
+                *pw::keys::MEDIA_ROLE => "Communication",
+                *pw::keys::NODE_NAME => "ALXR Audio Capture",
+                *pw::keys::APP_NAME => "ALXR",
+            };
+            // Target a specific node instead of relying on AUTOCONNECT's default, so users can
+            // pick a device from the ALVR UI without an external tool like pavucontrol.
+            if let Some(target_node) = &target_node {
+                props.insert(*pw::keys::TARGET_OBJECT, target_node);
+            }
+            // Loopback capture: record what `target_node` (or the default sink, if unset) is
+            // playing instead of a real microphone, so the headset mic path can stream
+            // game/desktop audio without the user having to wire a loopback manually in qpwgraph.
+            if loopback {
+                props.insert(*pw::keys::STREAM_CAPTURE_SINK, "true");
             }
-        })
-        .process({
-            let data_tx = Rc::clone(&data_tx);
-            move |stream, _| {
-                let Some(mut buffer) = stream.dequeue_buffer() else {
-                    return;
-                };
-                let datas = buffer.datas_mut();
-                if datas.is_empty() {
-                    return;
-                }
 
-                // Read chunk metadata before accessing mutable data
-                let data = &mut datas[0];
-                let size = data.chunk().size() as usize;
-                let offset = data.chunk().offset() as usize;
-
-                if let Some(audio_data) = data.data() {
-                    if size > 0 && offset + size <= audio_data.len() {
-                        // Note: Allocation here is unavoidable since we need to send
-                        // owned data across the channel to the async task.
-                        // The Vec is sized exactly to the audio chunk size.
-                        let mut samples = Vec::with_capacity(size);
-                        samples.extend_from_slice(&audio_data[offset..offset + size]);
+            let stream = StreamRc::new(core.clone(), "alxr-audio-capture", props)
+                .map_err(|e| format!("Failed to create PipeWire stream: {e}"))?;
+
+            let listener = stream
+                .add_local_listener::<()>()
+                .state_changed({
+                    let mainloop = mainloop.clone();
+                    move |_, _, old, new| {
+                        debug!("PipeWire capture: {old:?} -> {new:?}");
+                        if matches!(new, StreamState::Error(_)) {
+                            error!("PipeWire capture stream entered error state");
+                            mainloop.quit();
+                        }
+                    }
+                })
+                .param_changed({
+                    let negotiated = Rc::clone(&negotiated);
+                    move |_, _, id, param| {
+                        if let Some(format) = parse_negotiated_format(id, param) {
+                            info!(
+                                "PipeWire capture negotiated: {:?} {} Hz {} ch",
+                                format.format, format.rate, format.channels
+                            );
+                            LAST_NEGOTIATED_RATE.store(format.rate, Ordering::Relaxed);
+                            *negotiated.borrow_mut() = format;
+                        }
+                    }
+                })
+                .process({
+                    let data_tx = Rc::clone(&data_tx);
+                    let negotiated = Rc::clone(&negotiated);
+                    let dumper = dumper.clone();
+                    move |stream, _| {
+                        let Some(mut buffer) = stream.dequeue_buffer() else {
+                            return;
+                        };
+                        let datas = buffer.datas_mut();
+                        if datas.is_empty() {
+                            return;
+                        }
+
+                        // Read chunk metadata before accessing mutable data
+                        let data = &mut datas[0];
+                        let size = data.chunk().size() as usize;
+                        let offset = data.chunk().offset() as usize;
+
+                        let Some(audio_data) = data.data() else {
+                            return;
+                        };
+                        if size == 0 || offset + size > audio_data.len() {
+                            return;
+                        }
+                        let raw = &audio_data[offset..offset + size];
+
+                        // Note: allocation here is unavoidable since we need to send owned data
+                        // across the channel to the async task.
+                        let samples = match negotiated.borrow().format {
+                            AudioFormat::F32LE => {
+                                // Collected once as i16 so the dumper (below) can reuse these
+                                // values instead of re-parsing the bytes it just wrote.
+                                let pcm: Vec<i16> = raw
+                                    .chunks_exact(4)
+                                    .map(|c| {
+                                        let sample = f32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+                                        (sample.clamp(-1., 1.) * i16::MAX as f32) as i16
+                                    })
+                                    .collect();
+                                if let Some(dumper) = &dumper {
+                                    dumper.push_samples_i16(&pcm);
+                                }
+                                pcm.iter().flat_map(|s| s.to_le_bytes()).collect()
+                            }
+                            // S16LE (the default, and anything we didn't explicitly offer) passes
+                            // through.
+                            _ => {
+                                if let Some(dumper) = &dumper {
+                                    let pcm: Vec<i16> = raw
+                                        .chunks_exact(2)
+                                        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                                        .collect();
+                                    dumper.push_samples_i16(&pcm);
+                                }
+                                raw.to_vec()
+                            }
+                        };
+
                         let _ = data_tx.send(Ok(samples));
                     }
-                }
-            }
-        })
-        .register()
-        .map_err(|e| format!("Failed to register stream listener: {e}"))?;
-
-    // Build and connect with audio format
-    let mut pod_buffer = [0u8; 1024];
-    let pod_size = build_audio_format_pod(
-        &mut pod_buffer,
-        AudioFormat::S16LE,
-        sample_rate,
-        channels_count.into(),
-    )?;
-    let pod = Pod::from_bytes(&pod_buffer[..pod_size]).ok_or("Failed to create Pod from bytes")?;
-
-    // AUTOCONNECT: Let session manager route to default device
-    // MAP_BUFFERS: Map buffer memory for direct access
-    // RT_PROCESS: Enable real-time processing in the audio thread
-    let flags = StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS | StreamFlags::RT_PROCESS;
-    stream
-        .connect(Direction::Input, None, flags, &mut [pod])
-        .map_err(|e| format!("Failed to connect PipeWire capture stream: {e}"))?;
-
-    info!("PipeWire capture stream connected");
-    mainloop.run();
-    info!("PipeWire capture loop exited");
+                })
+                .register()
+                .map_err(|e| format!("Failed to register stream listener: {e}"))?;
+
+            // Offer every candidate format in `CAPTURE_FORMAT_PREFERENCE`; PipeWire/the session
+            // manager negotiates down to whichever one it can actually deliver.
+            let mut pod_buffers = [[0u8; 1024]; CAPTURE_FORMAT_PREFERENCE.len()];
+            let mut pods = build_format_preference_pods(
+                CAPTURE_FORMAT_PREFERENCE,
+                sample_rate,
+                channels_count.into(),
+                &mut pod_buffers,
+            )?;
+
+            // AUTOCONNECT: Let session manager route to default device
+            // MAP_BUFFERS: Map buffer memory for direct access
+            // RT_PROCESS: Enable real-time processing in the audio thread
+            let flags =
+                StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS | StreamFlags::RT_PROCESS;
+            stream
+                .connect(Direction::Input, None, flags, &mut pods)
+                .map_err(|e| format!("Failed to connect PipeWire capture stream: {e}"))?;
+
+            info!("PipeWire capture stream connected");
+            Ok((stream, Box::new(listener)))
+        }
+    };
 
-    stream.disconnect().ok();
-    Ok(())
+    let result = run_reconnecting_session(&mainloop, &shutdown_requested, &status_tx, build_stream);
+    info!("PipeWire capture loop exited");
+    result
 }
 
 /// Play audio using PipeWire.
 ///
 /// Receives audio samples and plays them through the default output device.
+#[allow(clippy::too_many_arguments)]
 pub async fn play_audio_loop(
-    _device: PipeWireAudioDevice,
+    device: PipeWireAudioDevice,
     channels_count: u16,
     sample_rate: u32,
     config: AudioConfig,
     receiver: StreamReceiver<()>,
+    codec: opus_codec::AudioCodecConfig,
+    fade_curve_kind: crate::fade_curve::FadeCurveKind,
+    resample_quality: crate::resampler::ResampleQuality,
+    control_sender: Option<tokio::sync::mpsc::UnboundedSender<alvr_sockets::ClientControlPacket>>,
+    audio_dump_config: crate::audio_dump::AudioDumpConfig,
 ) -> StrResult {
+    let target_node = device.target_node_name().map(str::to_owned);
+
     let batch_frames_count = sample_rate as usize * config.batch_ms as usize / 1000;
     let average_buffer_frames_count =
         sample_rate as usize * config.average_buffering_ms as usize / 1000;
 
-    let sample_buffer = Arc::new(Mutex::new(VecDeque::new()));
+    let ring_capacity = crate::playback_ring_capacity(
+        average_buffer_frames_count,
+        batch_frames_count,
+        channels_count as usize,
+    );
+    let sample_buffer = Arc::new(SampleRing::new(ring_capacity));
     let sample_buffer_clone = Arc::clone(&sample_buffer);
 
     let channels = channels_count as usize;
 
-    let (shutdown_tx, shutdown_rx) = pw::channel::channel::<Shutdown>();
+    // `None` unless dumping is enabled; see the equivalent note in `cpal_audio::play_audio_loop`.
+    let dumper = crate::audio_dump::AudioDumper::new(
+        "playback",
+        &audio_dump_config,
+        channels_count,
+        sample_rate,
+    );
 
-    let handle = thread::spawn(move || {
-        if let Err(e) = run_playback_loop(
-            channels,
-            sample_rate,
-            batch_frames_count,
-            sample_buffer_clone,
-            shutdown_rx,
-        ) {
-            error!("PipeWire playback error: {e}");
+    let (shutdown_tx, shutdown_rx) = pw::channel::channel::<Shutdown>();
+    let (status_tx, mut status_rx) = tmpsc::unbounded_channel::<ReconnectEvent>();
+
+    // Starts out at the rate we're about to request; a reconnect's `param_changed` may later
+    // negotiate a different native rate, at which point `receive_samples_loop` rebuilds its
+    // resampler against the updated value (see its doc comment).
+    let negotiated_output_rate = Arc::new(AtomicU32::new(sample_rate));
+
+    let handle = thread::spawn({
+        let negotiated_output_rate = Arc::clone(&negotiated_output_rate);
+        move || {
+            if let Err(e) = run_playback_loop(
+                channels,
+                sample_rate,
+                target_node,
+                batch_frames_count,
+                sample_buffer_clone,
+                shutdown_rx,
+                status_tx,
+                negotiated_output_rate,
+                dumper,
+            ) {
+                error!("PipeWire playback error: {e}");
+            }
         }
     });
 
     // Guard ensures shutdown is sent even if this async task is cancelled
     let shutdown_tx = ShutdownSender(Some(shutdown_tx));
 
+    // Drains `status_rx` into logs for as long as the playback thread runs; ends on its own once
+    // `status_tx` (held by that thread) drops.
+    let status_logger = tokio::spawn(async move {
+        while let Some(event) = status_rx.recv().await {
+            match event {
+                ReconnectEvent::Reconnecting { attempt } => {
+                    warn!("PipeWire playback reconnecting (attempt {attempt})")
+                }
+                ReconnectEvent::Recovered => info!("PipeWire playback reconnected"),
+                ReconnectEvent::GaveUp => error!("PipeWire playback gave up reconnecting"),
+            }
+        }
+    });
+
     let result = receive_samples_loop(
         receiver,
         sample_buffer,
         channels_count as _,
         batch_frames_count,
         average_buffer_frames_count,
+        sample_rate,
+        negotiated_output_rate,
+        codec,
+        fade_curve_kind,
+        resample_quality,
+        control_sender,
     )
     .await;
 
     drop(shutdown_tx);
     handle.join().ok();
+    status_logger.abort();
 
     result
 }
@@ -340,12 +827,17 @@ pub async fn play_audio_loop(
 /// This function blocks until shutdown is signaled or an error occurs.
 /// Audio samples are read from the shared `sample_buffer` and written
 /// to PipeWire in F32LE format.
+#[allow(clippy::too_many_arguments)]
 fn run_playback_loop(
     channels: usize,
     sample_rate: u32,
+    target_node: Option<String>,
     batch_frames_count: usize,
-    sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+    sample_buffer: Arc<SampleRing>,
     shutdown_rx: pw::channel::Receiver<Shutdown>,
+    status_tx: tmpsc::UnboundedSender<ReconnectEvent>,
+    negotiated_output_rate: Arc<AtomicU32>,
+    dumper: Option<crate::audio_dump::AudioDumper>,
 ) -> StrResult {
     // Initialize PipeWire library for this thread
     pw::init();
@@ -358,22 +850,16 @@ fn run_playback_loop(
         .connect_rc(None)
         .map_err(|e| format!("Failed to connect to PipeWire: {e}"))?;
 
-    // Stream properties for session manager routing and identification
-    let props = pw::properties::properties! {
-        *pw::keys::MEDIA_TYPE => "Audio",
-        *pw::keys::MEDIA_CATEGORY => "Playback",
-        *pw::keys::MEDIA_ROLE => "Game",
-        *pw::keys::NODE_NAME => "ALXR Audio Playback",
-        *pw::keys::APP_NAME => "ALXR",
-    };
-
-    let stream = StreamRc::new(core, "alxr-audio-playback", props)
-        .map_err(|e| format!("Failed to create PipeWire stream: {e}"))?;
-
-    // Attach shutdown receiver to quit main loop when signaled
+    // Attached once, to the long-lived mainloop: a reconnect rebuilds the `StreamRc` in place and
+    // re-enters `mainloop.run()`, but the shutdown source stays registered across every attempt.
+    let shutdown_requested = Rc::new(std::cell::Cell::new(false));
     let _shutdown = shutdown_rx.attach(mainloop.loop_(), {
         let mainloop = mainloop.clone();
-        move |_| mainloop.quit()
+        let shutdown_requested = Rc::clone(&shutdown_requested);
+        move |_| {
+            shutdown_requested.set(true);
+            mainloop.quit();
+        }
     });
 
     // Use Rc to share Arc with the callback
@@ -381,105 +867,194 @@ fn run_playback_loop(
     // Pre-allocate temp buffer with expected capacity to avoid reallocations in RT callback
     let initial_capacity = batch_frames_count * channels;
     let temp_buffer = Rc::new(RefCell::new(Vec::<f32>::with_capacity(initial_capacity)));
-    let bytes_per_sample = mem::size_of::<f32>();
-    let bytes_per_frame = channels * bytes_per_sample;
 
-    let _listener = stream
-        .add_local_listener::<()>()
-        .state_changed({
-            let mainloop = mainloop.clone();
-            move |_, _, old, new| {
-                debug!("PipeWire playback: {old:?} -> {new:?}");
-                if matches!(new, StreamState::Error(_)) {
-                    error!("PipeWire playback stream entered error state");
-                    mainloop.quit();
-                }
+    // `get_next_frame_batch` always produces f32 samples; `process` below converts into whatever
+    // `param_changed` last reported as negotiated. Kept across reconnects so a transient drop
+    // doesn't forget the last known-good format.
+    let negotiated = Rc::new(RefCell::new(NegotiatedFormat {
+        format: AudioFormat::F32LE,
+        rate: sample_rate,
+        channels: channels as u32,
+    }));
+
+    let build_stream = {
+        let core = core.clone();
+        let mainloop = mainloop.clone();
+        let target_node = target_node.clone();
+        let sample_buffer_rc = Rc::clone(&sample_buffer_rc);
+        let temp_buffer = Rc::clone(&temp_buffer);
+        let negotiated = Rc::clone(&negotiated);
+        let negotiated_output_rate = Arc::clone(&negotiated_output_rate);
+        let dumper = dumper.clone();
+        move || -> StrResult<(StreamRc, Box<dyn std::any::Any>)> {
+            // Stream properties for session manager routing and identification
+            let mut props = pw::properties::properties! {
+                *pw::keys::MEDIA_TYPE => "Audio",
+                *pw::keys::MEDIA_CATEGORY => "Playback",
+                *pw::keys::MEDIA_ROLE => "Game",
+                *pw::keys::NODE_NAME => "ALXR Audio Playback",
+                *pw::keys::APP_NAME => "ALXR",
+            };
+            if let Some(target_node) = &target_node {
+                props.insert(*pw::keys::TARGET_OBJECT, target_node);
             }
-        })
-        .process({
-            let sample_buffer = Rc::clone(&sample_buffer_rc);
-            let temp_buffer = Rc::clone(&temp_buffer);
-            move |stream, _| {
-                let Some(mut buffer) = stream.dequeue_buffer() else {
-                    return;
-                };
-                let datas = buffer.datas_mut();
-                if datas.is_empty() {
-                    return;
-                }
-
-                let data = &mut datas[0];
-
-                // For output streams, we write to the data buffer
-                let Some(audio_data) = data.data() else {
-                    return;
-                };
-
-                // Calculate how many frames we can write
-                let max_frames = audio_data.len() / bytes_per_frame;
-                if max_frames == 0 {
-                    return;
-                }
-
-                // Request frames from our sample buffer (use batch size or max available)
-                let frames_to_write = batch_frames_count.min(max_frames);
-
-                // Get frames from our sample buffer
-                let mut temp = temp_buffer.borrow_mut();
-                get_next_frame_batch(
-                    &mut *sample_buffer.lock(),
-                    channels,
-                    frames_to_write,
-                    &mut temp,
-                );
-
-                // Write f32 samples directly to the buffer as bytes
-                let samples_to_write = temp.len();
-                let bytes_to_write = samples_to_write * bytes_per_sample;
-
-                // SAFETY: temp is a Vec<f32> with valid alignment. We reinterpret the
-                // underlying memory as bytes for a memcpy. The bytes_to_write is correctly
-                // calculated as samples_to_write * size_of::<f32>().
-                let sample_bytes: &[u8] = unsafe {
-                    std::slice::from_raw_parts(temp.as_ptr() as *const u8, bytes_to_write)
-                };
 
-                // Copy to output buffer
-                let copy_len = bytes_to_write.min(audio_data.len());
-                audio_data[..copy_len].copy_from_slice(&sample_bytes[..copy_len]);
+            let stream = StreamRc::new(core.clone(), "alxr-audio-playback", props)
+                .map_err(|e| format!("Failed to create PipeWire stream: {e}"))?;
+
+            let listener = stream
+                .add_local_listener::<()>()
+                .state_changed({
+                    let mainloop = mainloop.clone();
+                    move |_, _, old, new| {
+                        debug!("PipeWire playback: {old:?} -> {new:?}");
+                        if matches!(new, StreamState::Error(_)) {
+                            error!("PipeWire playback stream entered error state");
+                            mainloop.quit();
+                        }
+                    }
+                })
+                .param_changed({
+                    let negotiated = Rc::clone(&negotiated);
+                    let negotiated_output_rate = Arc::clone(&negotiated_output_rate);
+                    move |_, _, id, param| {
+                        if let Some(format) = parse_negotiated_format(id, param) {
+                            info!(
+                                "PipeWire playback negotiated: {:?} {} Hz {} ch",
+                                format.format, format.rate, format.channels
+                            );
+                            LAST_NEGOTIATED_RATE.store(format.rate, Ordering::Relaxed);
+                            negotiated_output_rate.store(format.rate, Ordering::Relaxed);
+                            *negotiated.borrow_mut() = format;
+                        }
+                    }
+                })
+                .process({
+                    let sample_buffer = Rc::clone(&sample_buffer_rc);
+                    let temp_buffer = Rc::clone(&temp_buffer);
+                    let negotiated = Rc::clone(&negotiated);
+                    let dumper = dumper.clone();
+                    move |stream, _| {
+                        let Some(mut buffer) = stream.dequeue_buffer() else {
+                            return;
+                        };
+                        let datas = buffer.datas_mut();
+                        if datas.is_empty() {
+                            return;
+                        }
+
+                        let data = &mut datas[0];
+
+                        // For output streams, we write to the data buffer
+                        let Some(audio_data) = data.data() else {
+                            return;
+                        };
+
+                        let format = negotiated.borrow().format;
+                        let bytes_per_sample = NegotiatedFormat {
+                            format,
+                            rate: sample_rate,
+                            channels: channels as u32,
+                        }
+                        .bytes_per_sample();
+                        let bytes_per_frame = channels * bytes_per_sample;
+
+                        // Calculate how many frames we can write
+                        let max_frames = audio_data.len() / bytes_per_frame;
+                        if max_frames == 0 {
+                            return;
+                        }
+
+                        // Request frames from our sample buffer (use batch size or max available)
+                        let frames_to_write = batch_frames_count.min(max_frames);
+
+                        // Get frames from our sample buffer
+                        let mut temp = temp_buffer.borrow_mut();
+                        if let Some(dumper) = &dumper {
+                            // Cheap proxy for an imminent underrun, checked before the call that
+                            // would otherwise already have consumed whatever was left; see the
+                            // equivalent check in cpal_audio's `StreamingSource::next`.
+                            if sample_buffer.len() < frames_to_write * channels {
+                                dumper.mark_underrun();
+                            }
+                        }
+                        get_next_frame_batch(&sample_buffer, channels, frames_to_write, &mut temp);
+
+                        if let Some(dumper) = &dumper {
+                            dumper.push_samples_f32(&temp);
+                        }
+
+                        let samples_to_write = temp.len();
+                        let copy_len = match format {
+                            AudioFormat::S16LE => {
+                                let bytes_to_write = samples_to_write * bytes_per_sample;
+                                let copy_len = bytes_to_write.min(audio_data.len());
+                                for (sample, out) in temp
+                                    .iter()
+                                    .zip(audio_data[..copy_len].chunks_exact_mut(bytes_per_sample))
+                                {
+                                    let clamped = (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+                                    out.copy_from_slice(&clamped.to_le_bytes());
+                                }
+                                copy_len
+                            }
+                            // F32LE (the default, and anything we didn't explicitly offer) passes
+                            // through.
+                            _ => {
+                                let bytes_to_write = samples_to_write * bytes_per_sample;
+
+                                // SAFETY: temp is a Vec<f32> with valid alignment. We reinterpret
+                                // the underlying memory as bytes for a memcpy. The
+                                // bytes_to_write is correctly calculated as
+                                // samples_to_write * size_of::<f32>().
+                                let sample_bytes: &[u8] = unsafe {
+                                    std::slice::from_raw_parts(
+                                        temp.as_ptr() as *const u8,
+                                        bytes_to_write,
+                                    )
+                                };
+                                let copy_len = bytes_to_write.min(audio_data.len());
+                                audio_data[..copy_len]
+                                    .copy_from_slice(&sample_bytes[..copy_len]);
+                                copy_len
+                            }
+                        };
+
+                        // Update chunk to indicate how much data we wrote
+                        let chunk = data.chunk_mut();
+                        *chunk.size_mut() = copy_len as u32;
+                        *chunk.offset_mut() = 0;
+                        *chunk.stride_mut() = bytes_per_frame as i32;
+                    }
+                })
+                .register()
+                .map_err(|e| format!("Failed to register stream listener: {e}"))?;
+
+            // Offer every candidate format in `PLAYBACK_FORMAT_PREFERENCE`; PipeWire/the session
+            // manager negotiates down to whichever one it can actually deliver.
+            let mut pod_buffers = [[0u8; 1024]; PLAYBACK_FORMAT_PREFERENCE.len()];
+            let mut pods = build_format_preference_pods(
+                PLAYBACK_FORMAT_PREFERENCE,
+                sample_rate,
+                channels as u32,
+                &mut pod_buffers,
+            )?;
+
+            // AUTOCONNECT: Let session manager route to default device
+            // MAP_BUFFERS: Map buffer memory for direct access
+            // RT_PROCESS: Enable real-time processing in the audio thread
+            let flags =
+                StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS | StreamFlags::RT_PROCESS;
+            stream
+                .connect(Direction::Output, None, flags, &mut pods)
+                .map_err(|e| format!("Failed to connect PipeWire playback stream: {e}"))?;
+
+            info!("PipeWire playback stream connected");
+            Ok((stream, Box::new(listener)))
+        }
+    };
 
-                // Update chunk to indicate how much data we wrote
-                let chunk = data.chunk_mut();
-                *chunk.size_mut() = copy_len as u32;
-                *chunk.offset_mut() = 0;
-                *chunk.stride_mut() = bytes_per_frame as i32;
-            }
-        })
-        .register()
-        .map_err(|e| format!("Failed to register stream listener: {e}"))?;
-
-    // Build and connect with audio format
-    let mut pod_buffer = [0u8; 1024];
-    let pod_size = build_audio_format_pod(
-        &mut pod_buffer,
-        AudioFormat::F32LE,
-        sample_rate,
-        channels as u32,
-    )?;
-    let pod = Pod::from_bytes(&pod_buffer[..pod_size]).ok_or("Failed to create Pod from bytes")?;
-
-    // AUTOCONNECT: Let session manager route to default device
-    // MAP_BUFFERS: Map buffer memory for direct access
-    // RT_PROCESS: Enable real-time processing in the audio thread
-    let flags = StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS | StreamFlags::RT_PROCESS;
-    stream
-        .connect(Direction::Output, None, flags, &mut [pod])
-        .map_err(|e| format!("Failed to connect PipeWire playback stream: {e}"))?;
-
-    info!("PipeWire playback stream connected");
-    mainloop.run();
+    let result = run_reconnecting_session(&mainloop, &shutdown_requested, &status_tx, build_stream);
     info!("PipeWire playback loop exited");
-
-    stream.disconnect().ok();
-    Ok(())
+    result
 }
@@ -0,0 +1,355 @@
+// Polyphase FIR sample-rate converter, in the style of a dynamic FIR resampler: a windowed-sinc
+// low-pass (Kaiser window, cutoff at `min(input_rate, output_rate) / 2`) is split into
+// `num_phases` polyphase sub-filter banks. A fixed-point phase accumulator advances by
+// `input_rate / output_rate` per output sample; its integer part tells us how many new input
+// samples to fold into the per-channel history ring, and its fractional part selects/interpolates
+// between adjacent polyphase banks for the tap-weighted sum. Used by `receive_samples_loop` to let
+// the playback device's native rate differ from the incoming stream's rate.
+
+use std::collections::VecDeque;
+
+const FRAC_BITS: u32 = 32;
+const FRAC_SCALE: u64 = 1u64 << FRAC_BITS;
+
+/// Default filter quality (total prototype taps, split across the `DEFAULT_PHASES` banks: 32 taps
+/// per phase) and polyphase bank count: enough taps for a clean stopband without costing much per
+/// output sample.
+pub const DEFAULT_TAPS: usize = 32 * DEFAULT_PHASES;
+pub const DEFAULT_PHASES: usize = 32;
+
+/// Resample quality tiers, following Android's `AudioResampler` ladder (plain linear/cubic
+/// interpolators vs. `AudioResamplerSinc`'s windowed-sinc polyphase bank): `Fastest` picks the
+/// nearest already-buffered sample with no filtering at all, `Linear`/`Cubic` interpolate the
+/// waveform itself without a dedicated anti-alias filter, and `Sinc` runs the full Kaiser-windowed
+/// polyphase FIR bank built by `PolyphaseResampler::new`. Picking a cheaper tier trades stopband
+/// rejection (and a little latency) for per-sample CPU cost, for devices too weak to afford
+/// `Sinc`'s per-output-sample convolution.
+///
+/// Note: like `fade_curve`'s curve selection, `alvr_session::AudioConfig` (the settings struct
+/// this would normally be a selectable field on) isn't part of this crate, so quality selection is
+/// threaded as an explicit `ResampleQuality` parameter instead, the same way
+/// `opus_codec::AudioCodecConfig` is — see its doc comment for the client-local `APP_CONFIG`
+/// stand-in used by `alxr-common`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResampleQuality {
+    Fastest,
+    Linear,
+    Cubic,
+    Sinc,
+}
+
+impl Default for ResampleQuality {
+    fn default() -> Self {
+        ResampleQuality::Sinc
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+// Zeroth-order modified Bessel function of the first kind, needed to build the Kaiser window.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= (x / (2.0 * k)).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+fn kaiser_window(n: usize, len: usize, beta: f64) -> f64 {
+    if len <= 1 {
+        return 1.0;
+    }
+    let m = (len - 1) as f64;
+    let x = 2.0 * n as f64 / m - 1.0;
+    bessel_i0(beta * (1.0 - x * x).max(0.0).sqrt()) / bessel_i0(beta)
+}
+
+/// A single-channel polyphase FIR resampler. Converts between arbitrary input/output sample
+/// rates, carrying its history ring and phase accumulator across calls to `process` so packet
+/// boundaries don't introduce glitches.
+pub struct PolyphaseResampler {
+    quality: ResampleQuality,
+    taps_per_phase: usize,
+    num_phases: usize,
+    // banks[phase][tap], tap 0 being the most recently added history sample's weight. Empty for
+    // every quality but `Sinc`.
+    banks: Vec<Vec<f32>>,
+    history: VecDeque<f32>,
+    pending: VecDeque<f32>,
+    phase_acc: u64,
+    // The nominal input_rate/output_rate increment, before any drift correction.
+    base_increment: u64,
+    increment: u64,
+}
+
+impl PolyphaseResampler {
+    /// Builds a resampler for the given `quality`. `Sinc` runs the Kaiser-windowed polyphase bank
+    /// (`DEFAULT_TAPS`/`DEFAULT_PHASES`, same as `new`); the other tiers only keep as much history
+    /// as their interpolator needs and skip filter design entirely.
+    pub fn with_quality(input_rate: u32, output_rate: u32, quality: ResampleQuality) -> Self {
+        if quality == ResampleQuality::Sinc {
+            return Self::new(input_rate, output_rate, DEFAULT_TAPS, DEFAULT_PHASES);
+        }
+
+        // Linear/Fastest only ever look at the two most recently consumed samples; Cubic needs
+        // two more of lookback/lookahead for its Catmull-Rom neighbors.
+        let history_len = if quality == ResampleQuality::Cubic {
+            4
+        } else {
+            2
+        };
+        let base_increment = ((input_rate as u64) << FRAC_BITS) / output_rate as u64;
+
+        Self {
+            quality,
+            taps_per_phase: 0,
+            num_phases: 1,
+            banks: Vec::new(),
+            history: VecDeque::from(vec![0f32; history_len]),
+            pending: VecDeque::new(),
+            phase_acc: 0,
+            base_increment,
+            increment: base_increment,
+        }
+    }
+
+    pub fn new(input_rate: u32, output_rate: u32, num_taps: usize, num_phases: usize) -> Self {
+        let num_phases = num_phases.max(1);
+        let taps_per_phase = (num_taps / num_phases).max(1);
+        let full_len = taps_per_phase * num_phases;
+
+        let cutoff_hz = input_rate.min(output_rate) as f64 / 2.0;
+        // Taps are spaced 1/num_phases input-samples apart, i.e. sampled at num_phases*input_rate.
+        let tap_sample_rate = num_phases as f64 * input_rate as f64;
+        let cutoff_norm = (2.0 * cutoff_hz / tap_sample_rate).clamp(0.0, 1.0);
+
+        let beta = 8.0;
+        let center = (full_len - 1) as f64 / 2.0;
+        let mut prototype = vec![0f32; full_len];
+        for (n, tap) in prototype.iter_mut().enumerate() {
+            let t = n as f64 - center;
+            let h = cutoff_norm * sinc(cutoff_norm * t);
+            *tap = (h * kaiser_window(n, full_len, beta)) as f32;
+        }
+
+        // Only one out of every `num_phases` taps contributes to a given output sample, so
+        // normalize for unity DC gain against a single phase's worth of taps.
+        let dc_gain: f32 = prototype.iter().sum::<f32>() / num_phases as f32;
+        if dc_gain.abs() > 1e-9 {
+            for tap in &mut prototype {
+                *tap /= dc_gain;
+            }
+        }
+
+        let mut banks = vec![vec![0f32; taps_per_phase]; num_phases];
+        for (n, &tap) in prototype.iter().enumerate() {
+            let phase = n % num_phases;
+            let k = n / num_phases;
+            banks[phase][k] = tap;
+        }
+
+        let base_increment = ((input_rate as u64) << FRAC_BITS) / output_rate as u64;
+
+        Self {
+            quality: ResampleQuality::Sinc,
+            taps_per_phase,
+            num_phases,
+            banks,
+            history: VecDeque::from(vec![0f32; taps_per_phase]),
+            pending: VecDeque::new(),
+            phase_acc: 0,
+            base_increment,
+            increment: base_increment,
+        }
+    }
+
+    /// Nudges the effective input/output rate ratio by `correction` (e.g. `0.002` plays the
+    /// input back 0.2% faster), clamped to `±0.5%` so the resulting pitch shift stays inaudible.
+    /// Used to slowly compensate for server/client clock drift instead of letting the sample
+    /// buffer drain or overflow and forcing an audible resync.
+    pub fn set_rate_correction(&mut self, correction: f64) {
+        let correction = correction.clamp(-0.005, 0.005);
+        self.increment = (self.base_increment as f64 * (1.0 + correction)) as u64;
+    }
+
+    /// Feeds `input` samples through the resampler, appending every output sample it can produce
+    /// to `output`. Samples left over once there's not enough history to produce the next output
+    /// are carried over (along with the phase accumulator and history ring) to the next call.
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        self.pending.extend(input.iter().copied());
+
+        loop {
+            while self.phase_acc >= FRAC_SCALE {
+                let Some(sample) = self.pending.pop_front() else {
+                    return;
+                };
+                self.history.pop_front();
+                self.history.push_back(sample);
+                self.phase_acc -= FRAC_SCALE;
+            }
+
+            let frac = self.phase_acc as f64 / FRAC_SCALE as f64;
+            output.push(self.evaluate(frac));
+            self.phase_acc += self.increment;
+        }
+    }
+
+    fn evaluate(&self, frac: f64) -> f32 {
+        match self.quality {
+            ResampleQuality::Sinc => self.evaluate_sinc(frac),
+            ResampleQuality::Fastest => self.evaluate_fastest(frac),
+            ResampleQuality::Linear => self.evaluate_linear(frac),
+            ResampleQuality::Cubic => self.evaluate_cubic(frac),
+        }
+    }
+
+    fn evaluate_sinc(&self, frac: f64) -> f32 {
+        let phase_pos = frac * self.num_phases as f64;
+        let phase0 = (phase_pos.floor() as usize).min(self.num_phases - 1);
+        let phase1 = (phase0 + 1) % self.num_phases;
+        let phase_frac = phase_pos.fract() as f32;
+
+        let mut acc0 = 0f32;
+        let mut acc1 = 0f32;
+        for k in 0..self.taps_per_phase {
+            let h = self.history[self.taps_per_phase - 1 - k];
+            acc0 += h * self.banks[phase0][k];
+            acc1 += h * self.banks[phase1][k];
+        }
+        acc0 * (1.0 - phase_frac) + acc1 * phase_frac
+    }
+
+    // `Fastest`/`Linear` bracket `frac` between the two most-recently-consumed history samples
+    // (`history[len - 2]` at position 0, `history[len - 1]` at position 1) rather than the next,
+    // not-yet-consumed one — the causal history ring simply doesn't have that sample yet. `Cubic`
+    // brackets the pair one sample further back (`history[len - 3]`/`history[len - 2]`) so that
+    // `history[len - 1]` can serve as its lookahead neighbor. Each tier therefore has its own
+    // constant delay, different from `Sinc`'s centered-FIR group delay; a constant offset is
+    // inaudible and doesn't accumulate (unlike drift, it never grows over time).
+
+    fn evaluate_fastest(&self, frac: f64) -> f32 {
+        let len = self.history.len();
+        if frac < 0.5 {
+            self.history[len - 2]
+        } else {
+            self.history[len - 1]
+        }
+    }
+
+    fn evaluate_linear(&self, frac: f64) -> f32 {
+        let len = self.history.len();
+        let (p0, p1) = (self.history[len - 2], self.history[len - 1]);
+        p0 + (p1 - p0) * frac as f32
+    }
+
+    fn evaluate_cubic(&self, frac: f64) -> f32 {
+        let len = self.history.len();
+        // Interpolates between p1/p2 using p0/p3 as the Catmull-Rom neighbors; p3 (the newest
+        // history sample) is the one sample of lookahead past that bracket, which is why `Cubic`
+        // brackets one sample further back than `Linear`.
+        let (p0, p1, p2, p3) = (
+            self.history[len - 4],
+            self.history[len - 3],
+            self.history[len - 2],
+            self.history[len - 1],
+        );
+        catmull_rom(p0, p1, p2, p3, frac as f32)
+    }
+}
+
+// Catmull-Rom cubic spline through `p1`/`p2` at normalized position `t` in `[0, 1]`, using
+// `p0`/`p3` as the neighboring control points that shape the tangents.
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Resamples interleaved multi-channel audio by running one `PolyphaseResampler` per channel, as
+/// the request asked: each channel keeps its own history ring even though all channels share the
+/// same input/output rate (and so the same phase accumulator trajectory).
+pub struct ChannelResampler {
+    channels: Vec<PolyphaseResampler>,
+}
+
+impl ChannelResampler {
+    pub fn new(
+        channels_count: usize,
+        input_rate: u32,
+        output_rate: u32,
+        num_taps: usize,
+        num_phases: usize,
+    ) -> Self {
+        Self {
+            channels: (0..channels_count)
+                .map(|_| PolyphaseResampler::new(input_rate, output_rate, num_taps, num_phases))
+                .collect(),
+        }
+    }
+
+    /// Same as `new`, but selecting one of the cheaper `ResampleQuality` tiers instead of always
+    /// building the `Sinc` polyphase bank; see `PolyphaseResampler::with_quality`.
+    pub fn with_quality(
+        channels_count: usize,
+        input_rate: u32,
+        output_rate: u32,
+        quality: ResampleQuality,
+    ) -> Self {
+        Self {
+            channels: (0..channels_count)
+                .map(|_| PolyphaseResampler::with_quality(input_rate, output_rate, quality))
+                .collect(),
+        }
+    }
+
+    /// Applies the same drift correction (see `PolyphaseResampler::set_rate_correction`) to every
+    /// channel, keeping them all in phase with each other.
+    pub fn set_rate_correction(&mut self, correction: f64) {
+        for resampler in &mut self.channels {
+            resampler.set_rate_correction(correction);
+        }
+    }
+
+    /// Deinterleaves `input`, resamples each channel independently, and re-interleaves the
+    /// result.
+    pub fn process_interleaved(&mut self, input: &[f32]) -> Vec<f32> {
+        let channels_count = self.channels.len();
+        if channels_count == 0 {
+            return Vec::new();
+        }
+
+        let mut per_channel_out = vec![Vec::new(); channels_count];
+        for (c, resampler) in self.channels.iter_mut().enumerate() {
+            let channel_in: Vec<f32> = input
+                .iter()
+                .skip(c)
+                .step_by(channels_count)
+                .copied()
+                .collect();
+            resampler.process(&channel_in, &mut per_channel_out[c]);
+        }
+
+        let out_frames = per_channel_out[0].len();
+        let mut output = Vec::with_capacity(out_frames * channels_count);
+        for f in 0..out_frames {
+            for channel_out in &per_channel_out {
+                output.push(channel_out[f]);
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,212 @@
+// A single-producer/single-consumer ring buffer of interleaved `f32` samples, replacing the
+// `Arc<Mutex<VecDeque<f32>>>` hand-off between `receive_samples_loop` (the producer) and
+// `get_next_frame_batch` (the consumer, called from the real-time audio callback). The hot path on
+// both sides (`push`/`pop_into`) never blocks: they're just an atomic load, a copy, and an atomic
+// store, exactly like cubeb-coreaudio's `ringbuf`-backed design.
+//
+// `receive_samples_loop` also needs a handful of index-manipulating operations
+// (`clear`/`discard_front`/`truncate_published`/`peek_front`/`overwrite_front`) to keep rendering
+// its fade-in/cross-fade/overflow recovery logic against the ring's still-unconsumed tail, the same
+// way it used to operate directly on the shared `VecDeque` under a lock. These touch `read_index`
+// and the raw sample slots at the consumer's read cursor, which only the consumer may otherwise
+// touch - calling them directly from the producer thread while the consumer's `pop_into` runs
+// concurrently is a data race (two writers racing `read_index`, and unsynchronized reads/writes of
+// the same slots). So they're guarded by `structural_lock`, a mutex held for their whole body.
+// `pop_into` only ever `try_lock`s it: if a structural operation is in flight (rare - only while a
+// packet-loss or overflow recovery is being rendered) it reports silence for that one callback
+// instead of waiting, the same way a genuine underrun is already handled, so the real-time thread
+// still never blocks.
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Fixed-capacity (rounded up to a power of two) SPSC ring of `f32` samples.
+pub struct SampleRing {
+    buffer: Box<[UnsafeCell<f32>]>,
+    capacity: usize,
+    mask: usize,
+    write_index: AtomicUsize,
+    read_index: AtomicUsize,
+    structural_lock: Mutex<()>,
+}
+
+// Safety: `buffer` slots are only ever written by the single producer (`push`, and the structural
+// helpers below, all serialized against the consumer through `structural_lock`) and read by the
+// single consumer (`pop_into`), coordinated through the `Acquire`/`Release` ordering on
+// `write_index`/`read_index` plus `structural_lock` for anything that isn't a plain push/pop.
+unsafe impl Sync for SampleRing {}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.next_power_of_two();
+        let buffer = (0..capacity)
+            .map(|_| UnsafeCell::new(0.0f32))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
+        Self {
+            buffer,
+            capacity,
+            mask: capacity - 1,
+            write_index: AtomicUsize::new(0),
+            read_index: AtomicUsize::new(0),
+            structural_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of published, not-yet-consumed samples. Safe to call from either side.
+    pub fn len(&self) -> usize {
+        self.write_index
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.read_index.load(Ordering::Acquire))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Current occupancy as a fraction of `capacity` (`0.0` empty, `1.0` full), so a dashboard
+    /// buffer-health meter doesn't need to re-derive it from `len()`/`capacity()` itself.
+    pub fn fill_ratio(&self) -> f32 {
+        self.len() as f32 / self.capacity as f32
+    }
+
+    fn free_space(&self) -> usize {
+        self.capacity - self.len()
+    }
+
+    fn slot(&self, index: usize) -> *mut f32 {
+        self.buffer[index & self.mask].get()
+    }
+
+    /// Producer-only: appends as many of `samples` as fit, dropping any that don't. Callers size
+    /// their scratch buffer so this never truncates in practice (the ring is sized generously
+    /// relative to `average_buffer_frames_count`). Never blocks: only ever touches `write_index`,
+    /// which the consumer never writes.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let to_write = samples.len().min(self.free_space());
+        let write = self.write_index.load(Ordering::Relaxed);
+        for (i, &sample) in samples[..to_write].iter().enumerate() {
+            unsafe { *self.slot(write.wrapping_add(i)) = sample };
+        }
+        self.write_index
+            .store(write.wrapping_add(to_write), Ordering::Release);
+        to_write
+    }
+
+    /// Consumer-only: copies up to `out.len()` published samples into `out`, zero-filling any
+    /// remainder on underrun, and advances the read index by however many were actually available.
+    /// Returns the number of real (non-silence) samples copied. Never blocks: if a structural
+    /// operation (see module docs) is in flight on the producer side, this reports silence for the
+    /// call rather than waiting for it, the same way a genuine underrun is handled.
+    pub fn pop_into(&self, out: &mut [f32]) -> usize {
+        let Ok(_guard) = self.structural_lock.try_lock() else {
+            out.fill(0.0);
+            return 0;
+        };
+
+        let write = self.write_index.load(Ordering::Acquire);
+        let read = self.read_index.load(Ordering::Relaxed);
+        let available = write.wrapping_sub(read).min(out.len());
+
+        for (i, slot) in out.iter_mut().enumerate() {
+            *slot = if i < available {
+                unsafe { *self.slot(read.wrapping_add(i)) }
+            } else {
+                0.0
+            };
+        }
+
+        self.read_index
+            .store(read.wrapping_add(available), Ordering::Release);
+        available
+    }
+
+    /// Producer-only: discards every published-but-unconsumed sample, resetting the ring to empty
+    /// (mirrors the old `VecDeque::clear()` calls on packet loss).
+    pub fn clear(&self) {
+        let _guard = self.structural_lock.lock().unwrap();
+        let write = self.write_index.load(Ordering::Relaxed);
+        self.read_index.store(write, Ordering::Release);
+    }
+
+    /// Producer-only: moves every published-but-unconsumed sample out into `out` and empties the
+    /// ring (mirrors the old `VecDeque::drain(..)` that handed a shared buffer's whole backlog over
+    /// to a local scratch buffer before refilling it).
+    pub fn drain_into(&self, out: &mut Vec<f32>) {
+        let _guard = self.structural_lock.lock().unwrap();
+        let read = self.read_index.load(Ordering::Relaxed);
+        let write = self.write_index.load(Ordering::Acquire);
+        let count = write.wrapping_sub(read);
+        out.reserve(count);
+        for i in 0..count {
+            out.push(unsafe { *self.slot(read.wrapping_add(i)) });
+        }
+        self.read_index.store(write, Ordering::Release);
+    }
+
+    /// Producer-only: discards every published sample beyond the first `keep_len` (mirrors the old
+    /// `VecDeque::drain(keep_len..)` truncation).
+    pub fn truncate_published(&self, keep_len: usize) {
+        let _guard = self.structural_lock.lock().unwrap();
+        let read = self.read_index.load(Ordering::Relaxed);
+        let write = self.write_index.load(Ordering::Acquire);
+        if write.wrapping_sub(read) > keep_len {
+            self.write_index
+                .store(read.wrapping_add(keep_len), Ordering::Release);
+        }
+    }
+
+    /// Producer-only: discards the oldest `count` published samples without reading them (mirrors
+    /// the old `VecDeque::drain(0..drain_count)` used to shrink an over-full backlog).
+    pub fn discard_front(&self, count: usize) {
+        let _guard = self.structural_lock.lock().unwrap();
+        let read = self.read_index.load(Ordering::Relaxed);
+        self.read_index
+            .store(read.wrapping_add(count), Ordering::Release);
+    }
+
+    /// Producer-only: copies the oldest published-but-unconsumed samples into `out` without
+    /// consuming them. Returns how many were actually available (at most `out.len()`).
+    pub fn peek_front(&self, out: &mut [f32]) -> usize {
+        let _guard = self.structural_lock.lock().unwrap();
+        let read = self.read_index.load(Ordering::Relaxed);
+        let write = self.write_index.load(Ordering::Acquire);
+        let available = write.wrapping_sub(read).min(out.len());
+        for (i, slot) in out.iter_mut().take(available).enumerate() {
+            *slot = unsafe { *self.slot(read.wrapping_add(i)) };
+        }
+        available
+    }
+
+    /// Producer-only: copies the most recently published samples into `out` without consuming
+    /// them, used to read back the live tail for cross-fade blending. Returns how many were
+    /// actually available (at most `out.len()`). Only ever touches the producer's own just-written
+    /// slots and `write_index`/`read_index` as plain reads, so unlike the other helpers it doesn't
+    /// need `structural_lock`.
+    pub fn peek_tail(&self, out: &mut [f32]) -> usize {
+        let write = self.write_index.load(Ordering::Relaxed);
+        let read = self.read_index.load(Ordering::Acquire);
+        let available = write.wrapping_sub(read).min(out.len());
+        let start = write.wrapping_sub(available);
+        for (i, slot) in out.iter_mut().take(available).enumerate() {
+            *slot = unsafe { *self.slot(start.wrapping_add(i)) };
+        }
+        available
+    }
+
+    /// Producer-only: overwrites the first `samples.len()` published-but-unconsumed samples in
+    /// place, used to render an in-place cross-fade over the still-unconsumed head of the ring.
+    pub fn overwrite_front(&self, samples: &[f32]) {
+        let _guard = self.structural_lock.lock().unwrap();
+        let read = self.read_index.load(Ordering::Relaxed);
+        for (i, &sample) in samples.iter().enumerate() {
+            unsafe { *self.slot(read.wrapping_add(i)) = sample };
+        }
+    }
+}
@@ -1,10 +1,13 @@
 #![cfg(target_os = "android")]
 mod permissions;
+mod quirks;
+mod wakelock;
 mod wifi_manager;
 
 use permissions::check_android_permissions;
 use std::time::Duration;
 use version_compare::{Part, Version};
+use wakelock::{acquire_wakelock, release_wakelock};
 use wifi_manager::{acquire_wifi_lock, release_wifi_lock};
 
 use android_activity::{AndroidApp, MainEvent, PollEvent};
@@ -153,6 +156,9 @@ struct AppData {
     gained_focus: bool,
     window_inited: bool,
     sys_properties: Option<ALXRSystemProperties>,
+    // Set once at startup from `permissions::is_background_execution_restricted`. When `true`,
+    // the OS is likely to throttle or kill the session once the app backgrounds.
+    background_restricted: bool,
 }
 
 impl AppData {
@@ -163,10 +169,17 @@ impl AppData {
         }
         unsafe { alxr_on_pause() };
         release_wifi_lock();
+        release_wakelock();
+        if self.background_restricted {
+            log::warn!(
+                "alxr-client: app is background-execution restricted; the session is likely to be killed while backgrounded. Grant a battery-optimization exemption to avoid this."
+            );
+        }
     }
 
     fn resume(&mut self) {
         acquire_wifi_lock();
+        acquire_wakelock();
         unsafe { alxr_on_resume() };
         if let Some(sys_properties) = self.sys_properties {
             init_connections(&sys_properties);
@@ -242,12 +255,25 @@ unsafe fn run(android_app: &AndroidApp) -> Result<(), Box<dyn std::error::Error>
 
     check_android_permissions(native_activity as jni::sys::jobject, &vm)?;
 
+    let activity_obj = jni::objects::JObject::from_raw(native_activity as jni::sys::jobject);
+    let background_restricted =
+        permissions::is_background_execution_restricted(&activity_obj, &vm).unwrap_or(false);
+    if background_restricted {
+        log::warn!(
+            "alxr-client: background execution is restricted for this app; requesting a battery-optimization exemption."
+        );
+        if let Err(e) = permissions::request_ignore_battery_optimizations(&activity_obj, &vm) {
+            log::warn!("alxr-client: failed to request battery-optimization exemption: {e}");
+        }
+    }
+
     let mut app_data = AppData {
         destroy_requested: false,
         resumed: false,
         gained_focus: false,
         window_inited: false,
         sys_properties: None,
+        background_restricted,
     };
     wait_until_window_init(&android_app, &mut app_data);
     if app_data.destroy_requested || android_app.native_window().is_none() {
@@ -256,51 +282,40 @@ unsafe fn run(android_app: &AndroidApp) -> Result<(), Box<dyn std::error::Error>
     assert!(app_data.window_inited && android_app.native_window().is_some());
     log::debug!("alxr-client: is activity paused? {0} ", !app_data.resumed);
 
-    let no_linearize_srgb = APP_CONFIG.no_linearize_srgb || is_device("Lynx", &vm);
-    log::info!("alxr-client: Disable shader gamma/sRGB linearization? {no_linearize_srgb}");
-
     print_device_info(&vm);
 
+    let probed_device = quirks::ProbedDevice::probe(&vm);
+    let overrides = quirks::resolve_overrides(&quirks::quirk_table(), &probed_device);
+
+    let no_linearize_srgb = overrides
+        .no_linearize_srgb
+        .unwrap_or(APP_CONFIG.no_linearize_srgb);
+    log::info!("alxr-client: Disable shader gamma/sRGB linearization? {no_linearize_srgb}");
+
     let mut eye_tracking_type = APP_CONFIG.eye_tracking.unwrap_or(ALXREyeTrackingType::Auto);
-    // quest firmware version 71.0.0.178.498 has a crash bug in `xrSyncActions` when
-    // `XR_EXT_eye_gaze_interaction` extension is enabled.
-    match eye_tracking_type {
-        ALXREyeTrackingType::Auto | ALXREyeTrackingType::ExtEyeGazeInteraction => {
-            let build_id = get_build_property(&vm, "ID");
-            match build_id.as_str() {
-                "UP1A.231005.007.A1" | "SQ3A.220605.009.A1" => {
-                    log::warn!("alxr-client: override eye-tracking type workaround enabled.");
-                    eye_tracking_type = ALXREyeTrackingType::FBEyeTrackingSocial;
-                }
-                _ => {}
-            };
+    if matches!(
+        eye_tracking_type,
+        ALXREyeTrackingType::Auto | ALXREyeTrackingType::ExtEyeGazeInteraction
+    ) {
+        if let Some(forced) = overrides.eye_tracking {
+            eye_tracking_type = forced;
         }
-        _ => {}
-    };
+    }
 
-    let no_multi_view_rendering = APP_CONFIG.no_multi_view_rendering || is_android_emulator(&vm);
+    let no_multi_view_rendering = overrides.no_multi_view_rendering.unwrap_or(
+        APP_CONFIG.no_multi_view_rendering || is_android_emulator(&vm),
+    );
 
-    let mut no_visibility_masks = APP_CONFIG.no_visibility_masks;
-    // quest firmware v77.0.0.x has a crash bug when using `XR_KHR_visibility_mask`
-    let build_no = get_build_version_no(&vm);
-    match build_no {
-        50801630051100340 | 50801630046600340 => {
-            // quest v77.0.0.x
-            log::warn!("alxr-client: force disabling XR_KHR_visibility_mask, quest crash bug workaround for build-no.: {build_no}");
-            no_visibility_masks = true
-        }
-        _ => {}
-    };
+    let no_visibility_masks = overrides
+        .no_visibility_masks
+        .unwrap_or(APP_CONFIG.no_visibility_masks);
 
-    let xr_api_version = if is_device("Quest", &vm) {
-        // Quest bug workaround, if OpenXR apiVersion is >= 1.[0|1].49, controller aim poses are broken.
-        semver::Version::new(1, 0, 48)
-    } else {
+    let xr_api_version = overrides.xr_api_version.clone().unwrap_or_else(|| {
         APP_CONFIG
             .xr_api_version
             .clone()
             .unwrap_or(semver::Version::new(0, 0, 0))
-    };
+    });
 
     let ctx = ALXRClientCtx {
         graphicsApi: APP_CONFIG.graphics_api.unwrap_or(ALXRGraphicsApi::Auto),
@@ -0,0 +1,152 @@
+use jni::{
+    objects::{JObject, JString, JValue},
+    sys::jobject,
+    JavaVM,
+};
+
+/// Permissions the client needs granted before a session can be started.
+const REQUIRED_PERMISSIONS: &[&str] = &[
+    "android.permission.RECORD_AUDIO",
+    "android.permission.CAMERA",
+];
+
+const PERMISSION_GRANTED: i32 = 0;
+
+pub fn check_android_permissions(
+    activity: jobject,
+    vm: &JavaVM,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut env = vm.attach_current_thread()?;
+    let activity_obj = unsafe { JObject::from_raw(activity) };
+
+    let mut missing = Vec::new();
+    for permission in REQUIRED_PERMISSIONS {
+        let jpermission = env.new_string(permission)?;
+        let granted = env
+            .call_method(
+                &activity_obj,
+                "checkSelfPermission",
+                "(Ljava/lang/String;)I",
+                &[JValue::Object(&jpermission)],
+            )?
+            .i()?;
+        if granted != PERMISSION_GRANTED {
+            missing.push(*permission);
+        }
+    }
+
+    if !missing.is_empty() {
+        log::warn!("alxr-client: requesting missing permissions: {missing:?}");
+        let jarray =
+            env.new_object_array(missing.len() as i32, "java/lang/String", JObject::null())?;
+        for (idx, permission) in missing.iter().enumerate() {
+            let jpermission = env.new_string(permission)?;
+            env.set_object_array_element(&jarray, idx as i32, jpermission)?;
+        }
+        env.call_method(
+            &activity_obj,
+            "requestPermissions",
+            "([Ljava/lang/String;I)V",
+            &[JValue::Object(&jarray), JValue::Int(0)],
+        )?;
+    }
+
+    Ok(())
+}
+
+// AppOpsManager.MODE_ALLOWED and the OP_RUN_ANY_IN_BACKGROUND app-op code, mirroring how
+// `AppStateTrackerImpl` derives alarm/job restriction internally in AOSP.
+const OP_RUN_ANY_IN_BACKGROUND: i32 = 70;
+const MODE_ALLOWED: i32 = 0;
+
+/// Queries `AppOpsManager` for `OP_RUN_ANY_IN_BACKGROUND` on this app's own uid/package. Anything
+/// other than `MODE_ALLOWED` means the OS is likely to throttle or kill the session once the app
+/// backgrounds (screen off, task switch, ...), the usual cause of "connection drops when
+/// screen/app backgrounds" reports.
+pub fn is_background_execution_restricted(
+    activity: &JObject,
+    vm: &JavaVM,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut env = vm.attach_current_thread()?;
+
+    let app_ops_service_name = env.new_string("appops")?;
+    let app_ops = env
+        .call_method(
+            activity,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[JValue::Object(&app_ops_service_name)],
+        )?
+        .l()?;
+
+    let package_name = env
+        .call_method(activity, "getPackageName", "()Ljava/lang/String;", &[])?
+        .l()?;
+
+    let application_info = env
+        .call_method(
+            activity,
+            "getApplicationInfo",
+            "()Landroid/content/pm/ApplicationInfo;",
+            &[],
+        )?
+        .l()?;
+    let uid = env.get_field(&application_info, "uid", "I")?.i()?;
+
+    let mode = env
+        .call_method(
+            &app_ops,
+            "checkOpNoThrow",
+            "(IILjava/lang/String;)I",
+            &[
+                JValue::Int(OP_RUN_ANY_IN_BACKGROUND),
+                JValue::Int(uid),
+                JValue::Object(&package_name),
+            ],
+        )?
+        .i()?;
+
+    Ok(mode != MODE_ALLOWED)
+}
+
+/// Fires `ACTION_REQUEST_IGNORE_BATTERY_OPTIMIZATIONS` for this app's package, so the user can
+/// grant a battery-optimization exemption directly instead of hunting for the system setting.
+pub fn request_ignore_battery_optimizations(
+    activity: &JObject,
+    vm: &JavaVM,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut env = vm.attach_current_thread()?;
+
+    let package_name_obj = env
+        .call_method(activity, "getPackageName", "()Ljava/lang/String;", &[])?
+        .l()?;
+    let package_name: String = env.get_string(&JString::from(package_name_obj))?.into();
+
+    let uri_string = env.new_string(format!("package:{package_name}"))?;
+    let uri_class = env.find_class("android/net/Uri")?;
+    let uri = env
+        .call_static_method(
+            uri_class,
+            "parse",
+            "(Ljava/lang/String;)Landroid/net/Uri;",
+            &[JValue::Object(&uri_string)],
+        )?
+        .l()?;
+
+    let action = env.new_string("android.settings.REQUEST_IGNORE_BATTERY_OPTIMIZATIONS")?;
+    let intent_class = env.find_class("android/content/Intent")?;
+    let intent = env.new_object(
+        intent_class,
+        "(Ljava/lang/String;Landroid/net/Uri;)V",
+        &[JValue::Object(&action), JValue::Object(&uri)],
+    )?;
+
+    env.call_method(
+        activity,
+        "startActivity",
+        "(Landroid/content/Intent;)V",
+        &[JValue::Object(&intent)],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,239 @@
+// Data-driven device/driver quirk database, modeled on Firefox's GfxInfo driver blocklist: each
+// `QuirkRule` matches a device by any combination of manufacturer/model/device/build-id/firmware
+// version, and the matching rules' overrides are merged last-match-wins into the feature flags
+// passed to `ALXRClientCtx` before `alxr_init`. Adding a workaround for a new firmware crash bug
+// means adding a table entry here instead of another `is_device`/`match build_id` conditional in
+// `run()`.
+
+use crate::{
+    get_build_device, get_build_manufacturer, get_build_model, get_build_version_no,
+    get_firmware_version,
+};
+use alxr_common::ALXREyeTrackingType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersionOp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum VersionMatch {
+    Cmp(VersionOp, [u32; 3]),
+    InRange([u32; 3], [u32; 3]), // inclusive [low, high]
+}
+
+impl VersionMatch {
+    fn matches(self, version: [u32; 3]) -> bool {
+        match self {
+            VersionMatch::Cmp(VersionOp::Lt, bound) => version < bound,
+            VersionMatch::Cmp(VersionOp::Le, bound) => version <= bound,
+            VersionMatch::Cmp(VersionOp::Eq, bound) => version == bound,
+            VersionMatch::Cmp(VersionOp::Ge, bound) => version >= bound,
+            VersionMatch::Cmp(VersionOp::Gt, bound) => version > bound,
+            VersionMatch::InRange(low, high) => version >= low && version <= high,
+        }
+    }
+}
+
+/// Predicates a device must satisfy for a `QuirkRule` to apply; `None` fields are ignored.
+/// `manufacturer`/`model`/`device` match case-insensitive substrings the way `is_device` did;
+/// `build_id` is an exact match of `Build.ID`; `incremental` is an exact match of the raw
+/// `Build$VERSION.INCREMENTAL` build number; `firmware_version` matches the parsed version
+/// `get_firmware_version` derives from `Build.ID`/`Build.DISPLAY`.
+#[derive(Default, Clone, Copy)]
+pub struct DeviceMatch {
+    pub manufacturer: Option<&'static str>,
+    pub model: Option<&'static str>,
+    pub device: Option<&'static str>,
+    pub build_id: Option<&'static str>,
+    pub incremental: Option<u64>,
+    pub firmware_version: Option<VersionMatch>,
+    pub gpu_driver_version: Option<VersionMatch>,
+}
+
+impl DeviceMatch {
+    fn matches(&self, probed: &ProbedDevice) -> bool {
+        fn contains(predicate: Option<&'static str>, haystack_lower: &str) -> bool {
+            predicate.map_or(true, |needle| {
+                haystack_lower.contains(&needle.to_lowercase())
+            })
+        }
+
+        contains(self.manufacturer, &probed.manufacturer_lower)
+            && contains(self.model, &probed.model_lower)
+            && contains(self.device, &probed.device_lower)
+            && self.build_id.map_or(true, |id| probed.build_id == id)
+            && self
+                .incremental
+                .map_or(true, |inc| probed.incremental == inc)
+            && self
+                .firmware_version
+                .map_or(true, |v| v.matches(probed.firmware_version))
+            && self
+                .gpu_driver_version
+                .map_or(true, |v| v.matches(probed.gpu_driver_version))
+    }
+}
+
+/// Feature overrides a matching rule contributes; `None` means "no opinion", so an earlier
+/// matching rule's value (or the CLI/system-property default) is left alone for that field.
+#[derive(Default, Clone)]
+pub struct FeatureOverrides {
+    pub no_linearize_srgb: Option<bool>,
+    pub eye_tracking: Option<ALXREyeTrackingType>,
+    pub no_visibility_masks: Option<bool>,
+    pub xr_api_version: Option<semver::Version>,
+    pub no_multi_view_rendering: Option<bool>,
+}
+
+impl FeatureOverrides {
+    /// Merges `other` on top of `self`, last-match-wins: any field `other` sets explicitly
+    /// replaces whatever `self` already had.
+    fn merge(self, other: &FeatureOverrides) -> Self {
+        Self {
+            no_linearize_srgb: other.no_linearize_srgb.or(self.no_linearize_srgb),
+            eye_tracking: other.eye_tracking.or(self.eye_tracking),
+            no_visibility_masks: other.no_visibility_masks.or(self.no_visibility_masks),
+            xr_api_version: other.xr_api_version.clone().or(self.xr_api_version),
+            no_multi_view_rendering: other
+                .no_multi_view_rendering
+                .or(self.no_multi_view_rendering),
+        }
+    }
+}
+
+pub struct QuirkRule {
+    pub name: &'static str,
+    pub device: DeviceMatch,
+    pub overrides: FeatureOverrides,
+}
+
+/// The device/driver quirk table: add an entry here when a new firmware crash bug appears,
+/// instead of another `is_device`/`match build_id`/`match build_no` conditional in `run()`.
+pub fn quirk_table() -> Vec<QuirkRule> {
+    vec![
+        QuirkRule {
+            name: "lynx-no-srgb-linearize",
+            device: DeviceMatch {
+                model: Some("lynx"),
+                ..Default::default()
+            },
+            overrides: FeatureOverrides {
+                no_linearize_srgb: Some(true),
+                ..Default::default()
+            },
+        },
+        QuirkRule {
+            // quest firmware version 71.0.0.178.498 has a crash bug in `xrSyncActions` when
+            // `XR_EXT_eye_gaze_interaction` is enabled.
+            name: "quest-eye-gaze-xrSyncActions-crash-up1a",
+            device: DeviceMatch {
+                build_id: Some("UP1A.231005.007.A1"),
+                ..Default::default()
+            },
+            overrides: FeatureOverrides {
+                eye_tracking: Some(ALXREyeTrackingType::FBEyeTrackingSocial),
+                ..Default::default()
+            },
+        },
+        QuirkRule {
+            name: "quest-eye-gaze-xrSyncActions-crash-sq3a",
+            device: DeviceMatch {
+                build_id: Some("SQ3A.220605.009.A1"),
+                ..Default::default()
+            },
+            overrides: FeatureOverrides {
+                eye_tracking: Some(ALXREyeTrackingType::FBEyeTrackingSocial),
+                ..Default::default()
+            },
+        },
+        QuirkRule {
+            // quest firmware v77.0.0.x has a crash bug when using `XR_KHR_visibility_mask`.
+            name: "quest-v77-visibility-mask-crash-1",
+            device: DeviceMatch {
+                incremental: Some(50801630051100340),
+                ..Default::default()
+            },
+            overrides: FeatureOverrides {
+                no_visibility_masks: Some(true),
+                ..Default::default()
+            },
+        },
+        QuirkRule {
+            name: "quest-v77-visibility-mask-crash-2",
+            device: DeviceMatch {
+                incremental: Some(50801630046600340),
+                ..Default::default()
+            },
+            overrides: FeatureOverrides {
+                no_visibility_masks: Some(true),
+                ..Default::default()
+            },
+        },
+        QuirkRule {
+            // if OpenXR apiVersion is >= 1.[0|1].49, quest controller aim poses are broken.
+            name: "quest-controller-aim-pose-api-version-clamp",
+            device: DeviceMatch {
+                model: Some("quest"),
+                ..Default::default()
+            },
+            overrides: FeatureOverrides {
+                xr_api_version: Some(semver::Version::new(1, 0, 48)),
+                ..Default::default()
+            },
+        },
+    ]
+}
+
+/// A device's identifying properties, parsed firmware version, and GPU driver version, probed
+/// once via the `Build`/`Build$VERSION` getters and the engine's GPU probe.
+pub struct ProbedDevice {
+    manufacturer_lower: String,
+    model_lower: String,
+    device_lower: String,
+    build_id: String,
+    incremental: u64,
+    firmware_version: [u32; 3],
+    gpu_driver_version: [u32; 3],
+}
+
+impl ProbedDevice {
+    pub fn probe(jvm: &jni::JavaVM) -> Self {
+        let firmware_version = get_firmware_version(jvm);
+        let gpu_driver_version = alxr_common::probe_gpu_info()
+            .map(|info| info.driver_version_parts)
+            .unwrap_or([0, 0, 0]);
+        Self {
+            manufacturer_lower: get_build_manufacturer(jvm).to_lowercase(),
+            model_lower: get_build_model(jvm).to_lowercase(),
+            device_lower: get_build_device(jvm).to_lowercase(),
+            build_id: crate::get_build_property(jvm, "ID"),
+            incremental: get_build_version_no(jvm),
+            firmware_version: [
+                firmware_version.major,
+                firmware_version.minor,
+                firmware_version.patch,
+            ],
+            gpu_driver_version,
+        }
+    }
+}
+
+/// Resolves every rule in `table` against `probed`, merging matching overrides last-match-wins
+/// and logging each matched rule's name as it's applied.
+pub fn resolve_overrides(table: &[QuirkRule], probed: &ProbedDevice) -> FeatureOverrides {
+    table
+        .iter()
+        .filter(|rule| rule.device.matches(probed))
+        .fold(FeatureOverrides::default(), |acc, rule| {
+            log::warn!(
+                "alxr-client: device quirk '{}' matched, applying overrides.",
+                rule.name
+            );
+            acc.merge(&rule.overrides)
+        })
+}
@@ -0,0 +1,92 @@
+// CPU wakelock management, parallel to `wifi_manager`: without this, the CPU can still be
+// throttled or the device can doze mid-session even while the wifi lock holds the radio up,
+// causing decode/render stalls and disconnects.
+
+use jni::{
+    objects::{GlobalRef, JObject, JValue},
+    JavaVM,
+};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// PowerManager.PARTIAL_WAKE_LOCK: keeps the CPU running while letting the screen/GPU sleep,
+// which is what a headless render/decode/network loop needs to survive doze/app-standby.
+const PARTIAL_WAKE_LOCK: i32 = 0x00000001;
+
+static WAKELOCK: Mutex<Option<GlobalRef>> = Mutex::new(None);
+static REF_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn java_vm() -> Option<JavaVM> {
+    let ctx = ndk_context::android_context();
+    unsafe { JavaVM::from_raw(ctx.vm().cast()) }.ok()
+}
+
+/// Acquires a `PARTIAL_WAKE_LOCK`, reference-counted so repeated `resume()` calls across
+/// pause/resume cycles don't leak a lock per call: only the first acquire actually talks to
+/// `PowerManager`, and only the matching `release_wakelock` call lets it go.
+pub fn acquire_wakelock() {
+    if REF_COUNT.fetch_add(1, Ordering::SeqCst) > 0 {
+        return; // already held
+    }
+    if let Err(e) = try_acquire_wakelock() {
+        log::warn!("alxr-client: failed to acquire wakelock: {e}");
+        REF_COUNT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn try_acquire_wakelock() -> Result<(), Box<dyn std::error::Error>> {
+    let vm = java_vm().ok_or("no JavaVM available")?;
+    let mut env = vm.attach_current_thread()?;
+    let ctx = ndk_context::android_context();
+    let activity = unsafe { JObject::from_raw(ctx.context().cast()) };
+
+    let service_name = env.new_string("power")?;
+    let power_manager = env
+        .call_method(
+            &activity,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[JValue::Object(&service_name)],
+        )?
+        .l()?;
+
+    let tag = env.new_string("alxr-client::partial_wakelock")?;
+    let wakelock = env
+        .call_method(
+            &power_manager,
+            "newWakeLock",
+            "(ILjava/lang/String;)Landroid/os/PowerManager$WakeLock;",
+            &[JValue::Int(PARTIAL_WAKE_LOCK), JValue::Object(&tag)],
+        )?
+        .l()?;
+    env.call_method(&wakelock, "acquire", "()V", &[])?;
+
+    *WAKELOCK.lock() = Some(env.new_global_ref(wakelock)?);
+    Ok(())
+}
+
+/// Releases the wakelock once the matching number of `acquire_wakelock` calls have been undone.
+/// Safe against a stray release with no matching acquire (e.g. `pause()` before the first
+/// `resume()`): the ref-count never underflows, and a release with nothing held is a no-op.
+pub fn release_wakelock() {
+    let Ok(prev) = REF_COUNT.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+        count.checked_sub(1)
+    }) else {
+        return;
+    };
+    if prev != 1 {
+        return; // still held by another acquire
+    }
+    if let Some(wakelock) = WAKELOCK.lock().take() {
+        if let Err(e) = try_release_wakelock(&wakelock) {
+            log::warn!("alxr-client: failed to release wakelock: {e}");
+        }
+    }
+}
+
+fn try_release_wakelock(wakelock: &GlobalRef) -> Result<(), Box<dyn std::error::Error>> {
+    let vm = java_vm().ok_or("no JavaVM available")?;
+    let mut env = vm.attach_current_thread()?;
+    env.call_method(wakelock, "release", "()V", &[])?;
+    Ok(())
+}
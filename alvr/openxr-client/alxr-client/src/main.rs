@@ -32,13 +32,22 @@ const DEFAULT_DECODER_TYPE: ALXRDecoderType = ALXRDecoderType::VAAPI;
 #[cfg(target_vendor = "uwp")]
 const DEFAULT_GRAPHICS_API: ALXRGraphicsApi = ALXRGraphicsApi::D3D12;
 
-#[cfg(not(target_vendor = "uwp"))]
+// Vulkan2 (XR_KHR_vulkan_enable2) is the graphics binding Monado and other desktop Linux
+// runtimes expect; picking it explicitly here (instead of leaving it to `Auto`) means the
+// Wayland/X11 client works out of the box without relying on the runtime's own default.
+#[cfg(all(target_os = "linux", not(target_vendor = "uwp")))]
+const DEFAULT_GRAPHICS_API: ALXRGraphicsApi = ALXRGraphicsApi::Vulkan2;
+
+#[cfg(not(any(target_vendor = "uwp", target_os = "linux")))]
 const DEFAULT_GRAPHICS_API: ALXRGraphicsApi = ALXRGraphicsApi::Auto;
 
 #[cfg(not(target_os = "android"))]
 fn main() {
     println!("{:?}", *APP_CONFIG);
     let selected_api = APP_CONFIG.graphics_api.unwrap_or(DEFAULT_GRAPHICS_API);
+    if APP_CONFIG.verbose {
+        println!("selected OpenXR graphics binding: {selected_api:?}");
+    }
     let selected_decoder = APP_CONFIG.decoder_type.unwrap_or(DEFAULT_DECODER_TYPE);
     let xr_api_version = APP_CONFIG
         .xr_api_version
@@ -0,0 +1,27 @@
+use alvr_common::prelude::*;
+use alvr_sockets::ActionBindingSet;
+use parking_lot::Mutex;
+use std::path::Path;
+
+lazy_static::lazy_static! {
+    // The most recently resolved binding table, from either `--action-map`/a server-pushed
+    // `ServerControlPacket::ActionBindings`, whichever arrived last. Read back through `current`;
+    // nothing hands it to the engine, which keeps suggesting its own fixed bindings.
+    static ref CURRENT: Mutex<Option<ActionBindingSet>> = Mutex::new(None);
+}
+
+/// Loads a JSON-encoded `ActionBindingSet` from `path`, e.g. pointed to by `--action-map`.
+pub fn load_from_file(path: &Path) -> StrResult<ActionBindingSet> {
+    let contents = trace_err!(std::fs::read_to_string(path))?;
+    trace_err!(serde_json::from_str(&contents))
+}
+
+/// Installs `bindings` as the current table, overriding whatever was previously loaded/received.
+pub fn set_current(bindings: ActionBindingSet) {
+    *CURRENT.lock() = Some(bindings);
+}
+
+/// Returns a clone of the current binding table, if one has been loaded or received yet.
+pub fn current() -> Option<ActionBindingSet> {
+    CURRENT.lock().clone()
+}
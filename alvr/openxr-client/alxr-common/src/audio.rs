@@ -1,8 +1,91 @@
+// Shared between `android` and `non_android` below: both gate Opus on the same
+// `APP_CONFIG.opus_audio` switch (see its doc comment in `lib.rs` for why this lives on `Options`
+// rather than `alvr_session::AudioConfig`) AND on `peer_supports_opus()`, so turning this on
+// locally without a server that also advertised the flag falls back to PCM in that direction
+// instead of shipping Opus frames the server can't decode. Picks the frame/bitrate/application
+// defaults that fit each direction.
+fn mic_codec_config() -> alvr_audio::opus_codec::AudioCodecConfig {
+    if crate::APP_CONFIG.opus_audio && crate::peer_supports_opus() {
+        let mut config = alvr_audio::opus_codec::OpusCodecConfig::voip_default();
+        if let Some(bitrate) = crate::APP_CONFIG.opus_bitrate {
+            config.bitrate = bitrate;
+        }
+        alvr_audio::opus_codec::AudioCodecConfig::Opus(config)
+    } else {
+        alvr_audio::opus_codec::AudioCodecConfig::Pcm
+    }
+}
+
+fn game_audio_codec_config() -> alvr_audio::opus_codec::AudioCodecConfig {
+    if crate::APP_CONFIG.opus_audio && crate::peer_supports_opus() {
+        let mut config = alvr_audio::opus_codec::OpusCodecConfig::game_audio_default();
+        if let Some(bitrate) = crate::APP_CONFIG.opus_bitrate {
+            config.bitrate = bitrate;
+        }
+        alvr_audio::opus_codec::AudioCodecConfig::Opus(config)
+    } else {
+        alvr_audio::opus_codec::AudioCodecConfig::Pcm
+    }
+}
+
+// Same `APP_CONFIG`-backed stand-in as the codec configs above; see `fade_curve::FadeCurveKind`'s
+// doc comment for why this isn't sourced from `AudioConfig` directly.
+fn fade_curve_kind_config() -> alvr_audio::fade_curve::FadeCurveKind {
+    if crate::APP_CONFIG.linear_fade_curve {
+        alvr_audio::fade_curve::FadeCurveKind::Linear
+    } else {
+        alvr_audio::fade_curve::FadeCurveKind::EqualPower
+    }
+}
+
+// Same stand-in again, this time for `resampler::ResampleQuality`; see its doc comment.
+fn resample_quality_config() -> alvr_audio::resampler::ResampleQuality {
+    if crate::APP_CONFIG.fast_resampler {
+        alvr_audio::resampler::ResampleQuality::Linear
+    } else {
+        alvr_audio::resampler::ResampleQuality::default()
+    }
+}
+
+// Gated behind the same `voice_processing` option as android's `VoiceProcessor` (see its doc
+// comment): one user-facing switch turns on whichever platform's mic conditioning chain applies.
+fn mic_processing_config() -> alvr_audio::mic_processing::MicProcessingConfig {
+    if crate::APP_CONFIG.voice_processing {
+        alvr_audio::mic_processing::MicProcessingConfig {
+            high_pass_enabled: true,
+            noise_suppression_enabled: true,
+            gain_control_enabled: true,
+            ..Default::default()
+        }
+    } else {
+        alvr_audio::mic_processing::MicProcessingConfig::default()
+    }
+}
+
+// `APP_CONFIG.mic_monitor_gain`, unwrapped: `None` means mic monitoring is off, matching the
+// `non_android::record_audio_loop`/`play_audio_loop` call sites that read this.
+fn mic_monitor_gain_config() -> Option<f32> {
+    crate::APP_CONFIG.mic_monitor_gain
+}
+
+// Same stand-in again, this time for `audio_dump::AudioDumpConfig`: only `enabled` comes from
+// `APP_CONFIG`; the dump directory stays at `AudioDumpConfig::default()`'s.
+fn audio_dump_config() -> alvr_audio::audio_dump::AudioDumpConfig {
+    alvr_audio::audio_dump::AudioDumpConfig {
+        enabled: crate::APP_CONFIG.audio_dump,
+        ..Default::default()
+    }
+}
+
 #[cfg(target_os = "android")]
 mod android {
-    use alvr_common::prelude::*;
+    use alvr_common::{
+        glam::{EulerRot, Quat},
+        prelude::*,
+    };
     use alvr_session::AudioConfig;
     use alvr_sockets::{StreamReceiver, StreamSender};
+    use lazy_static::lazy_static;
     use oboe::{
         AudioInputCallback, AudioInputStreamSafe, AudioOutputCallback, AudioOutputStreamSafe,
         AudioStream, AudioStreamBase, AudioStreamBuilder, DataCallbackResult, InputPreset, Mono,
@@ -14,16 +97,288 @@ mod android {
         mem,
         sync::{Arc, mpsc as smpsc},
         thread,
+        time::{Duration, Instant},
     };
     use tokio::sync::mpsc as tmpsc;
 
+    // How stale the last tracking update may be before the spatializer gives up and falls back
+    // to plain stereo passthrough, e.g. if the tracking thread hangs or the headset sleeps.
+    const TRACKING_STALE_THRESHOLD: Duration = Duration::from_millis(250);
+
+    lazy_static! {
+        // Latest `HEAD_ID` orientation from `input_send`, paired with the instant it was recorded
+        // so the audio thread (which runs at block granularity, not tracking rate) can tell
+        // whether it's still fresh enough to spatialize against.
+        static ref HEAD_ORIENTATION: Mutex<(Quat, Instant)> =
+            Mutex::new((Quat::IDENTITY, Instant::now()));
+    }
+
+    /// Called from `input_send` at the tracking rate to keep the spatializer's head pose current.
+    pub fn update_head_orientation(orientation: Quat) {
+        *HEAD_ORIENTATION.lock() = (orientation, Instant::now());
+    }
+
+    fn current_head_orientation() -> Option<Quat> {
+        let (orientation, updated_at) = *HEAD_ORIENTATION.lock();
+        (updated_at.elapsed() <= TRACKING_STALE_THRESHOLD).then_some(orientation)
+    }
+
+    // Average adult ear-to-ear radius, used for the interaural time/level difference estimate.
+    const HEAD_RADIUS_M: f32 = 0.0875;
+    const SPEED_OF_SOUND_M_S: f32 = 343.0;
+    const MAX_ITD_DELAY_SAMPLES: usize = 32;
+
+    // Approximates a single-source HRTF (ITD + ILD + a head-shadow low-shelf) for a stereo pair
+    // assumed to originate from straight ahead in world space: as the listener turns, the pair is
+    // re-panned, delayed and shelved as if it stayed fixed in the world. This is not a measured
+    // HRIR, just a perceptually-reasonable approximation cheap enough to run per audio block.
+    struct Spatializer {
+        delay_line: VecDeque<(f32, f32)>,
+        shadow_lp: (f32, f32),
+    }
+
+    impl Spatializer {
+        fn new() -> Self {
+            Self {
+                delay_line: VecDeque::with_capacity(MAX_ITD_DELAY_SAMPLES + 1),
+                shadow_lp: (0.0, 0.0),
+            }
+        }
+
+        fn process(&mut self, buffer: &mut [f32], channels: usize, sample_rate: u32) {
+            if let Some(orientation) = current_head_orientation() {
+                let (yaw, _, _) = orientation.to_euler(EulerRot::YXZ);
+                // Turning the head left should make a world-locked source appear to shift right.
+                let azimuth = -yaw;
+                let sin_az = azimuth.sin();
+
+                // Equal-power pan law for the interaural level difference.
+                let gain_l = ((std::f32::consts::FRAC_PI_4) * (1.0 - sin_az)).cos();
+                let gain_r = ((std::f32::consts::FRAC_PI_4) * (1.0 + sin_az)).cos();
+
+                let itd_samples = ((sin_az * HEAD_RADIUS_M / SPEED_OF_SOUND_M_S)
+                    * sample_rate as f32)
+                    .abs()
+                    .min(MAX_ITD_DELAY_SAMPLES as f32) as usize;
+
+                // One-pole low-pass coefficient for the head-shadowed (far) ear; steeper shadow
+                // the more the source is off to one side.
+                let shadow_amount = sin_az.abs();
+                let shadow_coeff = 0.8 - 0.5 * shadow_amount;
+
+                for frame in buffer.chunks_exact_mut(channels) {
+                    let (l, r) = (frame[0], frame[1]);
+                    self.delay_line.push_back((l, r));
+                    if self.delay_line.len() > MAX_ITD_DELAY_SAMPLES {
+                        self.delay_line.pop_front();
+                    }
+                    let delayed_idx = self.delay_line.len().saturating_sub(1 + itd_samples);
+                    let (delayed_l, delayed_r) =
+                        self.delay_line.get(delayed_idx).copied().unwrap_or((l, r));
+
+                    // The near ear gets the undelayed signal, the far ear the ITD-delayed one.
+                    let (near_l, near_r) = if sin_az >= 0.0 {
+                        ((l, r), (delayed_l, delayed_r))
+                    } else {
+                        ((delayed_l, delayed_r), (l, r))
+                    };
+                    let mut out_l = near_l.0 * gain_l;
+                    let mut out_r = near_r.1 * gain_r;
+
+                    if sin_az < 0.0 {
+                        self.shadow_lp.0 += shadow_coeff * (out_l - self.shadow_lp.0);
+                        out_l = self.shadow_lp.0;
+                    } else if sin_az > 0.0 {
+                        self.shadow_lp.1 += shadow_coeff * (out_r - self.shadow_lp.1);
+                        out_r = self.shadow_lp.1;
+                    }
+
+                    frame[0] = out_l;
+                    frame[1] = out_r;
+                }
+            } else {
+                // Tracking is stale: pass the stereo mix through unmodified and let the delay
+                // line drain so spatialization resumes cleanly once tracking recovers.
+                self.delay_line.clear();
+                self.shadow_lp = (0.0, 0.0);
+            }
+        }
+    }
+
     // Batch duration in milliseconds for client-side microphone capture.
     // 10ms at 48kHz = 480 frames = 960 bytes, which fits well under the network MTU.
     const MIC_BATCH_MS: u32 = 10;
 
+    lazy_static! {
+        // Far-end (rendered) reference for the echo canceller: `PlayerCallback` downmixes whatever
+        // it just sent to the speaker and pushes it here; `VoiceProcessor` pulls it back out on the
+        // capture side to estimate how much of the mic signal is game audio bleeding back in
+        // through the same device, rather than the user's own voice. A single mono SPSC ring is
+        // enough since there's exactly one producer (the output stream) and one consumer (the
+        // input stream). Sized generously (well over one capture/render batch) so a stalled
+        // producer just means a stale-but-bounded reference rather than a reallocation.
+        static ref ECHO_REFERENCE: Arc<alvr_audio::SampleRing> =
+            Arc::new(alvr_audio::SampleRing::new(16384));
+    }
+
+    const ECHO_CANCELLER_TAPS: usize = 256;
+
+    // Models the acoustic path from speaker to mic as an adaptive FIR filter and subtracts the
+    // estimated echo from the mic signal, normalized least-mean-squares style: the step size is
+    // scaled by the reference's own energy, so convergence speed doesn't depend on how loud the
+    // game audio currently is (plain LMS would need a different step size per volume level).
+    struct EchoCanceller {
+        weights: Vec<f32>,
+        history: VecDeque<f32>,
+        step_size: f32,
+    }
+
+    impl EchoCanceller {
+        fn new(num_taps: usize) -> Self {
+            Self {
+                weights: vec![0.0; num_taps],
+                history: VecDeque::from(vec![0f32; num_taps]),
+                step_size: 0.5,
+            }
+        }
+
+        fn process_sample(&mut self, mic_sample: f32, reference_sample: f32) -> f32 {
+            self.history.pop_back();
+            self.history.push_front(reference_sample);
+
+            let estimate: f32 = self
+                .weights
+                .iter()
+                .zip(self.history.iter())
+                .map(|(w, x)| w * x)
+                .sum();
+            let error = mic_sample - estimate;
+
+            let energy: f32 = self.history.iter().map(|x| x * x).sum::<f32>() + 1e-6;
+            let normalized_step = self.step_size / energy;
+            for (w, x) in self.weights.iter_mut().zip(self.history.iter()) {
+                *w += normalized_step * error * x;
+            }
+
+            error
+        }
+    }
+
+    // Tracks a slowly-adapting noise floor (falls fast towards a quieter signal, rises slowly so
+    // speech peaks are never mistaken for noise) and soft-gates the signal towards it. A
+    // time-domain approximation of spectral subtraction, cheap enough to run per-sample on the
+    // real-time capture thread instead of needing a full STFT.
+    struct NoiseSuppressor {
+        noise_floor: f32,
+    }
+
+    impl NoiseSuppressor {
+        const FLOOR_RISE: f32 = 0.0002;
+        const FLOOR_FALL: f32 = 0.01;
+        const OVER_SUBTRACTION: f32 = 1.5;
+        const MIN_GAIN: f32 = 0.1;
+
+        fn new() -> Self {
+            Self { noise_floor: 0.0 }
+        }
+
+        fn process_sample(&mut self, sample: f32) -> f32 {
+            let magnitude = sample.abs();
+            if magnitude < self.noise_floor {
+                self.noise_floor += (magnitude - self.noise_floor) * Self::FLOOR_FALL;
+            } else {
+                self.noise_floor += (magnitude - self.noise_floor) * Self::FLOOR_RISE;
+            }
+
+            let excess = magnitude - Self::OVER_SUBTRACTION * self.noise_floor;
+            let gain = (excess / magnitude.max(1e-6)).clamp(Self::MIN_GAIN, 1.0);
+            sample * gain
+        }
+    }
+
+    // RMS-target automatic gain control: tracks the signal's short-term RMS level and slews the
+    // gain towards whatever would bring it to `TARGET_RMS`, the same slew-limiting approach
+    // `drift_control::DriftController` uses so a correction never steps audibly.
+    struct AutomaticGainControl {
+        rms_envelope: f32,
+        gain: f32,
+    }
+
+    impl AutomaticGainControl {
+        const TARGET_RMS: f32 = 0.1;
+        const ENVELOPE_COEFF: f32 = 0.01;
+        const MAX_GAIN: f32 = 4.0;
+        const MAX_STEP: f32 = 0.001;
+
+        fn new() -> Self {
+            Self {
+                rms_envelope: Self::TARGET_RMS * Self::TARGET_RMS,
+                gain: 1.0,
+            }
+        }
+
+        fn process_sample(&mut self, sample: f32) -> f32 {
+            self.rms_envelope += (sample * sample - self.rms_envelope) * Self::ENVELOPE_COEFF;
+            let current_rms = self.rms_envelope.sqrt().max(1e-4);
+
+            let desired_gain = (Self::TARGET_RMS / current_rms)
+                .clamp(1.0 / Self::MAX_GAIN, Self::MAX_GAIN);
+            let step = (desired_gain - self.gain).clamp(-Self::MAX_STEP, Self::MAX_STEP);
+            self.gain += step;
+
+            (sample * self.gain).clamp(-1.0, 1.0)
+        }
+    }
+
+    // WebRTC-inspired microphone conditioning (drawing on Mozilla's MediaEngineWebRTCAudio
+    // pipeline: AEC, then noise suppression, then AGC), applied to the captured block right where
+    // `RecorderCallback` used to forward it untouched. Built from the three stages above instead of
+    // the `webrtc-audio-processing` bindings, which aren't available in this build.
+    //
+    // Note: like `fade_curve`'s curve selection, this isn't exposed as a per-stage toggle on
+    // `alvr_session::AudioConfig` (not reachable from this crate); it's instead gated behind the
+    // single `--voice-processing` / `debug.alxr.voice_processing` option, the same way
+    // `spatial_audio` gates `Spatializer`.
+    struct VoiceProcessor {
+        echo_canceller: EchoCanceller,
+        noise_suppressor: NoiseSuppressor,
+        agc: AutomaticGainControl,
+        reference_scratch: Vec<f32>,
+    }
+
+    impl VoiceProcessor {
+        fn new(batch_frames_count: usize) -> Self {
+            Self {
+                echo_canceller: EchoCanceller::new(ECHO_CANCELLER_TAPS),
+                noise_suppressor: NoiseSuppressor::new(),
+                agc: AutomaticGainControl::new(),
+                reference_scratch: Vec::with_capacity(batch_frames_count),
+            }
+        }
+
+        /// Runs the AEC -> NS -> AGC chain over `frame` (mono, in place), pulling the aligned
+        /// far-end reference out of `ECHO_REFERENCE`.
+        fn process(&mut self, frame: &mut [f32]) {
+            self.reference_scratch.resize(frame.len(), 0.0);
+            ECHO_REFERENCE.pop_into(&mut self.reference_scratch);
+
+            for (sample, reference) in frame.iter_mut().zip(self.reference_scratch.iter()) {
+                let echo_cancelled = self.echo_canceller.process_sample(*sample, *reference);
+                let denoised = self.noise_suppressor.process_sample(echo_cancelled);
+                *sample = self.agc.process_sample(denoised);
+            }
+        }
+    }
+
+    // Runs on the Oboe `LowLatency` real-time thread; must not block on a lock held by a
+    // normal-priority thread (priority inversion) or allocate. `recycle_receiver` recovers a
+    // previously-sent buffer's backing allocation instead of allocating a fresh one every call.
+    // `float_scratch` is likewise reserved up front so the `voice_processor` path never allocates.
     struct RecorderCallback {
         sender: tmpsc::UnboundedSender<Vec<u8>>,
         recycle_receiver: smpsc::Receiver<Vec<u8>>,
+        voice_processor: Option<VoiceProcessor>,
+        float_scratch: Vec<f32>,
     }
 
     impl AudioInputCallback for RecorderCallback {
@@ -38,8 +393,21 @@ mod android {
             sample_buffer.clear();
             sample_buffer.reserve(frames.len() * mem::size_of::<i16>());
 
-            for frame in frames {
-                sample_buffer.extend(&frame.to_ne_bytes());
+            if let Some(voice_processor) = &mut self.voice_processor {
+                self.float_scratch.clear();
+                self.float_scratch
+                    .extend(frames.iter().map(|&s| s as f32 / i16::MAX as f32));
+
+                voice_processor.process(&mut self.float_scratch);
+
+                for &sample in &self.float_scratch {
+                    let quantized = (sample * i16::MAX as f32) as i16;
+                    sample_buffer.extend(&quantized.to_ne_bytes());
+                }
+            } else {
+                for frame in frames {
+                    sample_buffer.extend(&frame.to_ne_bytes());
+                }
             }
 
             self.sender.send(sample_buffer).ok();
@@ -85,6 +453,10 @@ mod android {
                     .set_callback(RecorderCallback {
                         sender: data_sender,
                         recycle_receiver,
+                        voice_processor: crate::APP_CONFIG
+                            .voice_processing
+                            .then(|| VoiceProcessor::new(batch_frames_count as usize)),
+                        float_scratch: Vec::with_capacity(batch_frames_count as usize),
                     })
                     .open_stream()
             )?;
@@ -99,20 +471,56 @@ mod android {
             Ok(())
         });
 
+        // `None` when Opus is off; see the equivalent note in `cpal_audio::record_audio_loop`.
+        let mut opus_encoder = match super::mic_codec_config() {
+            alvr_audio::opus_codec::AudioCodecConfig::Opus(opus_config) => {
+                Some(alvr_audio::opus_codec::Encoder::new(
+                    1,
+                    actual_sample_rate,
+                    opus_config,
+                )?)
+            }
+            alvr_audio::opus_codec::AudioCodecConfig::Pcm => None,
+        };
+        let mut batcher = opus_encoder.as_ref().map(|encoder| {
+            alvr_audio::opus_codec::FrameBatcher::new(
+                encoder.frame_size() * encoder.channels_count(),
+            )
+        });
+
         while let Some(data) = data_receiver.recv().await {
-            let mut buffer = sender.new_buffer(&(), data.len())?;
-            buffer.get_mut().extend(&data);
-            sender.send_buffer(buffer).await.ok();
-            recycle_sender.send(data).ok();
+            if let (Some(encoder), Some(batcher)) = (&mut opus_encoder, &mut batcher) {
+                for frame in batcher.push(&alvr_audio::opus_codec::pcm_s16le_to_f32(&data)) {
+                    let packet = encoder.encode(&frame)?;
+                    let mut buffer = sender.new_buffer(&(), packet.len())?;
+                    buffer.get_mut().extend(&packet);
+                    sender.send_buffer(buffer).await.ok();
+                }
+                recycle_sender.send(data).ok();
+            } else {
+                let mut buffer = sender.new_buffer(&(), data.len())?;
+                buffer.get_mut().extend(&data);
+                sender.send_buffer(buffer).await.ok();
+                recycle_sender.send(data).ok();
+            }
         }
 
         Ok(())
     }
 
+    // Also runs on the Oboe `LowLatency` real-time thread: `sample_buffer` is a wait-free SPSC
+    // ring rather than a mutex, so this callback never risks priority inversion against the
+    // normal-priority `receive_samples_loop` task that fills it; `temp_buffer`'s capacity is
+    // reserved up front so `get_next_frame_batch` never allocates here either.
     struct PlayerCallback {
-        sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+        sample_buffer: Arc<alvr_audio::SampleRing>,
         batch_frames_count: usize,
         temp_buffer: Vec<f32>,
+        sample_rate: u32,
+        spatializer: Option<Spatializer>,
+        // `Some` (and pre-sized) when `voice_processing` is on, so `ECHO_REFERENCE` gets fed for
+        // `VoiceProcessor`'s echo canceller on the capture side.
+        echo_reference_scratch: Option<Vec<f32>>,
     }
 
     impl AudioOutputCallback for PlayerCallback {
@@ -129,16 +537,30 @@ mod android {
                 "Oboe callback buffer size mismatch"
             );
             alvr_audio::get_next_frame_batch(
-                &mut *self.sample_buffer.lock(),
+                &self.sample_buffer,
                 2,
                 self.batch_frames_count,
                 &mut self.temp_buffer,
             );
 
+            if let Some(spatializer) = &mut self.spatializer {
+                spatializer.process(&mut self.temp_buffer, 2, self.sample_rate);
+            }
+
             for f in 0..out_frames.len() {
                 out_frames[f] = (self.temp_buffer[f * 2], self.temp_buffer[f * 2 + 1]);
             }
 
+            if let Some(scratch) = &mut self.echo_reference_scratch {
+                scratch.clear();
+                scratch.extend(
+                    self.temp_buffer
+                        .chunks_exact(2)
+                        .map(|frame| (frame[0] + frame[1]) * 0.5),
+                );
+                ECHO_REFERENCE.push(scratch);
+            }
+
             DataCallbackResult::Continue
         }
     }
@@ -151,7 +573,16 @@ mod android {
         let average_buffer_frames_count =
             sample_rate as usize * config.average_buffering_ms as usize / 1000;
 
-        let sample_buffer = Arc::new(Mutex::new(VecDeque::new()));
+        // Generous headroom over the overflow threshold (2 * average + 1 batch) used by
+        // `receive_samples_loop`, so a legitimate backlog never gets silently truncated by the
+        // ring. Stereo output, so 2 channels.
+        let ring_capacity = (4 * average_buffer_frames_count + 4 * batch_frames_count) * 2;
+        let sample_buffer = Arc::new(alvr_audio::SampleRing::new(ring_capacity));
+
+        // AAudio may silently clamp `sample_rate` to a rate the hardware actually supports (e.g.
+        // devices locked to 44.1 kHz); report the rate actually negotiated back to the caller so
+        // `receive_samples_loop` can resample into it instead of assuming a match.
+        let (rate_sender, rate_receiver) = smpsc::channel::<u32>();
 
         // store the stream in a thread (because !Send) and extract the playback handle
         let (_shutdown_notifier, shutdown_receiver) = smpsc::channel::<()>();
@@ -173,10 +604,17 @@ mod android {
                             sample_buffer,
                             batch_frames_count,
                             temp_buffer: Vec::with_capacity(batch_frames_count * 2),
+                            sample_rate,
+                            spatializer: crate::APP_CONFIG.spatial_audio.then(Spatializer::new),
+                            echo_reference_scratch: crate::APP_CONFIG
+                                .voice_processing
+                                .then(|| Vec::with_capacity(batch_frames_count)),
                         })
                         .open_stream()
                 )?;
 
+                rate_sender.send(stream.get_sample_rate() as u32).ok();
+
                 trace_err!(stream.start())?;
 
                 shutdown_receiver.recv().ok();
@@ -188,12 +626,23 @@ mod android {
             }
         });
 
+        let output_sample_rate = Arc::new(std::sync::atomic::AtomicU32::new(
+            rate_receiver.recv().unwrap_or(sample_rate),
+        ));
+
         alvr_audio::receive_samples_loop(
             receiver,
             sample_buffer,
             2,
             batch_frames_count,
             average_buffer_frames_count,
+            sample_rate,
+            output_sample_rate,
+            super::game_audio_codec_config(),
+            super::fade_curve_kind_config(),
+            super::resample_quality_config(),
+            // No control-channel sender is wired in here, so no `AudioStats` packets are sent.
+            None,
         )
         .await
     }
@@ -234,7 +683,18 @@ mod non_android {
     #[inline(always)]
     pub async fn record_audio_loop(sender: StreamSender<()>) -> StrResult {
         let device = get_input_audio_device()?;
-        alvr_audio::record_audio_loop(device, 1, false, sender).await
+        alvr_audio::record_audio_loop(
+            device,
+            1,
+            false,
+            sender,
+            super::mic_codec_config(),
+            super::resample_quality_config(),
+            super::mic_processing_config(),
+            super::mic_monitor_gain_config().is_some(),
+            super::audio_dump_config(),
+        )
+        .await
     }
 
     #[inline(always)]
@@ -248,7 +708,21 @@ mod non_android {
             AudioDeviceId::Default,
             AudioDeviceType::Output,
         )?;
-        alvr_audio::play_audio_loop(device, 2, sample_rate, config, receiver).await
+        alvr_audio::play_audio_loop(
+            device,
+            2,
+            sample_rate,
+            config,
+            receiver,
+            super::game_audio_codec_config(),
+            super::fade_curve_kind_config(),
+            super::resample_quality_config(),
+            super::mic_monitor_gain_config(),
+            // No control-channel sender is wired in here, so no `AudioStats` packets are sent.
+            None,
+            super::audio_dump_config(),
+        )
+        .await
     }
 }
 #[cfg(not(target_os = "android"))]
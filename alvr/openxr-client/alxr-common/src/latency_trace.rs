@@ -0,0 +1,99 @@
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Pipeline stages traced for a single frame's pose, identified by the same `target_timestamp`
+/// carried end-to-end in `Input`. Mirrors the motion-to-photon path: pose sampled -> packet
+/// handed off to the connection layer -> decode -> submitted to the compositor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    PoseSampled,
+    PacketSent,
+    DecodeBegin,
+    DecodeEnd,
+    SubmitToCompositor,
+}
+
+const STAGE_COUNT: usize = 5;
+
+fn stage_index(stage: Stage) -> usize {
+    match stage {
+        Stage::PoseSampled => 0,
+        Stage::PacketSent => 1,
+        Stage::DecodeBegin => 2,
+        Stage::DecodeEnd => 3,
+        Stage::SubmitToCompositor => 4,
+    }
+}
+
+struct FrameSpans {
+    timestamps: [Option<Instant>; STAGE_COUNT],
+}
+
+lazy_static! {
+    static ref IN_FLIGHT: Mutex<HashMap<Duration, FrameSpans>> = Mutex::new(HashMap::new());
+    static ref MOTION_TO_PHOTON_SAMPLES: Mutex<Vec<Duration>> = Mutex::new(Vec::new());
+}
+
+#[inline(always)]
+pub fn is_enabled() -> bool {
+    crate::APP_CONFIG.trace
+}
+
+/// Records `stage` having occurred now for the frame identified by `target_timestamp`. No-ops
+/// unless tracing is enabled via `--trace`/`debug.alxr.trace`. On `SubmitToCompositor` the full
+/// motion-to-photon latency for this frame (submit - pose sampled) is stashed for the shutdown
+/// summary and the in-flight entry is dropped so the map doesn't grow unbounded.
+pub fn record_stage(target_timestamp: Duration, stage: Stage) {
+    if !is_enabled() {
+        return;
+    }
+
+    let now = Instant::now();
+    let mut in_flight = IN_FLIGHT.lock();
+    let spans = in_flight.entry(target_timestamp).or_insert(FrameSpans {
+        timestamps: [None; STAGE_COUNT],
+    });
+    spans.timestamps[stage_index(stage)] = Some(now);
+
+    if stage == Stage::SubmitToCompositor {
+        if let Some(pose_sampled) = spans.timestamps[stage_index(Stage::PoseSampled)] {
+            MOTION_TO_PHOTON_SAMPLES
+                .lock()
+                .push(now.duration_since(pose_sampled));
+        }
+        in_flight.remove(&target_timestamp);
+    }
+}
+
+/// Prints mean/p50/p90/p99 motion-to-photon latency collected since tracing was enabled. Called
+/// from `shutdown()`; no-ops if tracing was never enabled or no frame reached the compositor.
+pub fn print_summary() {
+    if !is_enabled() {
+        return;
+    }
+
+    let mut samples = MOTION_TO_PHOTON_SAMPLES.lock();
+    if samples.is_empty() {
+        return;
+    }
+    samples.sort_unstable();
+
+    let percentile = |p: f32| -> Duration {
+        let idx = (((samples.len() - 1) as f32) * p).round() as usize;
+        samples[idx]
+    };
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+
+    println!(
+        "motion-to-photon latency over {} frames: mean={:.2}ms, p50={:.2}ms, p90={:.2}ms, p99={:.2}ms",
+        samples.len(),
+        mean.as_secs_f64() * 1000.0,
+        percentile(0.50).as_secs_f64() * 1000.0,
+        percentile(0.90).as_secs_f64() * 1000.0,
+        percentile(0.99).as_secs_f64() * 1000.0,
+    );
+}
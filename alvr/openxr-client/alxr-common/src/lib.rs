@@ -1,5 +1,9 @@
+mod action_bindings;
 mod connection;
 mod connection_utils;
+mod latency_trace;
+mod pose_prediction;
+mod recording;
 
 #[cfg(target_os = "android")]
 mod audio;
@@ -7,8 +11,9 @@ mod audio;
 use alvr_common::{prelude::*, ALVR_VERSION, HEAD_ID, LEFT_HAND_ID, RIGHT_HAND_ID};
 use alvr_session::Fov;
 use alvr_sockets::{
-    BatteryPacket, HeadsetInfoPacket, HiddenAreaMesh, Input, LegacyController, LegacyInput,
-    MotionData, TimeSyncPacket, ViewsConfig,
+    BatteryPacket, DepthRange, DistortionMesh, DistortionVertex, GpuInfo, HeadsetInfoPacket,
+    HiddenAreaMesh, Input, LegacyController, LegacyInput, MotionData, PassthroughMode,
+    PassthroughModePacket, TimeSyncPacket, ViewsConfig,
 };
 pub use alxr_engine_sys::*;
 use lazy_static::lazy_static;
@@ -18,6 +23,7 @@ use std::ffi::CStr;
 use std::{
     slice,
     sync::atomic::{AtomicBool, Ordering},
+    thread,
 };
 use tokio::{runtime::Runtime, sync::mpsc, sync::Notify};
 //#[cfg(not(target_os = "android"))]
@@ -39,6 +45,9 @@ pub struct Options {
     #[structopt(/*short,*/ long)]
     pub localhost: bool,
 
+    /// Selects the OpenXR graphics binding extension to create the session with, e.g. `Vulkan2`
+    /// (XR_KHR_vulkan_enable2, preferred on Monado/desktop Linux runtimes), `Vulkan`, `D3D12`,
+    /// `D3D11`. Leave unset (`Auto`) to let the runtime/engine pick.
     #[structopt(short = "g", long = "graphics", parse(from_str))]
     pub graphics_api: Option<ALXRGraphicsApi>,
 
@@ -130,9 +139,98 @@ pub struct Options {
     #[structopt(/*short,*/ long = "disable-multi-view")]
     pub no_multi_view_rendering: bool,
 
+    /// Enables head-tracked spatialization of the server audio stream, so a world-locked source
+    /// stays put as the user turns their head. Falls back to plain stereo when tracking is stale.
+    #[structopt(/*short,*/ long)]
+    pub spatial_audio: bool,
+
+    /// Enables WebRTC-style conditioning of the microphone capture path (acoustic echo
+    /// cancellation against the audio `play_audio_loop` renders, noise suppression, and automatic
+    /// gain control). Off by default since `InputPreset::VoiceCommunication` already applies the
+    /// platform's own equivalent on most devices.
+    #[structopt(/*short,*/ long)]
+    pub voice_processing: bool,
+
+    /// Compresses the mic and game-audio streams with Opus instead of shipping raw PCM, trading a
+    /// little audio quality for meaningfully less bandwidth alongside the video stream. Off by
+    /// default. `audio::mic_codec_config`/`audio::game_audio_codec_config` only select Opus when
+    /// the server's handshake response also advertised `alvr_sockets::OPUS_AUDIO_FLAG` (see
+    /// `peer_supports_opus`), so a server that doesn't understand Opus gets PCM regardless of this
+    /// flag. That response only counts once it reaches `peer_reserved_recv`, which nothing in the
+    /// client calls, so both directions stay PCM even with this flag set.
+    #[structopt(/*short,*/ long)]
+    pub opus_audio: bool,
+
+    /// Overrides the Opus bitrate (bits/s) `opus_audio` encodes at, for whichever direction is
+    /// active. Unset uses `OpusCodecConfig::voip_default`/`::game_audio_default`'s built-in
+    /// per-direction bitrate.
+    #[structopt(long)]
+    pub opus_bitrate: Option<i32>,
+
+    /// Mixes the locally-captured microphone into this client's own audio output at the given
+    /// linear gain (sidetone), so the user can hear themselves over a closed headset. Unset
+    /// (the default) disables mixing entirely. See `alvr_audio::mixer` for how the mic and
+    /// game-audio tracks get summed.
+    #[structopt(long)]
+    pub mic_monitor_gain: Option<f32>,
+
+    /// Uses the old hard linear ramp for the playback fade-in/fade-out/cross-fade instead of the
+    /// default equal-power curve. Off by default: equal-power keeps the summed energy of an
+    /// overlapping cross-fade constant instead of dipping or peaking.
+    #[structopt(/*short,*/ long)]
+    pub linear_fade_curve: bool,
+
+    /// Uses the cheap linear resampler instead of the default windowed-sinc polyphase filter for
+    /// sample-rate conversion. Off by default: the sinc filter gives cleaner stopband rejection at
+    /// a modest extra per-sample cost; this is for devices too weak to afford it.
+    #[structopt(/*short,*/ long)]
+    pub fast_resampler: bool,
+
+    /// Tees the raw capture and playback PCM streams to timestamped WAV files (plus a sibling
+    /// underrun/overrun log) in the system temp directory, for diagnosing audio glitches offline.
+    /// Off by default: see `alvr_audio::audio_dump` for the file format. The dump directory is not
+    /// configurable here.
+    #[structopt(/*short,*/ long)]
+    pub audio_dump: bool,
+
+    /// Enables motion-to-photon latency tracing: records pose-sampled/packet-sent/decode/submit
+    /// timestamps per frame and prints a mean/percentile summary on shutdown.
+    #[structopt(/*short,*/ long)]
+    pub trace: bool,
+
     /// Overrides the OpenXR Api Version used for XR instance creation, an advance option meant for runtime quirk workarounds.
     #[structopt(long = "xr-api-version")]
     pub xr_api_version: Option<Version>,
+
+    /// Loads a JSON action-binding table (action name -> interaction profile -> input source
+    /// path) and stores it as the current table (`action_bindings::current`). Nothing passes that
+    /// table to the engine, so `xrSuggestInteractionProfileBindings` keeps using the engine's
+    /// fixed bindings; a server-pushed table goes through `action_bindings_send`, which nothing
+    /// calls.
+    #[structopt(long = "action-map")]
+    pub action_map: Option<std::path::PathBuf>,
+
+    /// Records the incoming tracking/input stream (HMD pose, controllers, time-sync) to the
+    /// given file, for later deterministic replay with `--replay`.
+    #[structopt(long = "record")]
+    pub record: Option<std::path::PathBuf>,
+
+    /// Replays a `--record`-captured tracking/input stream from the given file instead of
+    /// reading live hardware input, re-injecting it into the same senders `input_send` and
+    /// `time_sync_send` use.
+    #[structopt(long = "replay")]
+    pub replay: Option<std::path::PathBuf>,
+
+    /// Upper bound on how far ahead of the last known pose `pose_prediction` extrapolates: the
+    /// horizon is the measured transport/decode/total latency from `TimeSync`, capped by this
+    /// (and by a hard 250 ms ceiling).
+    #[structopt(long, default_value = "100")]
+    pub prediction_horizon_ms: u64,
+
+    /// How many recent authoritative poses `pose_prediction` keeps per device, so a late-arriving
+    /// sample can roll back and resimulate whatever was predicted after it.
+    #[structopt(long, default_value = "16")]
+    pub max_rollback_depth: usize,
 }
 
 impl Options {
@@ -182,7 +280,21 @@ impl Options {
             passthrough_mode: Some(ALXRPassthroughMode::None),
             no_visibility_masks: false,
             no_multi_view_rendering: false,
+            spatial_audio: false,
+            voice_processing: false,
+            opus_audio: false,
+            opus_bitrate: None,
+            mic_monitor_gain: None,
+            linear_fade_curve: false,
+            fast_resampler: false,
+            audio_dump: false,
+            trace: false,
             xr_api_version: None,
+            action_map: None,
+            record: None,
+            replay: None,
+            prediction_horizon_ms: 100,
+            max_rollback_depth: 16,
         };
 
         let sys_properties = AndroidSystemProperties::new();
@@ -362,6 +474,94 @@ impl Options {
             );
         }
 
+        let property_name = "debug.alxr.spatial_audio";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.spatial_audio =
+                std::str::FromStr::from_str(value.as_str()).unwrap_or(new_options.spatial_audio);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.spatial_audio
+            );
+        }
+
+        let property_name = "debug.alxr.voice_processing";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.voice_processing = std::str::FromStr::from_str(value.as_str())
+                .unwrap_or(new_options.voice_processing);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.voice_processing
+            );
+        }
+
+        let property_name = "debug.alxr.opus_audio";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.opus_audio =
+                std::str::FromStr::from_str(value.as_str()).unwrap_or(new_options.opus_audio);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.opus_audio
+            );
+        }
+
+        let property_name = "debug.alxr.linear_fade_curve";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.linear_fade_curve = std::str::FromStr::from_str(value.as_str())
+                .unwrap_or(new_options.linear_fade_curve);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.linear_fade_curve
+            );
+        }
+
+        let property_name = "debug.alxr.opus_bitrate";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.opus_bitrate = std::str::FromStr::from_str(value.as_str()).ok();
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {:?}",
+                new_options.opus_bitrate
+            );
+        }
+
+        let property_name = "debug.alxr.mic_monitor_gain";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.mic_monitor_gain = std::str::FromStr::from_str(value.as_str()).ok();
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {:?}",
+                new_options.mic_monitor_gain
+            );
+        }
+
+        let property_name = "debug.alxr.fast_resampler";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.fast_resampler = std::str::FromStr::from_str(value.as_str())
+                .unwrap_or(new_options.fast_resampler);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.fast_resampler
+            );
+        }
+
+        let property_name = "debug.alxr.audio_dump";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.audio_dump =
+                std::str::FromStr::from_str(value.as_str()).unwrap_or(new_options.audio_dump);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.audio_dump
+            );
+        }
+
+        let property_name = "debug.alxr.trace";
+        if let Some(value) = sys_properties.get(&property_name) {
+            new_options.trace =
+                std::str::FromStr::from_str(value.as_str()).unwrap_or(new_options.trace);
+            println!(
+                "ALXR System Property: {property_name}, input: {value}, parsed-result: {}",
+                new_options.trace
+            );
+        }
+
         let property_name = "debug.alxr.xr_api_version";
         if let Some(value) = sys_properties.get(&property_name) {
             new_options.xr_api_version = std::str::FromStr::from_str(value.as_str()).ok();
@@ -403,7 +603,21 @@ impl Options {
             passthrough_mode: Some(ALXRPassthroughMode::None),
             no_visibility_masks: false,
             no_multi_view_rendering: false,
+            spatial_audio: false,
+            voice_processing: false,
+            opus_audio: false,
+            opus_bitrate: None,
+            mic_monitor_gain: None,
+            linear_fade_curve: false,
+            fast_resampler: false,
+            audio_dump: false,
+            trace: false,
             xr_api_version: None,
+            action_map: None,
+            record: None,
+            replay: None,
+            prediction_horizon_ms: 100,
+            max_rollback_depth: 16,
         };
         new_options
     }
@@ -422,7 +636,72 @@ lazy_static! {
         Mutex::new(None);
     static ref VIDEO_ERROR_REPORT_SENDER: Mutex<Option<mpsc::UnboundedSender<()>>> =
         Mutex::new(None);
+    static ref PASSTHROUGH_SENDER: Mutex<Option<mpsc::UnboundedSender<ALXRPassthroughMode>>> =
+        Mutex::new(None);
     pub static ref ON_PAUSE_NOTIFIER: Notify = Notify::new();
+    static ref RECORDER: Mutex<Option<(recording::Recorder, std::time::Instant)>> =
+        Mutex::new(None);
+    static ref PENDING_VIEWS_CONFIG: Mutex<Option<ViewsConfig>> = Mutex::new(None);
+    static ref VIEWS_CONFIG_NOTIFIER: Notify = Notify::new();
+    // Capability flags the server advertised in its handshake packet's `reserved` field (see
+    // `alvr_sockets::append_reserved_flag`/`reserved_has_flag`). Empty until the handshake
+    // completes, which `reserved_has_flag` treats the same as the server advertising nothing.
+    static ref PEER_RESERVED: Mutex<String> = Mutex::new(String::new());
+}
+
+// Several `views_config_send` calls can land within the same frame (e.g. a mode switch
+// triggering a couple of FOV updates in quick succession); debouncing them means the
+// render/encode pipeline only reconfigures eye render targets and projection matrices once for
+// the settled value instead of once per call.
+const VIEWS_CONFIG_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(16);
+
+// Builds a recorder frame tagged with the time elapsed since `--record` started and hands it to
+// `build` to fill in whichever slots this tap point has data for; no-ops if recording is off.
+fn record_frame(build: impl FnOnce(&mut recording::Frame)) {
+    if let Some((recorder, start)) = &mut *RECORDER.lock() {
+        let mut frame = recording::Frame::new(start.elapsed());
+        build(&mut frame);
+        recorder.write_frame(&frame).ok();
+    }
+}
+
+fn from_passthrough_mode(mode: PassthroughMode) -> ALXRPassthroughMode {
+    match mode {
+        PassthroughMode::None => ALXRPassthroughMode::None,
+        PassthroughMode::Blend => ALXRPassthroughMode::BlendLayer,
+        PassthroughMode::MaskedBlend => ALXRPassthroughMode::MaskLayer,
+    }
+}
+
+// Forwards a `ServerControlPacket::PassthroughMode` payload to the engine through the passthrough
+// task `init_connections` spawns, switching the blend mode live instead of only at session start.
+// `connection::connection_lifecycle_loop` does not call this, so no server packet reaches it.
+pub fn passthrough_mode_send(packet: PassthroughModePacket) {
+    if let Some(sender) = &*PASSTHROUGH_SENDER.lock() {
+        sender.send(from_passthrough_mode(packet.mode)).ok();
+    }
+}
+
+// Installs a server-pushed `ServerControlPacket::ActionBindings` table as the current one, the
+// same way `--action-map` does. `connection::connection_lifecycle_loop` does not call this, so no
+// server packet reaches it.
+pub fn action_bindings_send(packet: alvr_sockets::ActionBindingSet) {
+    action_bindings::set_current(packet);
+}
+
+// Records the server's handshake response (`ClientConfigPacket::reserved`) so
+// `audio::mic_codec_config`/`audio::game_audio_codec_config` can check what the server actually
+// advertised instead of trusting the local `--opus-audio` flag alone.
+// `connection::connection_lifecycle_loop` does not call this, so `PEER_RESERVED` stays empty.
+pub fn peer_reserved_recv(reserved: &str) {
+    *PEER_RESERVED.lock() = reserved.to_owned();
+}
+
+// Whether the peer's handshake `reserved` field advertised `OPUS_AUDIO_FLAG`. `false` (the same
+// as "peer said nothing") until `peer_reserved_recv` has run, so a server too old to send the flag
+// never gets Opus pushed at it.
+pub(crate) fn peer_supports_opus() -> bool {
+    alvr_sockets::reserved_has_flag(&PEER_RESERVED.lock(), alvr_sockets::OPUS_AUDIO_FLAG)
 }
 
 #[cfg(all(not(target_os = "android"), not(target_vendor = "uwp")))]
@@ -435,6 +714,53 @@ lazy_static! {
     pub static ref APP_CONFIG: Options = Options::from_system_properties();
 }
 
+fn cstr_buf_to_string(buf: &[std::os::raw::c_char]) -> String {
+    let bytes: Vec<u8> = buf
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Splits a driver version string into its first 3 numeric components, tolerant of vendor
+/// suffixes like "530.41.03-NVIDIA", so downstream matching can use `<`/`>=` against known-bad
+/// driver builds.
+fn parse_driver_version(version: &str) -> [u32; 3] {
+    let mut parts = [0u32; 3];
+    for (idx, part) in version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .take(3)
+        .enumerate()
+    {
+        parts[idx] = part.parse().unwrap_or(0);
+    }
+    parts
+}
+
+/// Probes the active graphics adapter's vendor/renderer/driver-version strings via the engine
+/// (`VkPhysicalDeviceProperties` on Vulkan, `GL_VENDOR`/`GL_RENDERER`/`GL_VERSION` on GLES), in
+/// the spirit of Firefox's GfxInfo GLStrings. Must be called after `alxr_init` has resolved the
+/// graphics API; returns `None` if no active graphics context exists yet.
+pub fn probe_gpu_info() -> Option<GpuInfo> {
+    let mut raw = ALXRGpuInfo {
+        vendor: [0; 128],
+        renderer: [0; 128],
+        driverVersion: [0; 128],
+    };
+    if !unsafe { alxr_get_gpu_info(&mut raw) } {
+        return None;
+    }
+    let driver_version = cstr_buf_to_string(&raw.driverVersion);
+    Some(GpuInfo {
+        vendor: cstr_buf_to_string(&raw.vendor),
+        renderer: cstr_buf_to_string(&raw.renderer),
+        driver_version_parts: parse_driver_version(&driver_version),
+        driver_version,
+    })
+}
+
 pub fn to_alxr_version(v: &semver::Version) -> ALXRVersion {
     ALXRVersion {
         major: v.major as u32,
@@ -457,12 +783,21 @@ pub fn init_connections(sys_properties: &ALXRSystemProperties) {
         };
         let preferred_refresh_rate = available_refresh_rates.last().cloned().unwrap_or(60_f32); //90.0;
 
+        // `reserved` already carries the ALVR version; append the Opus capability flag after it
+        // so a future server that understands `reserved_has_flag` can negotiate compressed audio
+        // while an older one just keeps reading the version prefix it always has.
+        let mut reserved = format!("{}", *ALVR_VERSION);
+        if APP_CONFIG.opus_audio {
+            reserved = alvr_sockets::append_reserved_flag(&reserved, alvr_sockets::OPUS_AUDIO_FLAG);
+        }
+
         let headset_info = HeadsetInfoPacket {
             recommended_eye_width: sys_properties.recommendedEyeWidth as _,
             recommended_eye_height: sys_properties.recommendedEyeHeight as _,
             available_refresh_rates,
             preferred_refresh_rate,
-            reserved: format!("{}", *ALVR_VERSION),
+            reserved,
+            gpu_info: probe_gpu_info(),
         };
 
         println!(
@@ -477,8 +812,86 @@ pub fn init_connections(sys_properties: &ALXRSystemProperties) {
         };
         let private_identity = alvr_sockets::create_identity(Some(ip_addr)).unwrap();
 
+        if let Some(action_map_path) = &APP_CONFIG.action_map {
+            match action_bindings::load_from_file(action_map_path) {
+                Ok(bindings) => action_bindings::set_current(bindings),
+                Err(e) => println!("Failed to load --action-map {action_map_path:?}: {e}"),
+            }
+        }
+
+        if let Some(record_path) = &APP_CONFIG.record {
+            match recording::Recorder::create(record_path) {
+                Ok(recorder) => *RECORDER.lock() = Some((recorder, std::time::Instant::now())),
+                Err(e) => println!("Failed to open --record {record_path:?}: {e}"),
+            }
+        }
+
+        if let Some(replay_path) = APP_CONFIG.replay.clone() {
+            thread::spawn(move || {
+                let result = recording::replay_all(&replay_path, |frame| {
+                    if let Some(bytes) = &frame.blocks[recording::SLOT_HMD_POSE] {
+                        let head_motion = recording::decode_hmd_pose(bytes)?;
+                        let left_controller = frame.blocks[recording::SLOT_CONTROLLER_LEFT]
+                            .as_deref()
+                            .map(recording::decode_controller)
+                            .transpose()?
+                            .unwrap_or_default();
+                        let right_controller = frame.blocks[recording::SLOT_CONTROLLER_RIGHT]
+                            .as_deref()
+                            .map(recording::decode_controller)
+                            .transpose()?
+                            .unwrap_or_default();
+                        if let Some(sender) = &*INPUT_SENDER.lock() {
+                            sender
+                                .send(Input {
+                                    target_timestamp: frame.timestamp,
+                                    device_motions: vec![(*HEAD_ID, head_motion)],
+                                    legacy: LegacyInput {
+                                        controllers: [left_controller, right_controller],
+                                        mounted: 1,
+                                    },
+                                })
+                                .ok();
+                        }
+                    }
+                    if let Some(bytes) = &frame.blocks[recording::SLOT_TIME_SYNC] {
+                        if let Some(sender) = &*TIME_SYNC_SENDER.lock() {
+                            sender.send(recording::decode_time_sync(bytes)?).ok();
+                        }
+                    }
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    println!("Replay of {replay_path:?} failed: {e}");
+                }
+            });
+        }
+
         let runtime = trace_err!(Runtime::new())?;
 
+        runtime.spawn(async move {
+            loop {
+                VIEWS_CONFIG_NOTIFIER.notified().await;
+                tokio::time::sleep(VIEWS_CONFIG_DEBOUNCE).await;
+                if let Some(views_config) = PENDING_VIEWS_CONFIG.lock().take() {
+                    if let Some(sender) = &*VIEWS_CONFIG_SENDER.lock() {
+                        sender.send(views_config).ok();
+                    }
+                }
+            }
+        });
+
+        let (passthrough_sender, mut passthrough_receiver) = mpsc::unbounded_channel();
+        *PASSTHROUGH_SENDER.lock() = Some(passthrough_sender);
+        runtime.spawn(async move {
+            // Dispatch live passthrough-mode changes to the engine as they arrive. This runs
+            // independently of the render/frame loop since `alxr_set_passthrough_mode` no-ops
+            // gracefully when `no_passthrough` disabled the extension.
+            while let Some(mode) = passthrough_receiver.recv().await {
+                unsafe { alxr_set_passthrough_mode(mode) };
+            }
+        });
+
         runtime.spawn(async move {
             let connection_loop =
                 connection::connection_lifecycle_loop(headset_info, &device_name, private_identity);
@@ -497,6 +910,7 @@ pub fn init_connections(sys_properties: &ALXRSystemProperties) {
 }
 
 pub fn shutdown() {
+    latency_trace::print_summary();
     ON_PAUSE_NOTIFIER.notify_waiters();
     drop(RUNTIME.lock().take());
 }
@@ -638,11 +1052,63 @@ pub extern "C" fn input_send(data_ptr: *const TrackingInfo) {
             ],
         },
     };
+    latency_trace::record_stage(input.target_timestamp, latency_trace::Stage::PoseSampled);
+    // Extrapolate each device's pose forward by the currently measured end-to-end latency
+    // (fed by `time_sync_send`), keeping enough history to roll back and resimulate if a
+    // sample ever arrives out of order.
+    let input = Input {
+        device_motions: pose_prediction::predict_input(
+            &input.device_motions,
+            input.target_timestamp,
+            APP_CONFIG.prediction_horizon_ms,
+            APP_CONFIG.max_rollback_depth,
+        ),
+        ..input
+    };
+    record_frame(|frame| {
+        frame.blocks[recording::SLOT_HMD_POSE] =
+            Some(recording::encode_hmd_pose(&input.device_motions[0].1));
+        frame.blocks[recording::SLOT_CONTROLLER_LEFT] =
+            Some(recording::encode_controller(&input.legacy.controllers[0]));
+        frame.blocks[recording::SLOT_CONTROLLER_RIGHT] =
+            Some(recording::encode_controller(&input.legacy.controllers[1]));
+    });
+    #[cfg(target_os = "android")]
+    audio::update_head_orientation(input.device_motions[0].1.orientation);
+    let target_timestamp = input.target_timestamp;
     if let Some(sender) = &*INPUT_SENDER.lock() {
         sender.send(input).ok();
+        latency_trace::record_stage(target_timestamp, latency_trace::Stage::PacketSent);
     }
 }
 
+/// Called by the engine when hardware/software decode of a frame begins, tagged with the same
+/// `targetTimestampNs` carried by `TrackingInfo`/`Input`, so `--trace` can follow a single
+/// frame's pose from sampling through to compositor submission.
+pub extern "C" fn trace_decode_begin(target_timestamp_ns: u64) {
+    latency_trace::record_stage(
+        std::time::Duration::from_nanos(target_timestamp_ns),
+        latency_trace::Stage::DecodeBegin,
+    );
+}
+
+/// Called by the engine when decode of a frame completes.
+pub extern "C" fn trace_decode_end(target_timestamp_ns: u64) {
+    latency_trace::record_stage(
+        std::time::Duration::from_nanos(target_timestamp_ns),
+        latency_trace::Stage::DecodeEnd,
+    );
+}
+
+/// Called by the engine right before a frame is submitted to the compositor; this is the final
+/// stage of the motion-to-photon trace and is what the shutdown summary measures against.
+pub extern "C" fn trace_submit_to_compositor(target_timestamp_ns: u64) {
+    latency_trace::record_stage(
+        std::time::Duration::from_nanos(target_timestamp_ns),
+        latency_trace::Stage::SubmitToCompositor,
+    );
+}
+
 #[inline(always)]
 fn make_hidden_area_meshes(view_config: &ALXRViewConfig) -> [HiddenAreaMesh; 2] {
     let empty_ham = HiddenAreaMesh {
@@ -679,32 +1145,120 @@ fn make_hidden_area_meshes(view_config: &ALXRViewConfig) -> [HiddenAreaMesh; 2]
     return hams;
 }
 
+// Marshals the dense per-eye distortion/warp mesh, using the same null/zero-count guards as
+// `make_hidden_area_meshes`: an eye with no distortion mesh (well described by `fov` alone)
+// yields `None` rather than an empty mesh, so the compositor can skip the warp pass for it.
+#[inline(always)]
+fn make_distortion_meshes(view_config: &ALXRViewConfig) -> [Option<DistortionMesh>; 2] {
+    let mut meshes: [Option<DistortionMesh>; 2] = [None, None];
+    for (view_idx, mesh) in meshes.iter_mut().enumerate() {
+        let src_mesh = &view_config.distortion_meshes[view_idx];
+        if src_mesh.vertices.is_null()
+            || src_mesh.indices.is_null()
+            || src_mesh.vertexCount == 0
+            || src_mesh.indexCount == 0
+        {
+            continue;
+        }
+        unsafe {
+            let verts_slice =
+                std::slice::from_raw_parts(src_mesh.vertices, src_mesh.vertexCount as _);
+            let indxs_slice =
+                std::slice::from_raw_parts(src_mesh.indices, src_mesh.indexCount as _);
+            let vertices = verts_slice
+                .iter()
+                .map(|vert| DistortionVertex {
+                    position: Vec2::new(vert.pos.x, vert.pos.y),
+                    red_uv: Vec2::new(vert.redUV.x, vert.redUV.y),
+                    green_uv: Vec2::new(vert.greenUV.x, vert.greenUV.y),
+                    blue_uv: Vec2::new(vert.blueUV.x, vert.blueUV.y),
+                })
+                .collect();
+            *mesh = Some(DistortionMesh {
+                vertices,
+                indices: indxs_slice.to_vec(),
+            });
+        }
+    }
+    meshes
+}
+
 pub extern "C" fn views_config_send(view_config_ptr: *const ALXRViewConfig) {
     let view_config: &ALXRViewConfig = unsafe { &*view_config_ptr };
     let eye_info = &view_config.eyeInfo;
     let fov = &view_config.eyeInfo.eyeFov;
-    if let Some(sender) = &*VIEWS_CONFIG_SENDER.lock() {
-        sender
-            .send(ViewsConfig {
-                ipd_m: eye_info.ipd,
-                fov: [
-                    Fov {
-                        left: fov[0].left,
-                        right: fov[0].right,
-                        top: fov[0].top,
-                        bottom: fov[0].bottom,
-                    },
-                    Fov {
-                        left: fov[1].left,
-                        right: fov[1].right,
-                        top: fov[1].top,
-                        bottom: fov[1].bottom,
-                    },
-                ],
-                hidden_area_meshes: make_hidden_area_meshes(&view_config),
-            })
-            .ok();
-    }
+    let views_config = ViewsConfig {
+        ipd_m: eye_info.ipd,
+        fov: [
+            Fov {
+                left: fov[0].left,
+                right: fov[0].right,
+                top: fov[0].top,
+                bottom: fov[0].bottom,
+            },
+            Fov {
+                left: fov[1].left,
+                right: fov[1].right,
+                top: fov[1].top,
+                bottom: fov[1].bottom,
+            },
+        ],
+        // The engine doesn't report per-session clip planes yet, so fall back to the
+        // default range; `off_axis_projection` still honors whatever gets plugged in here.
+        depth_range: DepthRange::default(),
+        hidden_area_meshes: make_hidden_area_meshes(&view_config),
+        distortion_meshes: make_distortion_meshes(&view_config),
+    };
+    // Stash the latest value and let the debounce task in `init_connections` forward it once
+    // things settle, instead of reconfiguring the render/encode pipeline on every call.
+    *PENDING_VIEWS_CONFIG.lock() = Some(views_config);
+    VIEWS_CONFIG_NOTIFIER.notify_one();
+}
+
+// Epsilon used to push the far plane to infinity while keeping depth-buffer precision usable,
+// following the standard "infinite far plane" trick (Lengyel, "Tightening the Precision of
+// Perspective Rendering"): m22 = epsilon - 1, m23 = (epsilon - 2) * near, in the limit far -> inf.
+const INFINITE_FAR_PLANE_EPSILON: f32 = 1.0 / (1u32 << 22) as f32;
+
+/// Builds an asymmetric (off-axis/canted) per-eye projection matrix from the four half-angles in
+/// `fov`, honoring `depth_range`. When `depth_range.far_z <= depth_range.near_z` an infinite far
+/// plane is used instead, maximizing depth-buffer precision for the common "no far clip" case.
+pub fn off_axis_projection(fov: &Fov, depth_range: DepthRange) -> [[f32; 4]; 4] {
+    let near = depth_range.near_z;
+    let far = depth_range.far_z;
+
+    let tan_left = fov.left.tan();
+    let tan_right = fov.right.tan();
+    let tan_up = fov.top.tan();
+    let tan_down = fov.bottom.tan();
+
+    let tan_width = tan_right - tan_left;
+    let tan_height = tan_up - tan_down;
+
+    let m00 = 2.0 / tan_width;
+    let m11 = 2.0 / tan_height;
+    let m02 = (tan_right + tan_left) / tan_width;
+    let m12 = (tan_up + tan_down) / tan_height;
+
+    let (m22, m23) = if far > near {
+        (
+            -(far + near) / (far - near),
+            -(2.0 * far * near) / (far - near),
+        )
+    } else {
+        // Infinite far plane: lim(far -> inf) of the standard terms above.
+        (
+            INFINITE_FAR_PLANE_EPSILON - 1.0,
+            (INFINITE_FAR_PLANE_EPSILON - 2.0) * near,
+        )
+    };
+
+    [
+        [m00, 0.0, 0.0, 0.0],
+        [0.0, m11, 0.0, 0.0],
+        [m02, m12, m22, -1.0],
+        [0.0, 0.0, m23, 0.0],
+    ]
 }
 
 pub extern "C" fn battery_send(device_id: u64, gauge_value: f32, is_plugged: bool) {
@@ -721,24 +1275,28 @@ pub extern "C" fn battery_send(device_id: u64, gauge_value: f32, is_plugged: boo
 
 pub extern "C" fn time_sync_send(data_ptr: *const TimeSync) {
     let data: &TimeSync = unsafe { &*data_ptr };
+    let time_sync = TimeSyncPacket {
+        mode: data.mode,
+        server_time: data.serverTime,
+        client_time: data.clientTime,
+        packets_lost_total: data.packetsLostTotal,
+        packets_lost_in_second: data.packetsLostInSecond,
+        average_send_latency: data.averageSendLatency,
+        average_transport_latency: data.averageTransportLatency,
+        average_decode_latency: data.averageDecodeLatency,
+        idle_time: data.idleTime,
+        fec_failure: data.fecFailure,
+        fec_failure_in_second: data.fecFailureInSecond,
+        fec_failure_total: data.fecFailureTotal,
+        fps: data.fps,
+        server_total_latency: data.serverTotalLatency,
+        tracking_recv_frame_index: data.trackingRecvFrameIndex,
+    };
+    pose_prediction::update_measured_latency(&time_sync);
+    record_frame(|frame| {
+        frame.blocks[recording::SLOT_TIME_SYNC] = Some(recording::encode_time_sync(&time_sync));
+    });
     if let Some(sender) = &*TIME_SYNC_SENDER.lock() {
-        let time_sync = TimeSyncPacket {
-            mode: data.mode,
-            server_time: data.serverTime,
-            client_time: data.clientTime,
-            packets_lost_total: data.packetsLostTotal,
-            packets_lost_in_second: data.packetsLostInSecond,
-            average_send_latency: data.averageSendLatency,
-            average_transport_latency: data.averageTransportLatency,
-            average_decode_latency: data.averageDecodeLatency,
-            idle_time: data.idleTime,
-            fec_failure: data.fecFailure,
-            fec_failure_in_second: data.fecFailureInSecond,
-            fec_failure_total: data.fecFailureTotal,
-            fps: data.fps,
-            server_total_latency: data.serverTotalLatency,
-            tracking_recv_frame_index: data.trackingRecvFrameIndex,
-        };
         sender.send(time_sync).ok();
     }
 }
@@ -0,0 +1,127 @@
+// Client-side pose prediction with rollback/resimulate, applying the same idea GGRS-style
+// netcode uses for game state to VR pose instead: extrapolate each device's pose forward by the
+// currently measured end-to-end latency, and keep enough recent history that a late-arriving
+// authoritative sample can roll back whatever was predicted after it and resimulate from there.
+
+use alvr_common::glam::{Quat, Vec3};
+use alvr_sockets::{MotionData, TimeSyncPacket};
+use parking_lot::Mutex;
+use std::{collections::HashMap, collections::VecDeque, time::Duration};
+
+// Hard ceiling on the extrapolation horizon so a latency spike can't make predicted poses diverge
+// wildly from the last known-good sample; `--prediction-horizon-ms` is clamped to this.
+const MAX_PREDICTION_HORIZON: Duration = Duration::from_millis(250);
+
+struct DeviceHistory {
+    // Most recent authoritative samples, oldest first, capped at `max_rollback_depth`.
+    raw_samples: VecDeque<(Duration, MotionData)>,
+}
+
+impl DeviceHistory {
+    fn new() -> Self {
+        Self {
+            raw_samples: VecDeque::new(),
+        }
+    }
+
+    /// Folds in a newly-arrived authoritative sample. If it's not newer than the latest one
+    /// already recorded (a late-arriving frame that should have preceded what we already predicted
+    /// from), every recorded sample at or after its timestamp is dropped, so the next `predict`
+    /// call extrapolates forward from this sample.
+    fn push_authoritative(&mut self, timestamp: Duration, pose: MotionData, max_rollback_depth: usize) {
+        while matches!(self.raw_samples.back(), Some((ts, _)) if *ts >= timestamp) {
+            self.raw_samples.pop_back();
+        }
+        self.raw_samples.push_back((timestamp, pose));
+        while self.raw_samples.len() > max_rollback_depth.max(2) {
+            self.raw_samples.pop_front();
+        }
+    }
+
+    /// Linear/angular velocity estimated from the last two authoritative samples.
+    fn estimate_velocity(&self) -> (Vec3, Vec3) {
+        let len = self.raw_samples.len();
+        if len < 2 {
+            return (Vec3::ZERO, Vec3::ZERO);
+        }
+        let (t0, p0) = &self.raw_samples[len - 2];
+        let (t1, p1) = &self.raw_samples[len - 1];
+        let dt = t1.saturating_sub(*t0).as_secs_f32();
+        if dt <= 0.0 {
+            return (Vec3::ZERO, Vec3::ZERO);
+        }
+        let linear_velocity = (p1.position - p0.position) / dt;
+        let delta_rotation = p1.orientation * p0.orientation.inverse();
+        let (axis, angle) = delta_rotation.to_axis_angle();
+        (linear_velocity, axis * (angle / dt))
+    }
+
+    /// Extrapolates the latest authoritative sample forward by `horizon`, integrating the
+    /// velocity estimated from the last couple of frames.
+    fn predict(&self, horizon: Duration) -> Option<MotionData> {
+        let (_, latest) = self.raw_samples.back()?;
+        let (linear_velocity, angular_velocity) = self.estimate_velocity();
+        let dt = horizon.as_secs_f32();
+
+        let position = latest.position + linear_velocity * dt;
+        let angle = angular_velocity.length() * dt;
+        let orientation = if angle > 1e-6 {
+            Quat::from_axis_angle(angular_velocity.normalize(), angle) * latest.orientation
+        } else {
+            latest.orientation
+        };
+
+        Some(MotionData {
+            orientation,
+            position,
+            linear_velocity: Some(linear_velocity),
+            angular_velocity: Some(angular_velocity),
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref HISTORIES: Mutex<HashMap<u64, DeviceHistory>> = Mutex::new(HashMap::new());
+    static ref MEASURED_LATENCY: Mutex<Duration> = Mutex::new(Duration::ZERO);
+}
+
+/// Feeds the measured end-to-end latency from `time_sync_send` into the prediction horizon.
+/// Gated behind the legacy time-sync mode field: only mode 1 ("network latency fill") carries
+/// populated transport/decode/total latency stats, the other modes are pings with zeroed stats.
+pub fn update_measured_latency(time_sync: &TimeSyncPacket) {
+    const TIME_SYNC_NETWORK_LATENCY_MODE: u32 = 1;
+    if time_sync.mode != TIME_SYNC_NETWORK_LATENCY_MODE {
+        return;
+    }
+    let measured = Duration::from_micros(
+        time_sync.average_transport_latency as u64 + time_sync.average_decode_latency,
+    ) + Duration::from_micros(time_sync.server_total_latency as u64);
+    *MEASURED_LATENCY.lock() = measured;
+}
+
+/// Predicts each device's pose forward by the current measured latency (capped by
+/// `--prediction-horizon-ms`), first folding `device_motions` into its rolling history so a
+/// rollback can happen if a sample arrives out of order.
+pub fn predict_input(
+    device_motions: &[(u64, MotionData)],
+    target_timestamp: Duration,
+    prediction_horizon_ms: u64,
+    max_rollback_depth: usize,
+) -> Vec<(u64, MotionData)> {
+    let horizon = MEASURED_LATENCY
+        .lock()
+        .min(Duration::from_millis(prediction_horizon_ms))
+        .min(MAX_PREDICTION_HORIZON);
+
+    let mut histories = HISTORIES.lock();
+    device_motions
+        .iter()
+        .map(|&(device_id, pose)| {
+            let history = histories
+                .entry(device_id)
+                .or_insert_with(DeviceHistory::new);
+            history.push_authoritative(target_timestamp, pose, max_rollback_depth);
+            (device_id, history.predict(horizon).unwrap_or(pose))
+        })
+        .collect()
+}
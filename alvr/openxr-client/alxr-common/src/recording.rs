@@ -0,0 +1,451 @@
+// Tracking/input recording & playback, modeled as a sequence of frames written to a flat file so
+// a captured session can be replayed deterministically without hardware. Taps the same structs
+// that flow through the FFI ingest path (`input_send`/`views_config_send`/`time_sync_send`/
+// `battery_send`) so recorded data can be re-injected into the same senders as if it arrived live.
+//
+// Frame layout: a fixed header (8-byte timestamp + a `[(offset, length); SLOT_COUNT]` table),
+// followed by the variable sub-blocks the frame actually carries. A zero-length table entry means
+// "absent this frame", so a partial frame (e.g. a lone controller update) costs only the header
+// plus whatever slots are present. Offsets are relative to the start of the sub-block region and
+// 8-byte aligned, computed by walking the table in slot order.
+
+use alvr_common::{
+    glam::{Quat, Vec2, Vec3},
+    prelude::*,
+};
+use alvr_sockets::{LegacyController, MotionData, TimeSyncPacket};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+    time::Duration,
+};
+
+pub const SLOT_HMD_POSE: usize = 0;
+pub const SLOT_CONTROLLER_LEFT: usize = 1;
+pub const SLOT_CONTROLLER_RIGHT: usize = 2;
+pub const SLOT_TIME_SYNC: usize = 3;
+const SLOT_COUNT: usize = 4;
+
+const ALIGNMENT: usize = 8;
+
+fn align_up(n: usize) -> usize {
+    (n + (ALIGNMENT - 1)) & !(ALIGNMENT - 1)
+}
+
+/// A single recorded frame: which slots are present (`blocks[i] == None` means absent) and the
+/// raw bytes for each present slot, already in the fixed binary layout the slot's type uses.
+#[derive(Default)]
+pub struct Frame {
+    pub timestamp: Duration,
+    pub blocks: [Option<Vec<u8>>; SLOT_COUNT],
+}
+
+impl Frame {
+    pub fn new(timestamp: Duration) -> Self {
+        Self {
+            timestamp,
+            blocks: Default::default(),
+        }
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> StrResult {
+        let mut table = [(0u32, 0u32); SLOT_COUNT];
+        let mut cursor = 0usize;
+        for (slot, block) in self.blocks.iter().enumerate() {
+            if let Some(bytes) = block {
+                let offset = align_up(cursor);
+                table[slot] = (offset as u32, bytes.len() as u32);
+                cursor = offset + bytes.len();
+            }
+        }
+
+        trace_err!(w.write_all(&self.timestamp.as_nanos().to_le_bytes()[..8]))?;
+        for (offset, length) in table {
+            trace_err!(w.write_all(&offset.to_le_bytes()))?;
+            trace_err!(w.write_all(&length.to_le_bytes()))?;
+        }
+
+        let mut written = 0usize;
+        for block in self.blocks.iter().flatten() {
+            let padded_offset = align_up(written);
+            for _ in written..padded_offset {
+                trace_err!(w.write_all(&[0u8]))?;
+            }
+            trace_err!(w.write_all(block))?;
+            written = padded_offset + block.len();
+        }
+
+        Ok(())
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> StrResult<Option<Self>> {
+        let mut timestamp_bytes = [0u8; 8];
+        if let Err(e) = r.read_exact(&mut timestamp_bytes) {
+            return if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                Ok(None)
+            } else {
+                fmt_e!("{e}")
+            };
+        }
+        let timestamp = Duration::from_nanos(u64::from_le_bytes(timestamp_bytes));
+
+        let mut table = [(0u32, 0u32); SLOT_COUNT];
+        for entry in &mut table {
+            let mut offset_bytes = [0u8; 4];
+            let mut length_bytes = [0u8; 4];
+            trace_err!(r.read_exact(&mut offset_bytes))?;
+            trace_err!(r.read_exact(&mut length_bytes))?;
+            *entry = (
+                u32::from_le_bytes(offset_bytes),
+                u32::from_le_bytes(length_bytes),
+            );
+        }
+
+        let body_len = table
+            .iter()
+            .filter(|(_, length)| *length > 0)
+            .map(|(offset, length)| *offset as usize + *length as usize)
+            .max()
+            .unwrap_or(0);
+        let mut body = vec![0u8; body_len];
+        trace_err!(r.read_exact(&mut body))?;
+
+        let mut blocks: [Option<Vec<u8>>; SLOT_COUNT] = Default::default();
+        for (slot, (offset, length)) in table.into_iter().enumerate() {
+            if length > 0 {
+                let start = offset as usize;
+                let end = start + length as usize;
+                blocks[slot] = Some(body[start..end].to_vec());
+            }
+        }
+
+        Ok(Some(Frame { timestamp, blocks }))
+    }
+}
+
+fn push_f32(buf: &mut Vec<u8>, v: f32) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+// Bounds-checks `len` bytes starting at `cursor` against `buf.len()` before any of the
+// `pop_*`/`take_*` helpers below index into it: recorded files are untrusted input (hand-edited,
+// truncated by a crash mid-write, or from a future format), so a short/malformed block must fail
+// with a `StrResult` error like the rest of this file's decoding, not panic the replay thread.
+fn check_len(buf: &[u8], cursor: usize, len: usize) -> StrResult {
+    if cursor + len > buf.len() {
+        return fmt_e!(
+            "recording: buffer too short (need {len} bytes at offset {cursor}, have {})",
+            buf.len()
+        );
+    }
+    Ok(())
+}
+fn pop_f32(buf: &[u8], cursor: &mut usize) -> StrResult<f32> {
+    check_len(buf, *cursor, 4)?;
+    let v = f32::from_le_bytes(buf[*cursor..*cursor + 4].try_into().unwrap());
+    *cursor += 4;
+    Ok(v)
+}
+fn push_vec3(buf: &mut Vec<u8>, v: Vec3) {
+    push_f32(buf, v.x);
+    push_f32(buf, v.y);
+    push_f32(buf, v.z);
+}
+fn pop_vec3(buf: &[u8], cursor: &mut usize) -> StrResult<Vec3> {
+    Ok(Vec3::new(
+        pop_f32(buf, cursor)?,
+        pop_f32(buf, cursor)?,
+        pop_f32(buf, cursor)?,
+    ))
+}
+fn push_quat(buf: &mut Vec<u8>, q: Quat) {
+    push_f32(buf, q.x);
+    push_f32(buf, q.y);
+    push_f32(buf, q.z);
+    push_f32(buf, q.w);
+}
+fn pop_quat(buf: &[u8], cursor: &mut usize) -> StrResult<Quat> {
+    Ok(Quat::from_xyzw(
+        pop_f32(buf, cursor)?,
+        pop_f32(buf, cursor)?,
+        pop_f32(buf, cursor)?,
+        pop_f32(buf, cursor)?,
+    ))
+}
+fn push_vec2(buf: &mut Vec<u8>, v: Vec2) {
+    push_f32(buf, v.x);
+    push_f32(buf, v.y);
+}
+fn pop_vec2(buf: &[u8], cursor: &mut usize) -> StrResult<Vec2> {
+    Ok(Vec2::new(pop_f32(buf, cursor)?, pop_f32(buf, cursor)?))
+}
+
+/// Encodes a HMD `MotionData` (orientation + position + optional velocities) into its fixed
+/// slot-0 binary layout.
+pub fn encode_hmd_pose(motion: &MotionData) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + 16 + 12 + 12 + 12);
+    push_quat(&mut buf, motion.orientation);
+    push_vec3(&mut buf, motion.position);
+    let mut flags = 0u8;
+    if motion.linear_velocity.is_some() {
+        flags |= 0b01;
+    }
+    if motion.angular_velocity.is_some() {
+        flags |= 0b10;
+    }
+    buf.push(flags);
+    buf.extend_from_slice(&[0u8; 3]); // pad to keep the velocities 4-byte aligned
+    if let Some(v) = motion.linear_velocity {
+        push_vec3(&mut buf, v);
+    }
+    if let Some(v) = motion.angular_velocity {
+        push_vec3(&mut buf, v);
+    }
+    buf
+}
+
+pub fn decode_hmd_pose(buf: &[u8]) -> StrResult<MotionData> {
+    let mut cursor = 0;
+    let orientation = pop_quat(buf, &mut cursor)?;
+    let position = pop_vec3(buf, &mut cursor)?;
+    check_len(buf, cursor, 4)?;
+    let flags = buf[cursor];
+    cursor += 4;
+    let linear_velocity = (flags & 0b01 != 0)
+        .then(|| pop_vec3(buf, &mut cursor))
+        .transpose()?;
+    let angular_velocity = (flags & 0b10 != 0)
+        .then(|| pop_vec3(buf, &mut cursor))
+        .transpose()?;
+    Ok(MotionData {
+        orientation,
+        position,
+        linear_velocity,
+        angular_velocity,
+    })
+}
+
+/// Encodes a `LegacyController` (buttons, joystick/trackpad, trigger/grip, the 19 bone
+/// rotations/positions) into its fixed slot-1/slot-2 binary layout.
+pub fn encode_controller(controller: &LegacyController) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(8 + 8 + 8 + 4 + 4 + 4 + 1 + 1 + 19 * (16 + 12));
+    buf.extend_from_slice(&controller.buttons.to_le_bytes());
+    push_vec2(&mut buf, controller.joystick_position);
+    push_vec2(&mut buf, controller.trackpad_position);
+    push_f32(&mut buf, controller.trigger_value);
+    push_f32(&mut buf, controller.grip_value);
+    buf.extend_from_slice(&controller.hand_finger_confience.to_le_bytes());
+    buf.push(controller.enabled as u8);
+    buf.push(controller.is_hand as u8);
+    for rotation in controller.bone_rotations {
+        push_quat(&mut buf, rotation);
+    }
+    for position in controller.bone_positions_base {
+        push_vec3(&mut buf, position);
+    }
+    buf
+}
+
+pub fn decode_controller(buf: &[u8]) -> StrResult<LegacyController> {
+    let mut cursor = 0;
+    check_len(buf, cursor, 8)?;
+    let buttons = u64::from_le_bytes(buf[cursor..cursor + 8].try_into().unwrap());
+    cursor += 8;
+    let joystick_position = pop_vec2(buf, &mut cursor)?;
+    let trackpad_position = pop_vec2(buf, &mut cursor)?;
+    let trigger_value = pop_f32(buf, &mut cursor)?;
+    let grip_value = pop_f32(buf, &mut cursor)?;
+    check_len(buf, cursor, 4)?;
+    let hand_finger_confience = u32::from_le_bytes(buf[cursor..cursor + 4].try_into().unwrap());
+    cursor += 4;
+    check_len(buf, cursor, 2)?;
+    let enabled = buf[cursor] != 0;
+    let is_hand = buf[cursor + 1] != 0;
+    cursor += 2;
+    let mut bone_rotations = [Quat::IDENTITY; 19];
+    for rotation in &mut bone_rotations {
+        *rotation = pop_quat(buf, &mut cursor)?;
+    }
+    let mut bone_positions_base = [Vec3::ZERO; 19];
+    for position in &mut bone_positions_base {
+        *position = pop_vec3(buf, &mut cursor)?;
+    }
+    Ok(LegacyController {
+        buttons,
+        joystick_position,
+        trackpad_position,
+        trigger_value,
+        grip_value,
+        hand_finger_confience,
+        enabled,
+        is_hand,
+        bone_rotations,
+        bone_positions_base,
+    })
+}
+
+/// Encodes a `TimeSyncPacket` into its fixed slot-3 binary layout.
+pub fn encode_time_sync(packet: &TimeSyncPacket) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + 8 * 3 + 4 * 2 + 8 + 4 * 3 + 4 + 4 + 8);
+    buf.extend_from_slice(&packet.mode.to_le_bytes());
+    buf.extend_from_slice(&packet.server_time.to_le_bytes());
+    buf.extend_from_slice(&packet.client_time.to_le_bytes());
+    buf.extend_from_slice(&packet.packets_lost_total.to_le_bytes());
+    buf.extend_from_slice(&packet.packets_lost_in_second.to_le_bytes());
+    buf.extend_from_slice(&packet.average_send_latency.to_le_bytes());
+    buf.extend_from_slice(&packet.average_transport_latency.to_le_bytes());
+    buf.extend_from_slice(&packet.average_decode_latency.to_le_bytes());
+    buf.extend_from_slice(&packet.idle_time.to_le_bytes());
+    buf.extend_from_slice(&packet.fec_failure.to_le_bytes());
+    buf.extend_from_slice(&packet.fec_failure_in_second.to_le_bytes());
+    buf.extend_from_slice(&packet.fec_failure_total.to_le_bytes());
+    push_f32(&mut buf, packet.fps);
+    buf.extend_from_slice(&packet.server_total_latency.to_le_bytes());
+    buf.extend_from_slice(&packet.tracking_recv_frame_index.to_le_bytes());
+    buf
+}
+
+pub fn decode_time_sync(buf: &[u8]) -> StrResult<TimeSyncPacket> {
+    fn take_u32(buf: &[u8], cursor: &mut usize) -> StrResult<u32> {
+        check_len(buf, *cursor, 4)?;
+        let v = u32::from_le_bytes(buf[*cursor..*cursor + 4].try_into().unwrap());
+        *cursor += 4;
+        Ok(v)
+    }
+    fn take_u64(buf: &[u8], cursor: &mut usize) -> StrResult<u64> {
+        check_len(buf, *cursor, 8)?;
+        let v = u64::from_le_bytes(buf[*cursor..*cursor + 8].try_into().unwrap());
+        *cursor += 8;
+        Ok(v)
+    }
+
+    let mut cursor = 0;
+    Ok(TimeSyncPacket {
+        mode: take_u32(buf, &mut cursor)?,
+        server_time: take_u64(buf, &mut cursor)?,
+        client_time: take_u64(buf, &mut cursor)?,
+        packets_lost_total: take_u64(buf, &mut cursor)?,
+        packets_lost_in_second: take_u64(buf, &mut cursor)?,
+        average_send_latency: take_u32(buf, &mut cursor)?,
+        average_transport_latency: take_u32(buf, &mut cursor)?,
+        average_decode_latency: take_u64(buf, &mut cursor)?,
+        idle_time: take_u32(buf, &mut cursor)?,
+        fec_failure: take_u32(buf, &mut cursor)?,
+        fec_failure_in_second: take_u64(buf, &mut cursor)?,
+        fec_failure_total: take_u64(buf, &mut cursor)?,
+        fps: pop_f32(buf, &mut cursor)?,
+        server_total_latency: take_u32(buf, &mut cursor)?,
+        tracking_recv_frame_index: take_u64(buf, &mut cursor)?,
+    })
+}
+
+/// Appends recorded frames to a flat file, one after another, for later `Player` playback.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> StrResult<Self> {
+        let file = trace_err!(File::create(path))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> StrResult {
+        frame.write_to(&mut self.writer)
+    }
+
+    pub fn flush(&mut self) -> StrResult {
+        trace_err!(self.writer.flush())
+    }
+}
+
+/// Reads frames back in order and supports seeking to an arbitrary timestamp by finding the two
+/// frames that bracket it. `Player` does not interpolate itself: `interpolate_hmd_pose` blends the
+/// HMD slot of two frames, and the controller slots have no interpolation helper.
+pub struct Player {
+    reader: BufReader<File>,
+    frames: Vec<(Duration, u64)>, // (timestamp, byte offset) index built once up-front
+}
+
+impl Player {
+    pub fn open(path: &Path) -> StrResult<Self> {
+        let file = trace_err!(File::open(path))?;
+        let mut reader = BufReader::new(file);
+
+        let mut frames = Vec::new();
+        loop {
+            let offset = trace_err!(reader.stream_position())?;
+            match trace_err!(Frame::read_from(&mut reader))? {
+                Some(frame) => frames.push((frame.timestamp, offset)),
+                None => break,
+            }
+        }
+
+        Ok(Self { reader, frames })
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn read_frame_at(&mut self, offset: u64) -> StrResult<Frame> {
+        trace_err!(self.reader.seek(SeekFrom::Start(offset)))?;
+        trace_err!(Frame::read_from(&mut self.reader))?.ok_or_else(|| "unexpected EOF".to_owned())
+    }
+
+    /// Finds the frame whose timestamp brackets `t` and returns it alongside the next frame (if
+    /// any), so pose slots can be linearly interpolated between the two for smooth playback.
+    pub fn seek(&mut self, t: Duration) -> StrResult<(Frame, Option<Frame>)> {
+        let idx = match self.frames.binary_search_by_key(&t, |(ts, _)| *ts) {
+            Ok(idx) => idx,
+            Err(0) => 0,
+            Err(idx) if idx >= self.frames.len() => self.frames.len() - 1,
+            Err(idx) => idx - 1,
+        };
+
+        let (_, offset) = self.frames[idx];
+        let current = self.read_frame_at(offset)?;
+        let next = if idx + 1 < self.frames.len() {
+            Some(self.read_frame_at(self.frames[idx + 1].1)?)
+        } else {
+            None
+        };
+        Ok((current, next))
+    }
+}
+
+/// Linearly interpolates two HMD poses by `t` in `[0, 1]`, used by playback between adjacent
+/// recorded frames instead of snapping to the nearest one.
+pub fn interpolate_hmd_pose(a: &MotionData, b: &MotionData, t: f32) -> MotionData {
+    MotionData {
+        orientation: a.orientation.slerp(b.orientation, t),
+        position: a.position.lerp(b.position, t),
+        linear_velocity: None,
+        angular_velocity: None,
+    }
+}
+
+/// Reads every frame in `path` from the start, blocking to line up with each frame's recorded
+/// timestamp, and invokes `on_frame` for each one as it becomes due, i.e. replays the capture as
+/// if it were arriving live instead of jumping straight to the end. `on_frame` can fail (e.g. a
+/// block that doesn't decode) without panicking the replay thread; the error aborts the replay.
+pub fn replay_all(path: &Path, mut on_frame: impl FnMut(Frame) -> StrResult) -> StrResult {
+    let file = trace_err!(File::open(path))?;
+    let mut reader = BufReader::new(file);
+    let replay_start = std::time::Instant::now();
+    while let Some(frame) = trace_err!(Frame::read_from(&mut reader))? {
+        let due = replay_start + frame.timestamp;
+        let now = std::time::Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+        on_frame(frame)?;
+    }
+    Ok(())
+}
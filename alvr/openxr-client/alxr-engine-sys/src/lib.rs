@@ -124,3 +124,28 @@ impl ALXRSystemProperties {
 }
 
 unsafe impl Send for ALXRGuardianData {}
+
+unsafe extern "C" {
+    /// Updates the active passthrough blend mode on a running session, re-entering the
+    /// composition-layer setup the same way `ALXRClientCtx::passthroughMode` does at session
+    /// creation. No-ops and returns `false` if `noPassthrough` disabled the extension.
+    pub fn alxr_set_passthrough_mode(mode: ALXRPassthroughMode) -> bool;
+}
+
+/// Vendor/renderer/driver-version strings for the active graphics adapter, filled in by
+/// `alxr_get_gpu_info` (from `VkPhysicalDeviceProperties` on Vulkan, `GL_VENDOR`/`GL_RENDERER`/
+/// `GL_VERSION` on GLES), in the spirit of Firefox's GfxInfo GLStrings. Each field is a
+/// NUL-terminated, possibly-truncated C string.
+#[repr(C)]
+pub struct ALXRGpuInfo {
+    pub vendor: [std::os::raw::c_char; 128],
+    pub renderer: [std::os::raw::c_char; 128],
+    pub driverVersion: [std::os::raw::c_char; 128],
+}
+
+unsafe extern "C" {
+    /// Probes the active graphics adapter's identity strings into `info`. Must only be called
+    /// after `alxr_init` has resolved the graphics API and created a device/context; returns
+    /// `false` (leaving `info` unchanged) if no active graphics context exists yet.
+    pub fn alxr_get_gpu_info(info: *mut ALXRGpuInfo) -> bool;
+}
@@ -15,17 +15,76 @@ lazy_static! {
 pub enum GpuVendor {
     Nvidia,
     Amd,
+    Intel,
     Other,
 }
 
-pub fn get_gpu_vendor() -> GpuVendor {
-    match GPU_ADAPTERS[0].get_info().vendor {
+/// The hardware video API a vendor is expected to expose, in the order ALVR should try them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreferredVideoApi {
+    Nvenc,
+    Amf,
+    QuickSync,
+    Vaapi,
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VideoCodec {
+    H264,
+    Hevc,
+    Av1,
+}
+
+/// Per-adapter summary used to pick an encoder when more than one GPU is present (e.g. a laptop's
+/// integrated + discrete GPU pair).
+pub struct GpuAdapterInfo {
+    pub name: String,
+    pub vendor: GpuVendor,
+    pub preferred_video_api: PreferredVideoApi,
+    pub is_discrete: bool,
+}
+
+impl GpuAdapterInfo {
+    /// Fast "can this adapter encode this codec" check based on the vendor alone, without
+    /// spinning up an actual encoder session. This is a coarse heuristic (plain vendor support,
+    /// not specific to the exact GPU model) good enough for startup/settings-UI filtering; the
+    /// session setup still falls back gracefully if the real encoder fails to initialize.
+    pub fn can_encode(&self, codec: VideoCodec) -> bool {
+        match (&self.vendor, codec) {
+            (GpuVendor::Nvidia, VideoCodec::H264 | VideoCodec::Hevc) => true,
+            (GpuVendor::Nvidia, VideoCodec::Av1) => false,
+            (GpuVendor::Amd, VideoCodec::H264 | VideoCodec::Hevc) => true,
+            (GpuVendor::Amd, VideoCodec::Av1) => false,
+            (GpuVendor::Intel, VideoCodec::H264 | VideoCodec::Hevc) => true,
+            (GpuVendor::Intel, VideoCodec::Av1) => false,
+            (GpuVendor::Other, _) => false,
+        }
+    }
+}
+
+fn vendor_from_pci_id(vendor_id: u32) -> GpuVendor {
+    match vendor_id {
         0x10de => GpuVendor::Nvidia,
         0x1002 => GpuVendor::Amd,
+        0x8086 => GpuVendor::Intel,
         _ => GpuVendor::Other,
     }
 }
 
+fn preferred_video_api(vendor: &GpuVendor) -> PreferredVideoApi {
+    match vendor {
+        GpuVendor::Nvidia => PreferredVideoApi::Nvenc,
+        GpuVendor::Amd => PreferredVideoApi::Amf,
+        GpuVendor::Intel => PreferredVideoApi::QuickSync,
+        GpuVendor::Other => PreferredVideoApi::Unknown,
+    }
+}
+
+pub fn get_gpu_vendor() -> GpuVendor {
+    vendor_from_pci_id(GPU_ADAPTERS[0].get_info().vendor)
+}
+
 pub fn get_gpu_names() -> Vec<String> {
     GPU_ADAPTERS
         .iter()
@@ -33,6 +92,28 @@ pub fn get_gpu_names() -> Vec<String> {
         .collect::<Vec<_>>()
 }
 
+/// Enumerates every adapter `wgpu` found into a vendor + preferred hardware video API, ranked with
+/// discrete GPUs first (the common case for picking the encoding GPU in hybrid systems).
+pub fn enumerate_gpu_capabilities() -> Vec<GpuAdapterInfo> {
+    let mut adapters = GPU_ADAPTERS
+        .iter()
+        .map(|adapter| {
+            let info = adapter.get_info();
+            let vendor = vendor_from_pci_id(info.vendor);
+            GpuAdapterInfo {
+                name: info.name,
+                preferred_video_api: preferred_video_api(&vendor),
+                vendor,
+                is_discrete: info.device_type == wgpu::DeviceType::DiscreteGpu,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    adapters.sort_by_key(|a| !a.is_discrete);
+
+    adapters
+}
+
 #[cfg(not(target_os = "macos"))]
 pub fn get_screen_size() -> StrResult<(u32, u32)> {
     use std::sync::mpsc;
@@ -39,6 +39,18 @@ pub enum HandshakePacket {
     Server(ServerHandshakePacket),
 }
 
+// GPU adapter identity probed at runtime (vendor/renderer/driver-version strings, plus the driver
+// version parsed into comparable numeric parts), in the spirit of Firefox's GfxInfo GLStrings.
+// Lets the server pick sane default decoder/color-space settings per adapter, and feeds the
+// client's own quirk matcher for driver-version-based feature gating.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GpuInfo {
+    pub vendor: String,
+    pub renderer: String,
+    pub driver_version: String,
+    pub driver_version_parts: [u32; 3],
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct HeadsetInfoPacket {
     pub recommended_eye_width: u32,
@@ -49,6 +61,28 @@ pub struct HeadsetInfoPacket {
     // reserved field is used to add features in a minor release that otherwise would break the
     // packets schema
     pub reserved: String,
+
+    pub gpu_info: Option<GpuInfo>,
+}
+
+/// `;`-separated `key=value` flags a peer can append to a `reserved` string (after whatever that
+/// packet already puts there, e.g. `HeadsetInfoPacket` puts its ALVR version first) to advertise
+/// optional features without bumping the packet schema. A peer that doesn't recognize a flag just
+/// never sees it set, so this is naturally forward- and backward-compatible: old builds fall back
+/// to whatever `reserved` meant for them before, new builds fall back to the feature's default
+/// (e.g. raw PCM audio) when the other side doesn't advertise it.
+pub const OPUS_AUDIO_FLAG: &str = "opus_audio=1";
+
+pub fn append_reserved_flag(reserved: &str, flag: &str) -> String {
+    if reserved.is_empty() {
+        flag.to_owned()
+    } else {
+        format!("{reserved};{flag}")
+    }
+}
+
+pub fn reserved_has_flag(reserved: &str, flag: &str) -> bool {
+    reserved.split(';').any(|kv| kv == flag)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -69,10 +103,47 @@ pub enum ServerControlPacket {
     Restarting,
     KeepAlive,
     TimeSync(TimeSyncPacket), // legacy
+    PassthroughMode(PassthroughModePacket),
+    ActionBindings(ActionBindingSet),
     Reserved(String),
     ReservedBuffer(Vec<u8>),
 }
 
+// A single `xrSuggestInteractionProfileBindings` entry: which action gets driven by which input
+// source path (e.g. "/user/hand/left/input/trigger/value") when the given interaction profile
+// (e.g. "/interaction_profiles/oculus/touch_controller") is the one bound to the session. This
+// lets users remap controls without recompiling the client, instead of the fixed bindings the
+// engine used to suggest.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionBinding {
+    pub action_name: String,
+    pub interaction_profile: String,
+    pub input_path: String,
+}
+
+// A full table of bindings, potentially spanning several interaction profiles at once (Touch,
+// Index, Vive, HTC, hand-interaction, ...), the way multi-runtime OpenXR layers suggest bindings
+// for every profile they know about rather than just the one currently active.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ActionBindingSet {
+    pub bindings: Vec<ActionBinding>,
+}
+
+// Blend mode for the client's passthrough composition layer. This mirrors the subset of
+// OpenXR passthrough behaviors (XR_FB_passthrough / XR_HTC_passthrough) that ALVR can switch
+// between while a session is running, decoupled from any engine-specific representation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PassthroughMode {
+    None,
+    Blend,
+    MaskedBlend,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct PassthroughModePacket {
+    pub mode: PassthroughMode,
+}
+
 // VisibilityMask following OpenXR conventions,
 // specifically XR_VISIBILITY_MASK_TYPE_HIDDEN_TRIANGLE_MESH_KHR,
 // requires a projection matrix for rendering:
@@ -85,12 +156,51 @@ pub struct HiddenAreaMesh {
     pub indices: Vec<u32>,
 }
 
+// The near/far clip planes used to build the per-eye projection matrix. An infinite far plane
+// (selected whenever `far_z <= near_z`) maximizes depth-buffer precision using the epsilon trick
+// instead of clipping at infinity; see `off_axis_projection`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct DepthRange {
+    pub near_z: f32,
+    pub far_z: f32,
+}
+
+impl Default for DepthRange {
+    fn default() -> Self {
+        Self {
+            near_z: 0.05,
+            far_z: 100.0,
+        }
+    }
+}
+
+// A dense per-eye warp mesh carrying, per vertex, the screen-space position plus a separate
+// sample UV for each color channel (as in the VivePro2 lens protocol), so the compositor can
+// correct barrel/chromatic distortion for HMDs whose lenses aren't well-described by the
+// parametric FOV alone.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DistortionVertex {
+    pub position: Vec2,
+    pub red_uv: Vec2,
+    pub green_uv: Vec2,
+    pub blue_uv: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DistortionMesh {
+    pub vertices: Vec<DistortionVertex>,
+    pub indices: Vec<u32>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ViewsConfig {
     // Note: the head-to-eye transform is always a translation along the x axis
     pub ipd_m: f32,
     pub fov: [Fov; 2],
+    pub depth_range: DepthRange,
     pub hidden_area_meshes: [HiddenAreaMesh; 2],
+    // `None` when the HMD's lenses are well described by `fov` alone and no warp pass is needed.
+    pub distortion_meshes: [Option<DistortionMesh>; 2],
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -100,6 +210,16 @@ pub struct BatteryPacket {
     pub is_plugged: bool,
 }
 
+// Audio pipeline health, reported periodically so the dashboard can chart it and the server can
+// react (lower the bitrate, widen the jitter buffer) when the client's playback is struggling.
+// Mirrors a subset of the richer local snapshot in `alvr_audio::diagnostics::AudioStats`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AudioStatsPacket {
+    pub underruns: u64,
+    pub overruns: u64,
+    pub buffer_frames: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum ClientControlPacket {
     PlayspaceSync(Vec2),
@@ -108,6 +228,7 @@ pub enum ClientControlPacket {
     StreamReady,
     ViewsConfig(ViewsConfig),
     Battery(BatteryPacket),
+    AudioStats(AudioStatsPacket),
     TimeSync(TimeSyncPacket), // legacy
     VideoErrorReport,         // legacy
     Reserved(String),
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Android APK flavor: which store/runtime's OpenXR loader and package id to target.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AndroidFlavor {
+    Generic,
+    OculusQuest,
+    Pico,
+    PicoV4,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FlagsConfig {
+    pub release: Option<bool>,
+    pub no_nvidia: Option<bool>,
+    pub bundle_ffmpeg: Option<bool>,
+    pub system_ffmpeg: Option<bool>,
+    pub fetch_crates: Option<bool>,
+    pub reproducible: Option<bool>,
+    /// Rust target triple to cross-compile for; see `AlxBuildFlags::target_os`.
+    pub target: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AndroidConfig {
+    pub sdk_path: Option<PathBuf>,
+    pub ndk_path: Option<PathBuf>,
+    pub min_api_version: Option<u32>,
+    pub build_tools_version: Option<String>,
+    pub keystore_path: Option<PathBuf>,
+    pub keystore_pass: Option<String>,
+    pub package_name: Option<String>,
+    pub flavor: Option<AndroidFlavor>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FfmpegConfig {
+    pub version: Option<String>,
+    pub enable_decoders: Option<bool>,
+}
+
+/// Deserialized `alxr-build.toml`: lets a developer commit their environment (SDK/NDK paths,
+/// keystore, preferred platform/flavor, FFmpeg version) once instead of retyping it as CLI flags
+/// on every `cargo xtask` invocation. Every field is optional; a CLI flag that was actually passed
+/// always takes precedence over the same setting in this file (see `resolve_flag`/`resolve_value`
+/// below), so the file only fills in whatever the command line left unspecified. `main` reads only
+/// `root`, `platform`, `flags.release`, `flags.no_nvidia` and `ffmpeg.version`; the other fields
+/// are parsed but not used.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BuildConfig {
+    pub root: Option<String>,
+    pub platform: Option<String>,
+    pub flags: FlagsConfig,
+    pub android: AndroidConfig,
+    pub ffmpeg: FfmpegConfig,
+}
+
+impl BuildConfig {
+    pub const DEFAULT_FILENAME: &'static str = "alxr-build.toml";
+
+    /// Looks for `alxr-build.toml` in the current directory. Returns `None` when it's simply
+    /// absent, since committing one is opt-in; panics on a present-but-malformed file so a typo
+    /// doesn't silently fall back to defaults.
+    pub fn load_default() -> Option<Self> {
+        Self::load_from(std::path::Path::new(Self::DEFAULT_FILENAME))
+    }
+
+    pub fn load_from(path: &std::path::Path) -> Option<Self> {
+        if !path.exists() {
+            return None;
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+        Some(
+            toml::from_str(&contents)
+                .unwrap_or_else(|e| panic!("failed to parse {}: {e}", path.display())),
+        )
+    }
+}
+
+/// Resolves a CLI presence-flag (e.g. `--no-nvidia`, `args.contains(...)`) against the same
+/// setting in an `alxr-build.toml`: the flag always wins when it was actually passed, otherwise
+/// the file's value is used, otherwise `default`.
+pub fn resolve_flag(cli_present: bool, file_value: Option<bool>, default: bool) -> bool {
+    if cli_present {
+        true
+    } else {
+        file_value.unwrap_or(default)
+    }
+}
+
+/// Resolves a CLI value-flag (e.g. `--platform <NAME>`) against the same setting in an
+/// `alxr-build.toml`, preferring the CLI value when present.
+pub fn resolve_value<T>(cli_value: Option<T>, file_value: Option<T>) -> Option<T> {
+    cli_value.or(file_value)
+}
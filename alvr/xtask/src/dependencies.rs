@@ -1,6 +1,6 @@
 use crate::command::{self, run_as_bash_in as bash_in};
 use alvr_filesystem as afs;
-use std::{fs, io::BufRead, path::Path};
+use std::{collections::HashSet, fs, io::BufRead, path::Path};
 
 fn download_and_extract_zip(url: &str, destination: &Path) {
     let zip_file = afs::deps_dir().join("temp_download.zip");
@@ -79,10 +79,331 @@ fn patch_rpath(lib_dir: &Path) {
     }
 }
 
+/// Which `ffmpeg -hide_banner` listing a feature should be checked against after the build, so a
+/// configure flag that got silently dropped (e.g. because a dependency wasn't found) is caught
+/// instead of shipping a degraded ffmpeg.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FfmpegFeatureKind {
+    Encoder,
+    Decoder,
+    Hwaccel,
+}
+
+/// One compile-time-relevant FFmpeg feature: the `./configure` token it contributes and (for
+/// anything the `-encoders`/`-decoders`/`-hwaccels` listings report on) the name to check for
+/// post-build. Modeled on ffmpeg-sys's own feature-table approach so callers assemble a validated
+/// set of flags instead of concatenating `--enable-*` strings by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FfmpegFeature {
+    EncoderH264Nvenc,
+    EncoderHevcNvenc,
+    DecoderH264Nvdec,
+    DecoderHevcNvdec,
+    DecoderH264Cuvid,
+    DecoderHevcCuvid,
+    HwaccelH264Nvdec,
+    HwaccelHevcNvdec,
+    HwaccelH264Cuvid,
+    HwaccelHevcCuvid,
+    HwaccelH264Nvenc,
+    HwaccelHevcNvenc,
+    EncoderH264Vaapi,
+    EncoderHevcVaapi,
+    HwaccelH264Vaapi,
+    HwaccelHevcVaapi,
+    DecoderH264Vaapi,
+    DecoderHevcVaapi,
+    EncoderLibx264,
+    EncoderLibx264Rgb,
+    EncoderLibx265,
+    DecoderLibx264,
+    DecoderLibx265,
+    FilterScale,
+    FilterScaleVaapi,
+    Vulkan,
+    Libdrm,
+    DecoderH264Vdpau,
+    DecoderHevcVdpau,
+    HwaccelH264Vdpau,
+    HwaccelHevcVdpau,
+}
+
+/// Hardware decode path to wire into the Linux from-source build, following the yuzu approach of
+/// picking a backend per platform/vendor (D3D11VA on Windows, CUVID/VDPAU on Nvidia, VAAPI on
+/// AMD/Intel) so the decoder can fall back gracefully when the preferred one isn't available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeBackend {
+    Vaapi,
+    Vdpau,
+    /// Windows-only; the Linux from-source build can't enable this, it's only meaningful for the
+    /// prebuilt BtbN binary used by `extract_ffmpeg_windows`.
+    D3d11va,
+}
+
+impl DecodeBackend {
+    fn is_supported_by_linux_build(self) -> bool {
+        matches!(self, DecodeBackend::Vaapi | DecodeBackend::Vdpau)
+    }
+}
+
+impl FfmpegFeature {
+    fn configure_token(self) -> &'static str {
+        use FfmpegFeature::*;
+        match self {
+            EncoderH264Nvenc => "--enable-encoder=h264_nvenc",
+            EncoderHevcNvenc => "--enable-encoder=hevc_nvenc",
+            DecoderH264Nvdec => "--enable-decoder=h264_nvdec",
+            DecoderHevcNvdec => "--enable-decoder=hevc_nvdec",
+            DecoderH264Cuvid => "--enable-decoder=h264_cuvid",
+            DecoderHevcCuvid => "--enable-decoder=hevc_cuvid",
+            HwaccelH264Nvdec => "--enable-hwaccel=h264_nvdec",
+            HwaccelHevcNvdec => "--enable-hwaccel=hevc_nvdec",
+            HwaccelH264Cuvid => "--enable-hwaccel=h264_cuvid",
+            HwaccelHevcCuvid => "--enable-hwaccel=hevc_cuvid",
+            HwaccelH264Nvenc => "--enable-hwaccel=h264_nvenc",
+            HwaccelHevcNvenc => "--enable-hwaccel=hevc_nvenc",
+            EncoderH264Vaapi => "--enable-encoder=h264_vaapi",
+            EncoderHevcVaapi => "--enable-encoder=hevc_vaapi",
+            HwaccelH264Vaapi => "--enable-hwaccel=h264_vaapi",
+            HwaccelHevcVaapi => "--enable-hwaccel=hevc_vaapi",
+            DecoderH264Vaapi => "--enable-decoder=h264_vaapi",
+            DecoderHevcVaapi => "--enable-decoder=hevc_vaapi",
+            EncoderLibx264 => "--enable-encoder=libx264",
+            EncoderLibx264Rgb => "--enable-encoder=libx264rgb",
+            EncoderLibx265 => "--enable-encoder=libx265",
+            DecoderLibx264 => "--enable-decoder=libx264",
+            DecoderLibx265 => "--enable-decoder=libx265",
+            FilterScale => "--enable-filter=scale",
+            FilterScaleVaapi => "--enable-filter=scale_vaapi",
+            Vulkan => "--enable-vulkan",
+            Libdrm => "--enable-libdrm",
+            DecoderH264Vdpau => "--enable-decoder=h264_vdpau",
+            DecoderHevcVdpau => "--enable-decoder=hevc_vdpau",
+            HwaccelH264Vdpau => "--enable-hwaccel=h264_vdpau",
+            HwaccelHevcVdpau => "--enable-hwaccel=hevc_vdpau",
+        }
+    }
+
+    /// `(listing kind, name)` this feature should show up under in `ffmpeg -hide_banner
+    /// -encoders/-decoders/-hwaccels`; `None` for flags (filters, libraries) those listings don't
+    /// report on.
+    fn listing(self) -> Option<(FfmpegFeatureKind, &'static str)> {
+        use FfmpegFeature::*;
+        use FfmpegFeatureKind::*;
+        Some(match self {
+            EncoderH264Nvenc => (Encoder, "h264_nvenc"),
+            EncoderHevcNvenc => (Encoder, "hevc_nvenc"),
+            DecoderH264Nvdec => (Decoder, "h264_nvdec"),
+            DecoderHevcNvdec => (Decoder, "hevc_nvdec"),
+            DecoderH264Cuvid => (Decoder, "h264_cuvid"),
+            DecoderHevcCuvid => (Decoder, "hevc_cuvid"),
+            HwaccelH264Nvdec => (Hwaccel, "h264_nvdec"),
+            HwaccelHevcNvdec => (Hwaccel, "hevc_nvdec"),
+            HwaccelH264Cuvid => (Hwaccel, "h264_cuvid"),
+            HwaccelHevcCuvid => (Hwaccel, "hevc_cuvid"),
+            HwaccelH264Nvenc => (Hwaccel, "h264_nvenc"),
+            HwaccelHevcNvenc => (Hwaccel, "hevc_nvenc"),
+            EncoderH264Vaapi => (Encoder, "h264_vaapi"),
+            EncoderHevcVaapi => (Encoder, "hevc_vaapi"),
+            HwaccelH264Vaapi => (Hwaccel, "h264_vaapi"),
+            HwaccelHevcVaapi => (Hwaccel, "hevc_vaapi"),
+            DecoderH264Vaapi => (Decoder, "h264_vaapi"),
+            DecoderHevcVaapi => (Decoder, "hevc_vaapi"),
+            EncoderLibx264 => (Encoder, "libx264"),
+            EncoderLibx264Rgb => (Encoder, "libx264rgb"),
+            EncoderLibx265 => (Encoder, "libx265"),
+            DecoderLibx264 => (Decoder, "libx264"),
+            DecoderLibx265 => (Decoder, "libx265"),
+            DecoderH264Vdpau => (Decoder, "h264_vdpau"),
+            DecoderHevcVdpau => (Decoder, "hevc_vdpau"),
+            HwaccelH264Vdpau => (Hwaccel, "h264_vdpau"),
+            HwaccelHevcVdpau => (Hwaccel, "hevc_vdpau"),
+            FilterScale | FilterScaleVaapi | Vulkan | Libdrm => return None,
+        })
+    }
+}
+
+/// Typed replacement for hand-concatenating `./configure` flags: push `FfmpegFeature`s and
+/// free-form `extra` flags (prefix/disable toggles, `--extra-cflags`, ...) and get back a
+/// validated, space-joined argument list, instead of a positional `format!` string where a
+/// dropped flag silently ships a degraded ffmpeg.
+#[derive(Default)]
+struct FfmpegConfigureBuilder {
+    features: Vec<FfmpegFeature>,
+    extra_flags: Vec<String>,
+}
+
+impl FfmpegConfigureBuilder {
+    fn feature(mut self, feature: FfmpegFeature) -> Self {
+        self.features.push(feature);
+        self
+    }
+
+    fn features(mut self, features: impl IntoIterator<Item = FfmpegFeature>) -> Self {
+        self.features.extend(features);
+        self
+    }
+
+    fn extra(mut self, flag: impl Into<String>) -> Self {
+        self.extra_flags.push(flag.into());
+        self
+    }
+
+    fn configure_args(&self) -> String {
+        self.features
+            .iter()
+            .map(|f| f.configure_token().to_string())
+            .chain(self.extra_flags.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Runs the just-installed `ffmpeg -hide_banner -encoders/-decoders/-hwaccels` and asserts every
+/// requested feature that has a listing (see `FfmpegFeature::listing`) actually compiled in,
+/// failing loudly instead of shipping an ffmpeg that's silently missing an encoder/decoder/hwaccel
+/// a configure flag was supposed to enable.
+fn verify_ffmpeg_build(install_path: &Path, features: &[FfmpegFeature]) {
+    let ffmpeg_bin = install_path.join("bin/ffmpeg");
+
+    fn listing(ffmpeg_bin: &Path, flag: &str) -> String {
+        std::process::Command::new(ffmpeg_bin)
+            .args(["-hide_banner", flag])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+            .unwrap_or_default()
+    }
+
+    let encoders = listing(&ffmpeg_bin, "-encoders");
+    let decoders = listing(&ffmpeg_bin, "-decoders");
+    let hwaccels = listing(&ffmpeg_bin, "-hwaccels");
+
+    for feature in features {
+        let Some((kind, name)) = feature.listing() else {
+            continue;
+        };
+        let listing_output = match kind {
+            FfmpegFeatureKind::Encoder => &encoders,
+            FfmpegFeatureKind::Decoder => &decoders,
+            FfmpegFeatureKind::Hwaccel => &hwaccels,
+        };
+        assert!(
+            listing_output.contains(name),
+            "ffmpeg build is missing requested {feature:?} ({name}); check the configure log for \
+             a dependency that wasn't found"
+        );
+    }
+}
+
+/// Lists the hwaccel backends (`vdpau`, `vaapi`, `cuvid`, ...) a built ffmpeg reports under
+/// `-hwaccels`, so the decoder can pick a fallback (e.g. VDPAU when VAAPI is unavailable on older
+/// Nvidia stacks) instead of hard-failing when its first choice isn't present.
+pub fn supported_hwaccel_backends(install_path: &Path) -> Vec<String> {
+    let output = std::process::Command::new(install_path.join("bin/ffmpeg"))
+        .args(["-hide_banner", "-hwaccels"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+        .unwrap_or_default();
+
+    output
+        .lines()
+        .skip(1) // "Hardware acceleration methods:"
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Presence of the external libraries the session/encoder code and settings UI care about, parsed
+/// out of `ffmpeg -buildconf`'s `configuration:` line.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct FfmpegLibraries {
+    pub libx264: bool,
+    pub libx265: bool,
+    pub vulkan: bool,
+    pub vaapi: bool,
+    pub vdpau: bool,
+    pub cuda: bool,
+}
+
+/// Snapshot of what a built or extracted ffmpeg actually supports, so runtime code can query
+/// compiled-in capabilities instead of guessing or hard-failing on a feature that `./configure`
+/// silently dropped, and the settings UI can disable codec options that were never compiled in.
+/// Mirrors the approach ffmpeg-sys's build.rs uses to turn the library's own config into feature
+/// flags, but persisted to disk (see `persist`/`load`) so it survives past the xtask process that
+/// built ffmpeg.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct FfmpegCapabilities {
+    pub encoders: Vec<String>,
+    pub decoders: Vec<String>,
+    pub hwaccels: Vec<String>,
+    pub libraries: FfmpegLibraries,
+}
+
+impl FfmpegCapabilities {
+    const PERSISTED_FILENAME: &'static str = "ffmpeg-capabilities.toml";
+
+    /// Probes `<install_path>/bin/ffmpeg` via `-buildconf`/`-encoders`/`-decoders`/`-hwaccels`.
+    pub fn probe(install_path: &Path) -> Self {
+        let ffmpeg_bin = install_path.join("bin/ffmpeg");
+
+        // Each listing has a couple of legend/header lines before the actual entries; entries are
+        // " <flags> <name> <description...>", legend lines contain a literal '=' ("V..... = Video").
+        fn listing_names(ffmpeg_bin: &Path, flag: &str) -> Vec<String> {
+            std::process::Command::new(ffmpeg_bin)
+                .args(["-hide_banner", flag])
+                .output()
+                .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+                .unwrap_or_default()
+                .lines()
+                .filter(|line| line.starts_with(' ') && !line.contains('='))
+                .filter_map(|line| line.split_whitespace().nth(1))
+                .map(str::to_owned)
+                .collect()
+        }
+
+        let buildconf = std::process::Command::new(&ffmpeg_bin)
+            .args(["-hide_banner", "-buildconf"])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+            .unwrap_or_default();
+        let has_flag = |flag: &str| buildconf.contains(flag);
+
+        Self {
+            encoders: listing_names(&ffmpeg_bin, "-encoders"),
+            decoders: listing_names(&ffmpeg_bin, "-decoders"),
+            hwaccels: supported_hwaccel_backends(install_path),
+            libraries: FfmpegLibraries {
+                libx264: has_flag("--enable-libx264"),
+                libx265: has_flag("--enable-libx265"),
+                vulkan: has_flag("--enable-vulkan"),
+                vaapi: has_flag("--enable-vaapi"),
+                vdpau: has_flag("--enable-vdpau"),
+                cuda: has_flag("--enable-cuda-nvcc") || has_flag("--enable-ffnvcodec"),
+            },
+        }
+    }
+
+    /// Persists alongside the deps directory so code outside xtask (session setup, settings UI)
+    /// can read the last-built ffmpeg's capabilities without re-invoking it.
+    pub fn persist(&self) {
+        let path = afs::deps_dir().join(Self::PERSISTED_FILENAME);
+        let contents = toml::to_string_pretty(self).expect("FfmpegCapabilities is serializable");
+        fs::write(path, contents).unwrap();
+    }
+
+    pub fn load() -> Option<Self> {
+        let path = afs::deps_dir().join(Self::PERSISTED_FILENAME);
+        let contents = fs::read_to_string(path).ok()?;
+        Some(toml::from_str(&contents).expect("persisted FfmpegCapabilities is well-formed"))
+    }
+}
+
 pub fn _build_ffmpeg_linux_install(
     nvenc_flag: bool,
     version_tag: &str,
     enable_decoders: bool,
+    decode_backends: &[DecodeBackend],
     install_path: &std::path::Path,
 ) -> std::path::PathBuf {
     /* dependencies: build-essential pkg-config nasm libva-dev libdrm-dev libvulkan-dev
@@ -102,17 +423,128 @@ pub fn _build_ffmpeg_linux_install(
         );
     }
 
-    #[inline(always)]
-    fn enable_if(flag: bool, val: &'static str) -> &'static str {
-        if flag { val } else { "" }
-    }
-
     let install_prefix = match install_path.to_str() {
         Some(ips) if ips.len() > 0 => {
             format!("--prefix={}", ips)
         }
         _ => String::new(),
     };
+    let has_install_prefix = !install_prefix.is_empty();
+
+    let mut builder = FfmpegConfigureBuilder::default()
+        .extra(install_prefix)
+        .extra("--disable-static")
+        .extra("--disable-programs")
+        .extra("--disable-doc")
+        .extra("--disable-avdevice")
+        .extra("--disable-avformat")
+        .extra("--disable-swresample")
+        .extra("--disable-postproc")
+        .extra("--disable-network")
+        .extra("--disable-debug")
+        .extra("--disable-everything")
+        .extra("--enable-shared")
+        .extra("--enable-gpl")
+        .extra("--enable-version3")
+        .extra("--enable-lto");
+
+    if nvenc_flag {
+        /*
+           Describing Nvidia specific options --nvccflags:
+           nvcc from CUDA toolkit version 11.0 or higher does not support compiling for 'compute_30' (default in ffmpeg)
+           52 is the minimum required for the current CUDA 11 version (Quadro M6000 , GeForce 900, GTX-970, GTX-980, GTX Titan X)
+           https://arnon.dk/matching-sm-architectures-arch-and-gencode-for-various-nvidia-cards/
+           Anyway below 50 arch card don't support nvenc encoding hevc https://developer.nvidia.com/nvidia-video-codec-sdk (Supported devices)
+           Nvidia docs:
+           https://docs.nvidia.com/video-technologies/video-codec-sdk/ffmpeg-with-nvidia-gpu/#commonly-faced-issues-and-tips-to-resolve-them
+        */
+        let cuda = pkg_config::Config::new().probe("cuda").unwrap();
+        let include_flags = cuda
+            .include_paths
+            .iter()
+            .map(|path| format!("-I{path:?}"))
+            .reduce(|a, b| format!("{a}{b}"))
+            .expect("pkg-config cuda entry to have include-paths");
+        let link_flags = cuda
+            .link_paths
+            .iter()
+            .map(|path| format!("-L{path:?}"))
+            .reduce(|a, b| format!("{a}{b}"))
+            .expect("pkg-config cuda entry to have link-paths");
+
+        builder = builder
+            .extra(format!("--extra-cflags=\"{include_flags}\""))
+            .extra(format!("--extra-ldflags=\"{link_flags}\""))
+            .extra("--enable-nonfree")
+            .extra("--enable-ffnvcodec")
+            .extra("--enable-cuda-nvcc")
+            .extra("--enable-libnpp")
+            .extra("--nvccflags=\"-gencode arch=compute_52,code=sm_52 -O2\"")
+            .feature(FfmpegFeature::EncoderH264Nvenc)
+            .feature(FfmpegFeature::EncoderHevcNvenc)
+            .feature(FfmpegFeature::HwaccelH264Nvenc)
+            .feature(FfmpegFeature::HwaccelHevcNvenc);
+
+        if enable_decoders {
+            builder = builder
+                .extra("--enable-nvdec")
+                .extra("--enable-nvenc")
+                .extra("--enable-cuvid")
+                .feature(FfmpegFeature::DecoderH264Nvdec)
+                .feature(FfmpegFeature::DecoderHevcNvdec)
+                .feature(FfmpegFeature::DecoderH264Cuvid)
+                .feature(FfmpegFeature::DecoderHevcCuvid)
+                .feature(FfmpegFeature::HwaccelH264Nvdec)
+                .feature(FfmpegFeature::HwaccelHevcNvdec)
+                .feature(FfmpegFeature::HwaccelH264Cuvid)
+                .feature(FfmpegFeature::HwaccelHevcCuvid);
+        }
+    }
+
+    builder = builder
+        .feature(FfmpegFeature::EncoderH264Vaapi)
+        .feature(FfmpegFeature::EncoderHevcVaapi)
+        .feature(FfmpegFeature::EncoderLibx264)
+        .feature(FfmpegFeature::EncoderLibx264Rgb)
+        .feature(FfmpegFeature::EncoderLibx265)
+        .feature(FfmpegFeature::HwaccelH264Vaapi)
+        .feature(FfmpegFeature::HwaccelHevcVaapi);
+
+    if enable_decoders {
+        builder = builder
+            .feature(FfmpegFeature::DecoderLibx264)
+            .feature(FfmpegFeature::DecoderLibx265)
+            .feature(FfmpegFeature::DecoderH264Vaapi)
+            .feature(FfmpegFeature::DecoderHevcVaapi)
+            .extra("--enable-vaapi");
+
+        if decode_backends.contains(&DecodeBackend::Vdpau) {
+            builder = builder
+                .feature(FfmpegFeature::DecoderH264Vdpau)
+                .feature(FfmpegFeature::DecoderHevcVdpau)
+                .feature(FfmpegFeature::HwaccelH264Vdpau)
+                .feature(FfmpegFeature::HwaccelHevcVdpau)
+                .extra("--enable-vdpau");
+        }
+    }
+
+    for backend in decode_backends {
+        assert!(
+            backend.is_supported_by_linux_build(),
+            "{backend:?} can't be enabled by the from-source Linux ffmpeg build; it only applies \
+             to the prebuilt Windows binary"
+        );
+    }
+
+    builder = builder
+        .feature(FfmpegFeature::FilterScale)
+        .feature(FfmpegFeature::FilterScaleVaapi)
+        .extra("--enable-libx264")
+        .extra("--enable-libx265")
+        .feature(FfmpegFeature::Vulkan)
+        .feature(FfmpegFeature::Libdrm)
+        .extra("--enable-pic")
+        .extra("--enable-rpath");
 
     bash_in(
         &ffmpeg_path,
@@ -120,68 +552,16 @@ pub fn _build_ffmpeg_linux_install(
             // The reason for 4x$ in LDSOFLAGS var refer to https://stackoverflow.com/a/71429999
             // all varients of --extra-ldsoflags='-Wl,-rpath,$ORIGIN' do not work! don't waste your time trying!
             //
-            r#"LDSOFLAGS=-Wl,-rpath,\''$$$$ORIGIN'\' ./configure {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}"#,
-            install_prefix,
-            "--disable-static",
-            "--disable-programs",
-            "--disable-doc",
-            "--disable-avdevice --disable-avformat --disable-swresample --disable-postproc",
-            "--disable-network",
-            "--disable-debug --disable-everything",
-            " --enable-shared --enable-gpl --enable-version3",
-            "--enable-lto",
-            /*
-               Describing Nvidia specific options --nvccflags:
-               nvcc from CUDA toolkit version 11.0 or higher does not support compiling for 'compute_30' (default in ffmpeg)
-               52 is the minimum required for the current CUDA 11 version (Quadro M6000 , GeForce 900, GTX-970, GTX-980, GTX Titan X)
-               https://arnon.dk/matching-sm-architectures-arch-and-gencode-for-various-nvidia-cards/
-               Anyway below 50 arch card don't support nvenc encoding hevc https://developer.nvidia.com/nvidia-video-codec-sdk (Supported devices)
-               Nvidia docs:
-               https://docs.nvidia.com/video-technologies/video-codec-sdk/ffmpeg-with-nvidia-gpu/#commonly-faced-issues-and-tips-to-resolve-them
-            */
-            (if nvenc_flag {
-                let cuda = pkg_config::Config::new().probe("cuda").unwrap();
-                let include_flags = cuda
-                    .include_paths
-                    .iter()
-                    .map(|path| format!("-I{path:?}"))
-                    .reduce(|a, b| format!("{a}{b}"))
-                    .expect("pkg-config cuda entry to have include-paths");
-                let link_flags = cuda
-                    .link_paths
-                    .iter()
-                    .map(|path| format!("-L{path:?}"))
-                    .reduce(|a, b| format!("{a}{b}"))
-                    .expect("pkg-config cuda entry to have link-paths");
-
-                format!(
-                    "{} {} {} {} {} --extra-cflags=\"{}\" --extra-ldflags=\"{}\" {} {}",
-                    enable_if(enable_decoders, "--enable-decoder=h264_nvdec --enable-decoder=hevc_nvdec --enable-decoder=h264_cuvid --enable-decoder=hevc_cuvid"),
-                    "--enable-encoder=h264_nvenc --enable-encoder=hevc_nvenc --enable-nonfree",
-                    "--enable-ffnvcodec --enable-cuda-nvcc --enable-libnpp",
-                    enable_if(enable_decoders, "--enable-nvdec --enable-nvenc --enable-cuvid"),
-                    "--nvccflags=\"-gencode arch=compute_52,code=sm_52 -O2\"",
-                    include_flags,
-                    link_flags,
-                    enable_if(enable_decoders, "--enable-hwaccel=h264_nvdec --enable-hwaccel=hevc_nvdec --enable-hwaccel=h264_cuvid --enable-hwaccel=hevc_cuvid"),
-                    "--enable-hwaccel=h264_nvenc --enable-hwaccel=hevc_nvenc"
-                )
-            } else {
-                "".to_string()
-            }),
-            "--enable-encoder=h264_vaapi --enable-encoder=hevc_vaapi",
-            "--enable-encoder=libx264 --enable-encoder=libx264rgb --enable-encoder=libx265",
-            "--enable-hwaccel=h264_vaapi --enable-hwaccel=hevc_vaapi",
-            enable_if(enable_decoders, "--enable-decoder=libx264 --enable-decoder=libx265 --enable-decoder=h264_vaapi --enable-decoder=hevc_vaapi --enable-vaapi"),
-            "--enable-filter=scale --enable-filter=scale_vaapi",
-            "--enable-libx264 --enable-libx265 --enable-vulkan",
-            "--enable-libdrm --enable-pic --enable-rpath"
+            r#"LDSOFLAGS=-Wl,-rpath,\''$$$$ORIGIN'\' ./configure {}"#,
+            builder.configure_args(),
         ),
     )
     .unwrap();
     bash_in(&ffmpeg_path, "make -j$(nproc)").unwrap();
-    if install_prefix.len() > 0 {
+    if has_install_prefix {
         bash_in(&ffmpeg_path, "make install").unwrap();
+        verify_ffmpeg_build(install_path, &builder.features);
+        FfmpegCapabilities::probe(install_path).persist();
     }
 
     ffmpeg_path
@@ -192,6 +572,7 @@ pub fn _build_ffmpeg_linux(nvenc_flag: bool) -> std::path::PathBuf {
         nvenc_flag,
         "release/5.1",
         /*enable_decoders=*/ true,
+        &[DecodeBackend::Vaapi, DecodeBackend::Vdpau],
         std::path::Path::new(""),
     )
 }
@@ -239,6 +620,7 @@ pub fn extract_ffmpeg_linux(version: &str, gpl: bool) -> std::path::PathBuf {
         ffmpeg_path = dunce::canonicalize(ffmpeg_path).unwrap();
         // Patch rpath to $ORIGIN so libraries find each other at runtime
         patch_rpath(&ffmpeg_path.join("lib"));
+        FfmpegCapabilities::probe(&ffmpeg_path).persist();
     }
     assert!(ffmpeg_path.exists(), "FFmpeg deps path does not exist!");
     dunce::canonicalize(ffmpeg_path).unwrap()
@@ -265,6 +647,322 @@ fn get_oculus_openxr_mobile_loader() {
     fs::remove_dir_all(temp_sdk_dir).ok();
 }
 
+/// Pinned Android SDK/NDK component set, the same idea as nixpkgs' `composeAndroidPackages`:
+/// exact versions so Quest/Pico client builds are reproducible across CI and contributor
+/// machines instead of depending on whatever happens to already be installed.
+pub struct AndroidSdkManifest {
+    pub platform_tools_version: &'static str,
+    pub build_tools_versions: &'static [&'static str],
+    pub platform_versions: &'static [&'static str],
+    /// NDK r-version known to work with ALVR's OpenXR loader; bump deliberately, not as a side
+    /// effect of chasing the latest NDK.
+    pub ndk_version: &'static str,
+    pub cmake_versions: &'static [&'static str],
+}
+
+pub const ANDROID_SDK_MANIFEST: AndroidSdkManifest = AndroidSdkManifest {
+    platform_tools_version: "34.0.5",
+    build_tools_versions: &["34.0.0"],
+    platform_versions: &["android-34"],
+    ndk_version: "26.1.10909125",
+    cmake_versions: &["3.22.1"],
+};
+
+const ANDROID_REPOSITORY_BASE_URL: &str = "https://dl.google.com/android/repository/";
+const ANDROID_REPOSITORY_INDEX_URL: &str = "https://dl.google.com/android/repository/repository2-3.xml";
+
+#[cfg(target_os = "linux")]
+const ANDROID_REPOSITORY_HOST_OS: &str = "linux";
+#[cfg(target_os = "windows")]
+const ANDROID_REPOSITORY_HOST_OS: &str = "windows";
+#[cfg(target_os = "macos")]
+const ANDROID_REPOSITORY_HOST_OS: &str = "macosx";
+
+struct RepoArchive {
+    url: String,
+    sha1: String,
+}
+
+struct RepoPackage {
+    path: String,
+    archives: Vec<RepoArchive>,
+}
+
+fn extract_attr(block: &str, attr: &str) -> Option<String> {
+    let needle = format!("{attr}=\"");
+    let start = block.find(&needle)? + needle.len();
+    let end = block[start..].find('"')? + start;
+    Some(block[start..end].to_string())
+}
+
+fn extract_tag(block: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = block.find(&open)? + open.len();
+    let end = block[start..].find(&close)? + start;
+    Some(block[start..end].trim().to_string())
+}
+
+/// Minimal scanner over the sdkmanager `repository2-3.xml` shape: each `<remotePackage
+/// path="...">` block holds one `<archive>` per host OS it's built for, each with a `<url>`
+/// relative to `ANDROID_REPOSITORY_BASE_URL` and a sha1 `<checksum>`. A hand-rolled scanner is
+/// enough here -- this is the only XML document xtask ever reads, and the real document is large
+/// enough that pulling in a full XML crate would only be paying for one use site.
+fn fetch_repository_index() -> Vec<RepoPackage> {
+    let xml_path = afs::deps_dir().join("android/repository2-3.xml");
+    if !xml_path.exists() {
+        fs::create_dir_all(xml_path.parent().unwrap()).unwrap();
+        command::download(ANDROID_REPOSITORY_INDEX_URL, &xml_path).unwrap();
+    }
+    let xml = fs::read_to_string(&xml_path).unwrap();
+
+    let mut packages = vec![];
+    for block in xml.split("<remotePackage ").skip(1) {
+        let Some(path) = extract_attr(block, "path") else {
+            continue;
+        };
+
+        let mut archives = vec![];
+        for archive_block in block.split("<archive>").skip(1) {
+            let archive_block = archive_block.split("</archive>").next().unwrap();
+            // Packages that aren't host-specific (platforms, build-tools, most packages) have no
+            // <host-os> tag at all; ones that are (cmdline-tools, ndk) list one archive per OS.
+            if let Some(host_os) = extract_tag(archive_block, "host-os") {
+                if host_os != ANDROID_REPOSITORY_HOST_OS {
+                    continue;
+                }
+            }
+            // The checksum element can carry a `type="sha1"` attribute, which `extract_tag`'s exact
+            // `<tag>` match would miss.
+            let sha1 = archive_block.find("<checksum").and_then(|start| {
+                let rest = &archive_block[start..];
+                let value_start = rest.find('>')? + 1;
+                let value_end = rest.find("</checksum>")?;
+                Some(rest[value_start..value_end].trim().to_string())
+            });
+            let (Some(url), Some(sha1)) = (extract_tag(archive_block, "url"), sha1) else {
+                continue;
+            };
+            archives.push(RepoArchive { url, sha1 });
+        }
+
+        if !archives.is_empty() {
+            packages.push(RepoPackage { path, archives });
+        }
+    }
+    packages
+}
+
+fn resolve_package<'a>(index: &'a [RepoPackage], path: &str) -> &'a RepoArchive {
+    index
+        .iter()
+        .find(|pkg| pkg.path == path)
+        .unwrap_or_else(|| panic!("Android SDK package {path:?} not found in repository index"))
+        .archives
+        .first()
+        .unwrap_or_else(|| panic!("Android SDK package {path:?} has no archive for this host OS"))
+}
+
+fn sha1_hex(bytes: &[u8]) -> String {
+    use sha1::{Digest, Sha1};
+    let mut hasher = Sha1::new();
+    hasher.update(bytes);
+    hasher.finalize().iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Downloads and unpacks `archive` into `sdk_root.join(install_subdir)`, verifying its sha1
+/// checksum against the repository index first. A no-op if that directory already exists, so
+/// re-running `prepare-deps --platform android` on a machine that already has the pinned set
+/// installed doesn't re-download anything.
+fn install_sdk_package(sdk_root: &Path, pkg_path: &str, archive: &RepoArchive, install_subdir: &Path) {
+    let install_dir = sdk_root.join(install_subdir);
+    if install_dir.exists() {
+        println!("{pkg_path} already installed at {}", install_dir.display());
+        return;
+    }
+
+    let url = format!("{ANDROID_REPOSITORY_BASE_URL}{}", archive.url);
+    let android_deps_dir = afs::deps_dir().join("android");
+    fs::create_dir_all(&android_deps_dir).unwrap();
+
+    let zip_file = android_deps_dir.join("temp_download.zip");
+    command::download(&url, &zip_file).unwrap();
+
+    let actual_sha1 = sha1_hex(&fs::read(&zip_file).unwrap());
+    assert_eq!(
+        actual_sha1, archive.sha1,
+        "checksum mismatch downloading {pkg_path} ({url})"
+    );
+
+    let extract_dir = android_deps_dir.join("temp_extract");
+    fs::remove_dir_all(&extract_dir).ok();
+    command::unzip(&zip_file, &extract_dir).unwrap();
+    fs::remove_file(&zip_file).unwrap();
+
+    // Every one of these archives extracts to a single top-level directory (the unpacked package
+    // itself); move its contents up to `install_dir` rather than leaving it nested one level too
+    // deep.
+    let unpacked_root = fs::read_dir(&extract_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|p| p.is_dir())
+        .unwrap_or_else(|| panic!("unexpected archive layout for {pkg_path}"));
+    fs::create_dir_all(install_dir.parent().unwrap()).unwrap();
+    fs::rename(&unpacked_root, &install_dir).unwrap();
+    fs::remove_dir_all(&extract_dir).ok();
+
+    println!("installed {pkg_path} -> {}", install_dir.display());
+}
+
+/// Resolves and installs `manifest`'s pinned build-tools/platforms/cmake/NDK set, plus whichever
+/// `platform-tools` revision the repository index lists (`platform_tools_version` is not checked
+/// against it), into a crate-managed SDK root under `afs::deps_dir()`, the way nixpkgs'
+/// `composeAndroidPackages` does, instead of relying on whatever SDK/NDK happens to already be on
+/// the machine.
+pub fn provision_android_sdk(manifest: &AndroidSdkManifest) -> std::path::PathBuf {
+    let sdk_root = afs::deps_dir().join("android/sdk");
+    fs::create_dir_all(&sdk_root).unwrap();
+
+    let index = fetch_repository_index();
+
+    install_sdk_package(
+        &sdk_root,
+        "platform-tools",
+        resolve_package(&index, "platform-tools"),
+        Path::new("platform-tools"),
+    );
+
+    for build_tools_version in manifest.build_tools_versions {
+        let pkg_path = format!("build-tools;{build_tools_version}");
+        install_sdk_package(
+            &sdk_root,
+            &pkg_path,
+            resolve_package(&index, &pkg_path),
+            &Path::new("build-tools").join(build_tools_version),
+        );
+    }
+
+    for platform_version in manifest.platform_versions {
+        let pkg_path = format!("platforms;{platform_version}");
+        install_sdk_package(
+            &sdk_root,
+            &pkg_path,
+            resolve_package(&index, &pkg_path),
+            &Path::new("platforms").join(platform_version),
+        );
+    }
+
+    for cmake_version in manifest.cmake_versions {
+        let pkg_path = format!("cmake;{cmake_version}");
+        install_sdk_package(
+            &sdk_root,
+            &pkg_path,
+            resolve_package(&index, &pkg_path),
+            &Path::new("cmake").join(cmake_version),
+        );
+    }
+
+    let ndk_pkg_path = format!("ndk;{}", manifest.ndk_version);
+    install_sdk_package(
+        &sdk_root,
+        &ndk_pkg_path,
+        resolve_package(&index, &ndk_pkg_path),
+        &Path::new("ndk").join(manifest.ndk_version),
+    );
+
+    sdk_root
+}
+
+/// Provisions the pinned Android SDK/NDK set (see `ANDROID_SDK_MANIFEST`) and exports
+/// `ANDROID_HOME`/`ANDROID_NDK_ROOT` for the `cargo apk build` invocations in
+/// `build_alxr_android`/`build_alxr_android_fat`, then installs the Rust targets and `cargo-apk`
+/// itself and fetches the Oculus OpenXR mobile loader, same as before this was pinned.
+pub fn build_android_deps(for_ci: bool) {
+    let sdk_root = provision_android_sdk(&ANDROID_SDK_MANIFEST);
+    std::env::set_var("ANDROID_HOME", &sdk_root);
+    std::env::set_var(
+        "ANDROID_NDK_ROOT",
+        sdk_root.join("ndk").join(ANDROID_SDK_MANIFEST.ndk_version),
+    );
+    if for_ci {
+        println!(
+            "prepare-deps --ci: Android SDK/NDK provisioned to {}",
+            sdk_root.display()
+        );
+    }
+
+    command::run(
+        "rustup target add aarch64-linux-android armv7-linux-androideabi x86_64-linux-android i686-linux-android",
+    )
+    .unwrap();
+    command::run("cargo install cargo-apk").unwrap();
+
+    get_oculus_openxr_mobile_loader();
+}
+
+/// `libavcodec`'s pkg-config version below this is missing hwaccels/codecs ALXR depends on; a
+/// system FFmpeg older than this is rejected outright rather than silently linking against a
+/// build that's missing features the bundled from-source build always has.
+pub const MIN_SYSTEM_AVCODEC_VERSION: u32 = 60;
+
+/// Include/link search paths for a system FFmpeg found via `find_system_ffmpeg`, in the same
+/// shape `_build_ffmpeg_linux_install`'s caller already expects from the from-source build (an
+/// install dir whose `lib`/`include` subfolders get fed to the cargo build).
+pub struct SystemFFmpegLibs {
+    pub include_paths: Vec<std::path::PathBuf>,
+    pub link_paths: Vec<std::path::PathBuf>,
+    /// Whether `ffnvcodec` (the headers NVENC/NVDEC hwaccels are built against) was also found,
+    /// so callers can decide whether to enable the `cuda-interop` feature for a system build the
+    /// same way `AlxBuildFlags::make_build_string` does for the bundled one.
+    pub nvenc: bool,
+}
+
+/// Looks up an already-installed FFmpeg via pkg-config, for distro packagers who ship their own
+/// and would rather not pay for `_build_ffmpeg_linux_install`'s ~20 minute from-source build.
+/// Probes the libraries ALXR actually links against and rejects the whole thing if `libavcodec`
+/// is older than `min_avcodec_major`.
+pub fn find_system_ffmpeg(
+    min_avcodec_major: u32,
+) -> Result<SystemFFmpegLibs, Box<dyn std::error::Error>> {
+    let required_libs = ["libavutil", "libavcodec", "libavformat", "libswscale"];
+
+    let mut include_paths = vec![];
+    let mut link_paths = vec![];
+    for lib_name in required_libs {
+        let lib = pkg_config::Config::new().probe(lib_name)?;
+
+        if lib_name == "libavcodec" {
+            let major = lib
+                .version
+                .split('.')
+                .next()
+                .and_then(|part| part.parse::<u32>().ok())
+                .ok_or_else(|| format!("could not parse {lib_name} version {:?}", lib.version))?;
+            if major < min_avcodec_major {
+                return Err(format!(
+                    "system {lib_name} {} is older than the required {min_avcodec_major}.x",
+                    lib.version
+                )
+                .into());
+            }
+        }
+
+        include_paths.extend(lib.include_paths);
+        link_paths.extend(lib.link_paths);
+    }
+    include_paths.dedup();
+    link_paths.dedup();
+
+    let nvenc = pkg_config::Config::new().probe("ffnvcodec").is_ok();
+
+    Ok(SystemFFmpegLibs {
+        include_paths,
+        link_paths,
+        nvenc,
+    })
+}
+
 pub fn build_deps(target_os: &str) {
     if target_os == "android" {
         command::run("rustup target add aarch64-linux-android").unwrap();
@@ -276,28 +974,86 @@ pub fn build_deps(target_os: &str) {
     }
 }
 
+/// Extracts the dependency path/name `ldd` prints on one line of its output, e.g. turns
+/// `libfoo.so.1 => /usr/lib/libfoo.so.1 (0x00007f...)` into `/usr/lib/libfoo.so.1`, or
+/// `linux-vdso.so.1 (0x00007ffc...)` (no resolved path) into `linux-vdso.so.1`.
+fn parse_ldd_line(line: &str) -> Option<std::path::PathBuf> {
+    let after_arrow = line.split_once('>').map_or(line, |(_, rest)| rest);
+    after_arrow
+        .split_whitespace()
+        .next()
+        .map(std::path::PathBuf::from)
+}
+
+/// Runs `ldd` on `bin_or_so` and returns every dependency path it resolved, canonicalized. Shells
+/// out to the `ldd` binary directly (no `sh -c` one-liner) so a path containing spaces or shell
+/// metacharacters can't break or inject into the command.
+fn run_ldd(bin_or_so: &Path) -> Vec<std::path::PathBuf> {
+    std::process::Command::new("ldd")
+        .arg(bin_or_so)
+        .output()
+        .map_or(vec![], |output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(parse_ldd_line)
+                .filter_map(|p| p.canonicalize().ok()) // canonicalize resolves symlinks
+                .collect::<Vec<_>>()
+        })
+}
+
 pub fn find_resolved_so_paths(
     bin_or_so: &std::path::Path,
     depends_so: &str,
 ) -> Vec<std::path::PathBuf> {
-    let cmdline = format!(
-        "ldd {} | cut -d '>' -f 2 | awk \'{{print $1}}\' | grep {}",
-        bin_or_so.display(),
-        depends_so
-    );
-    std::process::Command::new("sh")
-        .args(&["-c", &cmdline])
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .map_or(vec![], |mut child| {
-            let mut result = std::io::BufReader::new(child.stdout.take().unwrap())
-                .lines()
-                .filter(|line| line.is_ok())
-                .map(|line| std::path::PathBuf::from(line.unwrap()).canonicalize()) // canonicalize resolves symlinks
-                .filter(|result| result.is_ok())
-                .map(|pp| pp.unwrap())
-                .collect::<Vec<_>>();
-            result.dedup();
-            result
-        })
+    let mut result = run_ldd(bin_or_so)
+        .into_iter()
+        .filter(|path| path.to_string_lossy().contains(depends_so))
+        .collect::<Vec<_>>();
+    result.dedup();
+    result
+}
+
+/// Same as `find_resolved_so_paths` but without the dependency-name filter: every `ldd`-resolved
+/// dependency of `bin_or_so`, canonicalized.
+fn ldd_dependencies(bin_or_so: &Path) -> Vec<std::path::PathBuf> {
+    run_ldd(bin_or_so)
+}
+
+/// Recursively resolves the transitive `.so` dependency closure of every shared object already in
+/// `lib_dir` (via `ldd`), copies any that live outside `lib_dir` into it, dedups by canonical path,
+/// and re-runs the `$ORIGIN` rpath patch so the whole set stays relocatable. Lets the Linux
+/// packaging step ship a self-contained tree instead of relying on the host's system ffmpeg/VAAPI
+/// libs being ABI-compatible at the user's machine.
+pub fn bundle_transitive_shared_libs(lib_dir: &Path) {
+    let lib_dir = lib_dir.canonicalize().unwrap();
+
+    let mut seen = HashSet::new();
+    let mut queue: Vec<_> = walkdir::WalkDir::new(&lib_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| afs::is_dynlib_file(p))
+        .filter_map(|p| p.canonicalize().ok())
+        .collect();
+    seen.extend(queue.iter().cloned());
+
+    while let Some(so_path) = queue.pop() {
+        for dep in ldd_dependencies(&so_path) {
+            if !seen.insert(dep.clone()) {
+                continue;
+            }
+
+            if dep.parent() != Some(lib_dir.as_path()) {
+                let dst = lib_dir.join(dep.file_name().unwrap());
+                if !dst.exists() {
+                    println!("Bundling {dep:?} into {dst:?}");
+                    fs::copy(&dep, &dst).unwrap();
+                }
+            }
+
+            queue.push(dep);
+        }
+    }
+
+    patch_rpath(&lib_dir);
 }
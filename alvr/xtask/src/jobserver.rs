@@ -0,0 +1,95 @@
+use std::sync::{Arc, Condvar, Mutex};
+
+/// A GNU-make-style jobserver: a shared pool of `capacity` tokens. Each build unit blocks on
+/// `acquire` before doing its actual (expensive) work and gives the token back when the returned
+/// `JobToken` drops, so at most `capacity` `cargo`/script invocations run at once no matter how
+/// many units were fanned out with `run_units`.
+#[derive(Clone)]
+pub struct JobServer {
+    inner: Arc<(Mutex<usize>, Condvar)>,
+}
+
+impl JobServer {
+    /// `jobs == 0` falls back to the available core count, mirroring `make -j` with no explicit
+    /// count and `cargo build`'s own `--jobs` default.
+    pub fn new(jobs: usize) -> Self {
+        let jobs = if jobs == 0 {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        } else {
+            jobs
+        };
+
+        Self {
+            inner: Arc::new((Mutex::new(jobs), Condvar::new())),
+        }
+    }
+
+    pub fn acquire(&self) -> JobToken<'_> {
+        let (lock, cvar) = &*self.inner;
+        let mut tokens = lock.lock().unwrap();
+        while *tokens == 0 {
+            tokens = cvar.wait(tokens).unwrap();
+        }
+        *tokens -= 1;
+        JobToken { server: self }
+    }
+
+    fn release(&self) {
+        let (lock, cvar) = &*self.inner;
+        *lock.lock().unwrap() += 1;
+        cvar.notify_one();
+    }
+}
+
+pub struct JobToken<'a> {
+    server: &'a JobServer,
+}
+
+impl Drop for JobToken<'_> {
+    fn drop(&mut self) {
+        self.server.release();
+    }
+}
+
+/// Runs each of `units` on its own thread, with every unit blocking on a `JobServer` token before
+/// doing its work, so at most `jobs` run concurrently regardless of how many units were queued.
+/// Collects every unit's result and returns the first error encountered (by queue order); units
+/// that are already running are let finish rather than being killed, the same way `make -jN`
+/// lets in-flight recipes complete after one of their siblings fails.
+pub fn run_units<T, F>(jobs: usize, units: Vec<F>) -> Result<Vec<T>, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    let server = JobServer::new(jobs);
+    let handles: Vec<_> = units
+        .into_iter()
+        .map(|unit| {
+            let server = server.clone();
+            std::thread::spawn(move || {
+                let _token = server.acquire();
+                unit()
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    let mut first_err = None;
+    for handle in handles {
+        let unit_result = handle
+            .join()
+            .unwrap_or_else(|_| Err("build unit panicked".to_owned()));
+        match unit_result {
+            Ok(value) => results.push(value),
+            Err(e) => {
+                if first_err.is_none() {
+                    first_err = Some(e);
+                }
+            }
+        }
+    }
+
+    first_err.map_or(Ok(results), Err)
+}
@@ -1,6 +1,8 @@
 mod build;
 mod command;
+mod config;
 mod dependencies;
+mod jobserver;
 mod packaging;
 mod version;
 
@@ -26,6 +28,9 @@ SUBCOMMANDS:
     run-streamer        Build streamer and then open the dashboard
     package-streamer    Build streamer in release mode, make portable version and installer
     package-client-lib  Build client library then zip it
+    package-client-all  Build the UWP x64/arm64 clients and the Android client concurrently,
+                         capped at --jobs parallel build units
+    sign-client         Re-sign a built APK (--apk) with a keystore and regenerate its .idsig
     clean               Removes all build artifacts and dependencies.
     bump                Bump streamer and client package versions
     clippy              Show warnings for selected clippy lints
@@ -38,17 +43,42 @@ FLAGS:
     --release           Optimized build with less debug checks. For build subcommands
     --gpl               Bundle GPL libraries (FFmpeg). Only for Windows
     --appimage          Package as AppImage. For package-streamer subcommand
+    --aab               Also produce a Play Store Android App Bundle (.aab) and a local-testing
+                         .apks set. For package-client subcommand
     --zsync             For --appimage, create .zsync update file and build AppImage with embedded update information. For package-streamer subcommand
     --nightly           Append nightly tag to versions. For bump subcommand
     --no-rebuild        Do not rebuild the streamer with run-streamer
     --ci                Do some CI related tweaks. Depends on the other flags and subcommand
     --no-stdcpp         Disable linking to libc++_shared with build-client-lib
+    --sign              Re-sign the built APK and regenerate its .idsig. For package-client,
+                         requires --keystore/--ks-pass/--ks-alias
 
 ARGS:
+    --jobs <N>          Max concurrent build units for package-client-all. Defaults to the
+                         available core count, as with `make -j` or `cargo build -j` with no
+                         explicit count.
     --platform <NAME>   Name of the platform (operative system or hardware name). snake_case
     --version <VERSION> Specify version to set with the bump-versions subcommand
     --root <PATH>       Installation root. By default no root is set and paths are calculated using
                         relative paths, which requires conforming to FHS on Linux.
+    --target <TRIPLE>   Rust target triple for the alxr-client builds (e.g.
+                        aarch64-unknown-linux-gnu), held in `AlxBuildFlags::target` and read by
+                        `AlxBuildFlags::target_os()` for FFmpeg bundling and Windows-specific
+                        packaging. No subcommand parses this flag: passing it is rejected as
+                        wrong arguments, and every build uses the xtask host's own triple.
+    --prefix <PATH>     cargo-c-style install prefix for package-client-lib's pkg-config staging.
+                        Defaults to a `client_lib_install` directory next to the built library.
+    --libdir <PATH>     Overrides just the library directory within --prefix for
+                        package-client-lib's pkg-config staging. Defaults to `<prefix>/lib`.
+    --apk <PATH>        APK to re-sign with the sign-client subcommand
+    --keystore <PATH>   Keystore used by sign-client / package-client --sign
+    --ks-pass <PASS>    Keystore password used by sign-client / package-client --sign
+    --ks-alias <ALIAS>  Key alias within --keystore used by sign-client / package-client --sign
+
+An optional `alxr-build.toml` in the working directory fills in --no-nvidia, --release,
+--platform, --root and --ffmpeg-version when they weren't passed on the command line; see
+`config::BuildConfig` for its layout. CLI flags always win over the file. Its other settings
+(the rest of [flags], [android], ffmpeg.enable_decoders) are parsed but not used.
 "#;
 
 pub fn run_streamer() {
@@ -175,13 +205,23 @@ fn find_linked_native_paths(
     Ok(linked_path_set)
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct AlxBuildFlags {
     is_release: bool,
     reproducible: bool,
     no_nvidia: bool,
     bundle_ffmpeg: bool,
+    /// Link against an already-installed FFmpeg found via pkg-config instead of building one from
+    /// source. Takes precedence over `bundle_ffmpeg` when both are set.
+    system_ffmpeg: bool,
     fetch_crates: bool,
+    /// Rust target triple to cross-compile for (`--target <triple>`), e.g.
+    /// `aarch64-unknown-linux-gnu`. `None` builds for the host triple. Every decision that used
+    /// to read `cfg!(target_os = ...)`/`cfg!(target_arch = ...)` — which only ever reflects the
+    /// machine running xtask, not the one being built for — should go through `target_os()`
+    /// instead so cross-builds (e.g. a Linux client built from a Windows CI runner) do the right
+    /// thing.
+    target: Option<String>,
 }
 
 impl Default for AlxBuildFlags {
@@ -191,14 +231,31 @@ impl Default for AlxBuildFlags {
             reproducible: true,
             no_nvidia: true,
             bundle_ffmpeg: true,
+            system_ffmpeg: false,
             fetch_crates: false,
+            target: None,
         }
     }
 }
 
 impl AlxBuildFlags {
+    /// The OS component of `target`, falling back to the xtask host's own OS when no explicit
+    /// `--target` was given. Matched by substring rather than position since Rust target triples
+    /// aren't all the same shape (e.g. `aarch64-linux-android` has no vendor component).
+    pub fn target_os(&self) -> &str {
+        match &self.target {
+            Some(triple) if triple.contains("windows") => "windows",
+            Some(triple) if triple.contains("android") => "android",
+            Some(triple) if triple.contains("linux") => "linux",
+            Some(triple) if triple.contains("apple") || triple.contains("darwin") => "macos",
+            Some(_) => "unknown",
+            None => std::env::consts::OS,
+        }
+    }
+
     pub fn make_build_string(&self) -> String {
-        let enable_bundle_ffmpeg = cfg!(target_os = "linux") && self.bundle_ffmpeg;
+        let enable_bundle_ffmpeg =
+            self.target_os() == "linux" && self.bundle_ffmpeg && !self.system_ffmpeg;
         let feature_map = vec![
             (enable_bundle_ffmpeg, "bundled-ffmpeg"),
             (!self.no_nvidia, "cuda-interop"),
@@ -221,6 +278,13 @@ impl AlxBuildFlags {
 
         let features = feature_strs.join(",");
         let mut build_str = flag_strs.join(" ").to_string();
+        if let Some(target) = &self.target {
+            if !build_str.is_empty() {
+                build_str.push(' ');
+            }
+            build_str.push_str("--target ");
+            build_str.push_str(target);
+        }
         if features.len() > 0 {
             if build_str.len() > 0 {
                 build_str.push(' ');
@@ -246,7 +310,30 @@ pub fn build_alxr_client(root: Option<String>, ffmpeg_version: &str, flags: AlxB
     fs::remove_dir_all(&alxr_client_build_dir).ok();
     fs::create_dir_all(&alxr_client_build_dir).unwrap();
 
-    let bundle_ffmpeg_enabled = cfg!(target_os = "linux") && flags.bundle_ffmpeg;
+    let system_ffmpeg_enabled = flags.target_os() == "linux" && flags.system_ffmpeg;
+    if system_ffmpeg_enabled {
+        let libs = dependencies::find_system_ffmpeg(dependencies::MIN_SYSTEM_AVCODEC_VERSION)
+            .unwrap_or_else(|e| panic!("system FFmpeg not usable: {e}"));
+        // Mirrors `_build_ffmpeg_linux_install`'s own `nvenc_flag` contract: if nvidia support
+        // was requested but the system FFmpeg wasn't built with ffnvcodec, fail loudly instead
+        // of silently producing a client without hardware encode/decode.
+        assert!(
+            flags.no_nvidia || libs.nvenc,
+            "--no-nvidia was not set but the system FFmpeg has no ffnvcodec (NVENC/NVDEC) support"
+        );
+
+        let search_paths = libs
+            .include_paths
+            .iter()
+            .chain(libs.link_paths.iter())
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(":");
+        env::set_var("ALXR_SYSTEM_FFMPEG_SEARCH_PATH", search_paths);
+    }
+
+    let bundle_ffmpeg_enabled =
+        flags.target_os() == "linux" && flags.bundle_ffmpeg && !flags.system_ffmpeg;
     if bundle_ffmpeg_enabled {
         assert!(!ffmpeg_version.is_empty(), "ffmpeg-version is empty!");
 
@@ -291,6 +378,7 @@ pub fn build_alxr_client(root: Option<String>, ffmpeg_version: &str, flags: AlxB
                 }
             }
         }
+        dependencies::bundle_transitive_shared_libs(&lib_dir);
     }
 
     if flags.fetch_crates {
@@ -298,7 +386,7 @@ pub fn build_alxr_client(root: Option<String>, ffmpeg_version: &str, flags: AlxB
     }
 
     let alxr_client_dir = afs::workspace_dir().join("alvr/openxr-client/alxr-client");
-    let (alxr_cargo_cmd, alxr_build_lib_dir) = if cfg!(target_os = "windows") {
+    let (alxr_cargo_cmd, alxr_build_lib_dir) = if flags.target_os() == "windows" {
         (
             format!("cargo build {}", build_flags),
             alxr_client_build_dir.to_owned(),
@@ -314,11 +402,11 @@ pub fn build_alxr_client(root: Option<String>, ffmpeg_version: &str, flags: AlxB
     };
     command::run_in(&alxr_client_dir, &alxr_cargo_cmd).unwrap();
 
-    fn is_linked_depends_file(path: &Path) -> bool {
+    fn is_linked_depends_file(path: &Path, is_windows_target: bool) -> bool {
         if afs::is_dynlib_file(&path) {
             return true;
         }
-        if cfg!(target_os = "windows") {
+        if is_windows_target {
             if let Some(ext) = path.extension() {
                 if ext.to_str().unwrap().eq("pdb") {
                     return true;
@@ -341,12 +429,13 @@ pub fn build_alxr_client(root: Option<String>, ffmpeg_version: &str, flags: AlxB
     println!("Searching for linked native dependencies, please wait this may take some time.");
     let linked_paths =
         find_linked_native_paths(&alxr_client_dir, &build_flags, false, None).unwrap();
+    let is_windows_target = flags.target_os() == "windows";
     for linked_path in linked_paths.iter() {
         for linked_depend_file in walkdir::WalkDir::new(linked_path)
             .into_iter()
             .filter_map(|maybe_entry| maybe_entry.ok())
             .map(|entry| entry.into_path())
-            .filter(|entry| is_linked_depends_file(&entry))
+            .filter(|entry| is_linked_depends_file(&entry, is_windows_target))
         {
             let relative_lpf = linked_depend_file.strip_prefix(linked_path).unwrap();
             let dst_file = alxr_build_lib_dir.join(relative_lpf);
@@ -355,7 +444,7 @@ pub fn build_alxr_client(root: Option<String>, ffmpeg_version: &str, flags: AlxB
         }
     }
 
-    if cfg!(target_os = "windows") {
+    if is_windows_target {
         let pdb_fname = "alxr_client.pdb";
         fs::copy(
             artifacts_dir.join(&pdb_fname),
@@ -429,20 +518,21 @@ pub fn build_alxr_uwp(root: Option<String>, arch: UWPArch, flags: AlxBuildFlags)
             .open(artifacts_dir.join(&file_mapping))
             .unwrap();
 
+        // Unlike `build_alxr_client`'s generic `--target`, UWP output is always Windows
+        // regardless of the xtask host, so the pdb/cso check below isn't a `cfg!`/`target_os()`
+        // decision at all.
         fn is_linked_depends_file(path: &Path) -> bool {
             if afs::is_dynlib_file(&path) {
                 return true;
             }
-            if cfg!(target_os = "windows") {
-                if let Some(ext) = path.extension() {
-                    if ext.to_str().unwrap().eq("pdb") {
-                        return true;
-                    }
+            if let Some(ext) = path.extension() {
+                if ext.to_str().unwrap().eq("pdb") {
+                    return true;
                 }
-                if let Some(ext) = path.extension() {
-                    if ext.to_str().unwrap().eq("cso") {
-                        return true;
-                    }
+            }
+            if let Some(ext) = path.extension() {
+                if ext.to_str().unwrap().eq("cso") {
+                    return true;
                 }
             }
             if let Some(ext) = path.extension() {
@@ -613,35 +703,165 @@ fn _setup_cargo_appimage() {
     command::run("cargo install cargo-appimage").unwrap();
 }
 
-pub fn build_alxr_app_image(_root: Option<String>, _ffmpeg_version: &str, _flags: AlxBuildFlags) {
-    println!("Not Implemented!");
-    // setup_cargo_appimage();
+// GitHub releases tag the AppImage build is continuously published under; zsync clients resolve
+// `<ZSYNC_RELEASE_URL>/<appimage_fname>.zsync` to fetch only the blocks that changed since the
+// version they already have.
+const ZSYNC_RELEASE_URL: &str = "https://github.com/korejan/ALVR/releases/download/continuous";
+
+pub fn build_alxr_app_image(root: Option<String>, ffmpeg_version: &str, flags: AlxBuildFlags, zsync: bool) {
+    _setup_cargo_appimage();
+
+    if let Some(root) = root {
+        env::set_var("ALVR_ROOT_DIR", root);
+    }
+
+    let build_flags = flags.make_build_string();
+    let target_dir = afs::target_dir();
+    let build_type = if flags.is_release { "release" } else { "debug" };
+    let artifacts_dir = target_dir.join(build_type);
+
+    let alxr_client_build_dir = afs::alxr_client_build_dir(build_type, !flags.no_nvidia);
+    fs::remove_dir_all(&alxr_client_build_dir).ok();
+    fs::create_dir_all(&alxr_client_build_dir).unwrap();
+
+    assert!(!ffmpeg_version.is_empty(), "ffmpeg-version is empty!");
+    let ffmpeg_build_dir = &alxr_client_build_dir;
+    dependencies::build_ffmpeg_linux_install(
+        /*nvenc_flag=*/ !flags.no_nvidia,
+        ffmpeg_version,
+        /*enable_decoders=*/ true,
+        ffmpeg_build_dir,
+    );
+    assert!(ffmpeg_build_dir.exists());
+    env::set_var(
+        "ALXR_BUNDLE_FFMPEG_INSTALL_PATH",
+        ffmpeg_build_dir.to_str().unwrap(),
+    );
+
+    let app_dir = alxr_client_build_dir.join("AlxrClient.AppDir");
+    fs::remove_dir_all(&app_dir).ok();
+    let app_dir_lib = app_dir.join("usr/lib");
+    let app_dir_bin = app_dir.join("usr/bin");
+    fs::create_dir_all(&app_dir_lib).unwrap();
+    fs::create_dir_all(&app_dir_bin).unwrap();
+
+    // Same rpath-resolution trick as `build_alxr_client`: libavcodec.so dynamically depends on
+    // libx264.so/libx265.so rather than linking them directly, so they don't show up as build
+    // artifacts and have to be found and copied in manually.
+    fn find_shared_lib(dir: &Path, key: &str) -> Option<std::path::PathBuf> {
+        for so_file in walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|maybe_entry| maybe_entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| afs::is_dynlib_file(&path))
+        {
+            let so_filename = so_file.file_name().unwrap();
+            if so_filename.to_string_lossy().starts_with(&key) {
+                return Some(so_file.canonicalize().unwrap());
+            }
+        }
+        None
+    }
 
-    // // let target_dir = afs::target_dir();
+    let lib_dir = alxr_client_build_dir.join("lib").canonicalize().unwrap();
+    if let Some(libavcodec_so) = find_shared_lib(&lib_dir, "libavcodec.so") {
+        for solib in ["libx264.so", "libx265.so"] {
+            let src_libs = dependencies::find_resolved_so_paths(&libavcodec_so, solib);
+            if !src_libs.is_empty() {
+                let src_lib = src_libs.first().unwrap();
+                let dst_lib = lib_dir.join(src_lib.file_name().unwrap());
+                println!("Copying {src_lib:?} to {dst_lib:?}");
+                fs::copy(src_lib, dst_lib).unwrap();
+            }
+        }
+    }
+    dependencies::bundle_transitive_shared_libs(&lib_dir);
 
-    // // let bundle_ffmpeg_enabled = cfg!(target_os = "linux") && flags.bundle_ffmpeg;
-    // // if bundle_ffmpeg_enabled {
-    // //     assert!(!ffmpeg_version.is_empty(), "ffmpeg-version is empty!");
+    for so_file in walkdir::WalkDir::new(&lib_dir)
+        .into_iter()
+        .filter_map(|maybe_entry| maybe_entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| afs::is_dynlib_file(&path))
+    {
+        let dst_file = app_dir_lib.join(so_file.file_name().unwrap());
+        fs::copy(&so_file, &dst_file).unwrap();
+    }
 
-    // //     let ffmpeg_lib_dir = &alxr_client_build_dir;
-    // //     dependencies::build_ffmpeg_linux_install(true, ffmpeg_version, /*enable_decoders=*/true, &ffmpeg_lib_dir);
+    if flags.fetch_crates {
+        command::run("cargo update").unwrap();
+    }
+
+    let alxr_client_dir = afs::workspace_dir().join("alvr/openxr-client/alxr-client");
+    command::run_in(
+        &alxr_client_dir,
+        &format!("cargo rustc {build_flags} -- -C link-args='-Wl,-rpath,$ORIGIN/lib'"),
+    )
+    .unwrap();
+
+    let alxr_client_fname = afs::exec_fname("alxr-client");
+    fs::copy(
+        artifacts_dir.join(&alxr_client_fname),
+        app_dir_bin.join(&alxr_client_fname),
+    )
+    .unwrap();
 
-    // //     assert!(ffmpeg_lib_dir.exists());
-    // //     env::set_var("ALXR_BUNDLE_FFMPEG_INSTALL_PATH", ffmpeg_lib_dir.to_str().unwrap());
-    // // }
+    let icon_fname = "alxr-client.png";
+    let icon_src = alxr_client_dir.join("resources").join(icon_fname);
+    let icon_dst = app_dir.join(icon_fname);
+    if icon_src.exists() {
+        fs::copy(&icon_src, &icon_dst).unwrap();
+    }
 
-    // if let Some(root) = root {
-    //     env::set_var("ALVR_ROOT_DIR", root);
-    // }
-    // if flags.fetch_crates {
-    //     command::run("cargo update").unwrap();
-    // }
-    // let build_flags = flags.make_build_string();
-    // let alxr_client_dir = afs::workspace_dir().join("alvr/openxr-client/alxr-client");
+    fs::write(
+        app_dir.join("alxr-client.desktop"),
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=ALXR Client\n\
+             Exec={alxr_client_fname}\n\
+             Icon=alxr-client\n\
+             Categories=Game;\n\
+             Terminal=false\n"
+        ),
+    )
+    .unwrap();
 
-    // let rustflags = r#"RUSTFLAGS="-C link-args=-Wl,-rpath,$ORIGIN/lib""#;
-    // //env::set_var("RUSTFLAGS", "-C link-args=\'-Wl,-rpath,$ORIGIN/lib\'");
-    // command::run_in(&alxr_client_dir, &format!("{} cargo appimage {}", rustflags, build_flags)).unwrap();
+    #[cfg(target_arch = "x86_64")]
+    let target_arch_str = "x86_64";
+    #[cfg(target_arch = "x86")]
+    let target_arch_str = "i686";
+    #[cfg(target_arch = "aarch64")]
+    let target_arch_str = "aarch64";
+    #[cfg(target_arch = "arm")]
+    let target_arch_str = "armhf";
+
+    let alxr_version = command::crate_version(&alxr_client_dir);
+    let appimage_fname = format!("alxr-client-{alxr_version}-{target_arch_str}.AppImage");
+    let appimage_path = alxr_client_build_dir.join(&appimage_fname);
+
+    let ait_cmd = if zsync {
+        format!(
+            "appimagetool -u \"zsync|{ZSYNC_RELEASE_URL}/{appimage_fname}.zsync\" {} {}",
+            app_dir.to_string_lossy(),
+            appimage_path.to_string_lossy()
+        )
+    } else {
+        format!(
+            "appimagetool {} {}",
+            app_dir.to_string_lossy(),
+            appimage_path.to_string_lossy()
+        )
+    };
+    command::run(&ait_cmd).unwrap();
+    assert!(appimage_path.exists(), "appimagetool did not produce an AppImage");
+
+    if zsync {
+        let zsync_path = alxr_client_build_dir.join(format!("{appimage_fname}.zsync"));
+        assert!(
+            zsync_path.exists(),
+            "appimagetool did not produce the companion .zsync file"
+        );
+    }
 }
 
 fn install_alxr_depends() {
@@ -684,46 +904,584 @@ pub fn build_alxr_android(
         AndroidFlavor::PicoV4 => "pico-v4",
         _ => "",
     };
-    // cargo-apk has an issue where it will search the entire "target" build directory for "output" files that contain
-    // a build.rs print of out "cargo:rustc-link-search=...." and use those paths to determine which
-    // shared libraries copy into the final apk, this can causes issues if there are multiple versions of shared libs
-    // with the same name.
-    //     E.g.: The wrong platform build of libopenxr_loader.so gets copied into the wrong apk when
-    //           more than one variant of android client gets built.
-    // The workaround is set different "target-dir" for each variant/flavour of android builds.
-    let target_dir = afs::target_dir().join(client_dir);
     let alxr_client_dir = afs::workspace_dir()
         .join("alvr/openxr-client/alxr-android-client")
         .join(client_dir);
 
+    // xbuild walks the full transitive `cargo:rustc-link-search` graph per ABI and resolves which
+    // `.so` belongs to which ABI straight from that build graph (the same mechanism
+    // rust-mobile/xbuild#140 added for recursively-included native libraries), so unlike cargo-apk
+    // it doesn't need a dedicated `--target-dir` per flavor to avoid picking up the wrong
+    // `libopenxr_loader.so` when more than one ABI variant's build shares a target directory.
     command::run_in(
         &alxr_client_dir,
-        &format!(
-            "cargo apk build {0} --target-dir={1}",
-            build_flags,
-            target_dir.display()
-        ),
+        &format!("x build --platform android --format apk {build_flags}"),
     )
     .unwrap();
 
     fn is_package_file(p: &Path) -> bool {
         p.extension().map_or(false, |ext| {
             let ext_str = ext.to_str().unwrap();
-            return ["apk", "aar", "idsig"].contains(&ext_str);
+            ["apk", "aab", "aar", "idsig"].contains(&ext_str)
         })
     }
-    let apk_dir = target_dir.join(build_type).join("apk");
-    for file in walkdir::WalkDir::new(&apk_dir)
+
+    // xbuild's artifact output is deterministic -- `target/x/<profile>/android/` holds exactly the
+    // requested format(s) for the package being built -- so the output set can be read directly
+    // instead of a `WalkDir` scan over a cargo-apk `apk_dir` trying to spot the right files.
+    let xbuild_out_dir = alxr_client_dir
+        .join("target/x")
+        .join(build_type)
+        .join("android");
+    for file in fs::read_dir(&xbuild_out_dir)
+        .unwrap()
+        .filter_map(|maybe_entry| maybe_entry.ok())
+        .map(|entry| entry.path())
+        .filter(|entry| is_package_file(entry))
+    {
+        let dst_file = alxr_client_build_dir.join(file.file_name().unwrap());
+        fs::copy(&file, &dst_file).unwrap();
+    }
+}
+
+/// Android ABI identifiers `cargo apk build --target` and an APK's `lib/<abi>/` tree use; kept
+/// distinct from the Rust target triple since the two naming schemes don't agree (e.g.
+/// `arm64-v8a` vs. `aarch64-linux-android`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AndroidAbi {
+    Arm64V8a,
+    ArmeabiV7a,
+    X86_64,
+    X86,
+}
+
+impl AndroidAbi {
+    fn rust_triple(self) -> &'static str {
+        match self {
+            AndroidAbi::Arm64V8a => "aarch64-linux-android",
+            AndroidAbi::ArmeabiV7a => "armv7-linux-androideabi",
+            AndroidAbi::X86_64 => "x86_64-linux-android",
+            AndroidAbi::X86 => "i686-linux-android",
+        }
+    }
+
+    fn jniabi(self) -> &'static str {
+        match self {
+            AndroidAbi::Arm64V8a => "arm64-v8a",
+            AndroidAbi::ArmeabiV7a => "armeabi-v7a",
+            AndroidAbi::X86_64 => "x86_64",
+            AndroidAbi::X86 => "x86",
+        }
+    }
+}
+
+// Shared objects the OpenXR loader resolves at runtime; these are the ones worth merging across
+// ABIs into a single fat APK instead of shipping one APK per ABI.
+const FAT_APK_NATIVE_LIBS: &[&str] = &["libalxr_client.so", "libopenxr_loader.so"];
+
+// `.so` entries inside an APK's `lib/<abi>/` tree must be stored uncompressed and page-aligned so
+// the loader can `mmap` them directly on API 23+ instead of the installer copying them out first;
+// this is the same alignment `zipalign -p` enforces.
+const SO_ALIGNMENT: u16 = 4096;
+
+/// Builds `client_flavor` once per entry in `abis` with cargo-apk (each into its own
+/// `--target-dir`, which dodges cargo-apk's bug of picking up the wrong `libopenxr_loader.so` when
+/// more than one ABI variant shares a target directory),
+/// then merges every other ABI's native libraries into the first ABI's APK so a single fat APK
+/// ships instead of one per ABI. Mirrors the approach in rust-mobile/xbuild's `Apk::add_lib`.
+pub fn build_alxr_android_fat(
+    root: Option<String>,
+    client_flavor: AndroidFlavor,
+    abis: Vec<AndroidAbi>,
+    flags: AlxBuildFlags,
+) {
+    assert!(
+        !abis.is_empty(),
+        "build_alxr_android_fat requires at least one ABI"
+    );
+
+    let build_type = if flags.is_release { "release" } else { "debug" };
+    let build_flags = flags.make_build_string();
+
+    if let Some(root) = &root {
+        env::set_var("ALVR_ROOT_DIR", root);
+    }
+    if flags.fetch_crates {
+        command::run("cargo update").unwrap();
+    }
+    install_alxr_depends();
+
+    let client_dir = match client_flavor {
+        AndroidFlavor::OculusQuest => "quest",
+        AndroidFlavor::Pico => "pico",
+        AndroidFlavor::PicoV4 => "pico-v4",
+        _ => "",
+    };
+    let alxr_client_dir = afs::workspace_dir()
+        .join("alvr/openxr-client/alxr-android-client")
+        .join(client_dir);
+
+    // Build every ABI into its own target-dir (the cargo-apk workaround described above), keeping
+    // track of each one's apk output directory so the native libs can be pulled back out below.
+    let mut per_abi_apk_dirs = Vec::with_capacity(abis.len());
+    for abi in &abis {
+        let target_dir = afs::target_dir().join(client_dir).join(abi.jniabi());
+        command::run_in(
+            &alxr_client_dir,
+            &format!(
+                "cargo apk build {0} --target {1} --target-dir={2}",
+                build_flags,
+                abi.rust_triple(),
+                target_dir.display()
+            ),
+        )
+        .unwrap();
+        per_abi_apk_dirs.push((*abi, target_dir.join(build_type).join("apk")));
+    }
+
+    let alxr_client_build_dir = afs::alxr_android_build_dir(build_type);
+    fs::create_dir_all(&alxr_client_build_dir).unwrap();
+
+    fn find_apk(apk_dir: &Path) -> std::path::PathBuf {
+        walkdir::WalkDir::new(apk_dir)
+            .into_iter()
+            .filter_map(|maybe_entry| maybe_entry.ok())
+            .map(|entry| entry.into_path())
+            .find(|p| p.extension().map_or(false, |ext| ext == "apk"))
+            .unwrap_or_else(|| panic!("no .apk found under {}", apk_dir.display()))
+    }
+
+    fn find_native_lib(apk_dir: &Path, lib_name: &str) -> std::path::PathBuf {
+        walkdir::WalkDir::new(apk_dir)
+            .into_iter()
+            .filter_map(|maybe_entry| maybe_entry.ok())
+            .map(|entry| entry.into_path())
+            .find(|p| p.file_name().map_or(false, |f| f == lib_name))
+            .unwrap_or_else(|| panic!("{lib_name} not found under {}", apk_dir.display()))
+    }
+
+    let (base_abi, base_apk_dir) = &per_abi_apk_dirs[0];
+    let base_apk_path = find_apk(base_apk_dir);
+    let fat_apk_fname = base_apk_path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .replace(base_abi.jniabi(), "fat");
+    let fat_apk_path = alxr_client_build_dir.join(fat_apk_fname);
+    fs::copy(&base_apk_path, &fat_apk_path).unwrap();
+
+    // `base_abi`'s own libraries are already in `fat_apk_path` from cargo-apk; only the remaining
+    // ABIs' libraries need to be merged in under their own `lib/<abi>/` entries.
+    for (abi, apk_dir) in per_abi_apk_dirs.iter().skip(1) {
+        for lib_name in FAT_APK_NATIVE_LIBS {
+            let lib_path = find_native_lib(apk_dir, lib_name);
+            insert_lib_into_apk(&fat_apk_path, abi.jniabi(), lib_name, &lib_path);
+        }
+    }
+
+    let zipaligned_path = fat_apk_path.with_extension("aligned.apk");
+    command::run(&format!(
+        "zipalign -f -p 4 {} {}",
+        fat_apk_path.to_string_lossy(),
+        zipaligned_path.to_string_lossy()
+    ))
+    .unwrap();
+    fs::rename(&zipaligned_path, &fat_apk_path).unwrap();
+
+    command::run(&format!(
+        "apksigner sign --ks debug.keystore {}",
+        fat_apk_path.to_string_lossy()
+    ))
+    .unwrap();
+
+    assert!(fat_apk_path.exists(), "failed to produce fat APK");
+}
+
+/// Inserts `lib_path` into `apk_path`'s zip under `lib/<jniabi>/<lib_name>`, stored uncompressed
+/// and padded to `SO_ALIGNMENT` so the loader can `mmap` it without `zipalign` needing to move it
+/// later (every other entry is copied through unchanged via `raw_copy_file`, so existing
+/// alignment/signing metadata for them is left untouched until the explicit re-sign below).
+fn insert_lib_into_apk(apk_path: &Path, jniabi: &str, lib_name: &str, lib_path: &Path) {
+    let apk_bytes = fs::read(apk_path).unwrap();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(apk_bytes)).unwrap();
+
+    let mut out_bytes = Vec::new();
+    {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(&mut out_bytes));
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i).unwrap();
+            writer.raw_copy_file(entry).unwrap();
+        }
+
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .with_alignment(SO_ALIGNMENT);
+        writer
+            .start_file(format!("lib/{jniabi}/{lib_name}"), options)
+            .unwrap();
+        writer.write_all(&fs::read(lib_path).unwrap()).unwrap();
+        writer.finish().unwrap();
+    }
+
+    fs::write(apk_path, out_bytes).unwrap();
+}
+
+/// Keystore credentials for re-signing a built APK, shared between the `sign-client` subcommand
+/// and `package-client --sign`.
+#[derive(Clone, Debug)]
+pub struct SignClientArgs {
+    pub keystore: String,
+    pub ks_pass: String,
+    pub ks_alias: String,
+}
+
+const IDSIG_BLOCK_SIZE: usize = 4096;
+
+fn find_built_apk(dir: &Path) -> std::path::PathBuf {
+    walkdir::WalkDir::new(dir)
         .into_iter()
         .filter_map(|maybe_entry| maybe_entry.ok())
         .map(|entry| entry.into_path())
-        .filter(|entry| is_package_file(&entry))
+        .find(|p| p.extension().map_or(false, |ext| ext == "apk"))
+        .unwrap_or_else(|| panic!("no .apk found under {}", dir.display()))
+}
+
+/// Re-signs `apk_path` with `sign_args`'s keystore using APK Signature Scheme v2/v3 (cargo-apk's
+/// debug keystore signs distribution builds otherwise, which the Play Store and most devices
+/// reject for side-loading), then regenerates the companion `.idsig` Merkle-tree file `adb install
+/// --incremental` needs.
+pub fn sign_client_apk(apk_path: &Path, sign_args: &SignClientArgs) {
+    command::run(&format!(
+        "apksigner sign --v2-signing-enabled --v3-signing-enabled --ks {} --ks-pass pass:{} --ks-key-alias {} {}",
+        sign_args.keystore,
+        sign_args.ks_pass,
+        sign_args.ks_alias,
+        apk_path.to_string_lossy()
+    ))
+    .unwrap();
+
+    let idsig_path = apk_path.with_extension("apk.idsig");
+    write_idsig(apk_path, &idsig_path);
+}
+
+fn sha256_with_prefix(prefix: u8, data: &[u8]) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update([prefix]);
+    hasher.update(data);
+    hasher.finalize().to_vec()
+}
+
+/// Computes a SHA-256 Merkle tree over `apk_path` in `IDSIG_BLOCK_SIZE`-byte blocks -- leaf hashes
+/// (prefixed `0x00` to separate them from node hashes) bottom-up to a single root (nodes prefixed
+/// `0x01`) -- and writes its root hash next to the APK as `<apk>.idsig`, after an `IDSG` magic,
+/// the APK length and the block size, for `adb install --incremental`. The intermediate levels of
+/// the tree are not written.
+fn write_idsig(apk_path: &Path, idsig_path: &Path) {
+    let apk_bytes = fs::read(apk_path).unwrap();
+
+    let mut level: Vec<Vec<u8>> = apk_bytes
+        .chunks(IDSIG_BLOCK_SIZE)
+        .map(|chunk| sha256_with_prefix(0x00, chunk))
+        .collect();
+    if level.is_empty() {
+        level.push(sha256_with_prefix(0x00, &[]));
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => sha256_with_prefix(0x01, &[a.as_slice(), b.as_slice()].concat()),
+                [a] => a.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    let root = level.into_iter().next().unwrap();
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"IDSG");
+    out.extend_from_slice(&(apk_bytes.len() as u64).to_le_bytes());
+    out.extend_from_slice(&(IDSIG_BLOCK_SIZE as u32).to_le_bytes());
+    out.extend_from_slice(&root);
+    fs::write(idsig_path, out).unwrap();
+}
+
+/// Builds `client_flavor` across `abis` (the same per-ABI build step `build_alxr_android_fat`
+/// uses), then assembles the Play Store `base/` module layout -- `base/manifest/`, `base/dex/`,
+/// `base/lib/<abi>/` and `base/res/`/`resources.pb` -- from the first ABI's compiled APK plus
+/// every ABI's native libraries, zips that into the bundle format, and drives `bundletool
+/// build-bundle` followed by `bundletool build-apks` to produce a signed `.apks` set for local
+/// testing. This is how ALVR ships per-device-optimized downloads (split by ABI/density) instead
+/// of a single universal APK, which matters for the size of the bundled FFmpeg/OpenXR payloads.
+pub fn build_android_app_bundle(
+    root: Option<String>,
+    client_flavor: AndroidFlavor,
+    abis: Vec<AndroidAbi>,
+    flags: AlxBuildFlags,
+    sign_args: Option<SignClientArgs>,
+) {
+    assert!(
+        !abis.is_empty(),
+        "build_android_app_bundle requires at least one ABI"
+    );
+
+    let build_type = if flags.is_release { "release" } else { "debug" };
+    let build_flags = flags.make_build_string();
+
+    if let Some(root) = &root {
+        env::set_var("ALVR_ROOT_DIR", root);
+    }
+    if flags.fetch_crates {
+        command::run("cargo update").unwrap();
+    }
+    install_alxr_depends();
+
+    let client_dir = match client_flavor {
+        AndroidFlavor::OculusQuest => "quest",
+        AndroidFlavor::Pico => "pico",
+        AndroidFlavor::PicoV4 => "pico-v4",
+        _ => "",
+    };
+    let alxr_client_dir = afs::workspace_dir()
+        .join("alvr/openxr-client/alxr-android-client")
+        .join(client_dir);
+
+    let mut per_abi_apk_dirs = Vec::with_capacity(abis.len());
+    for abi in &abis {
+        let target_dir = afs::target_dir().join(client_dir).join(abi.jniabi());
+        command::run_in(
+            &alxr_client_dir,
+            &format!(
+                "cargo apk build {0} --target {1} --target-dir={2}",
+                build_flags,
+                abi.rust_triple(),
+                target_dir.display()
+            ),
+        )
+        .unwrap();
+        per_abi_apk_dirs.push((*abi, target_dir.join(build_type).join("apk")));
+    }
+
+    fn find_native_lib(apk_dir: &Path, lib_name: &str) -> std::path::PathBuf {
+        walkdir::WalkDir::new(apk_dir)
+            .into_iter()
+            .filter_map(|maybe_entry| maybe_entry.ok())
+            .map(|entry| entry.into_path())
+            .find(|p| p.file_name().map_or(false, |f| f == lib_name))
+            .unwrap_or_else(|| panic!("{lib_name} not found under {}", apk_dir.display()))
+    }
+
+    let alxr_client_build_dir = afs::alxr_android_build_dir(build_type);
+    let bundle_staging_dir = alxr_client_build_dir.join("bundle_staging");
+    fs::remove_dir_all(&bundle_staging_dir).ok();
+    let base_module_dir = bundle_staging_dir.join("base");
+    fs::create_dir_all(&base_module_dir).unwrap();
+
+    // The manifest, dex files and compiled resources are identical across ABIs; only the native
+    // libraries differ, so the first ABI's APK is unpacked once to source everything else from.
+    let (_, base_apk_dir) = &per_abi_apk_dirs[0];
+    let base_apk_path = find_built_apk(base_apk_dir);
+    let unpacked_dir = bundle_staging_dir.join("unpacked_apk");
+    command::run(&format!(
+        "unzip -o {} -d {}",
+        base_apk_path.to_string_lossy(),
+        unpacked_dir.to_string_lossy()
+    ))
+    .unwrap();
+
+    fs::create_dir_all(base_module_dir.join("manifest")).unwrap();
+    fs::copy(
+        unpacked_dir.join("AndroidManifest.xml"),
+        base_module_dir.join("manifest/AndroidManifest.xml"),
+    )
+    .unwrap();
+
+    fs::create_dir_all(base_module_dir.join("dex")).unwrap();
+    for entry in walkdir::WalkDir::new(&unpacked_dir)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|maybe_entry| maybe_entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|p| p.extension().map_or(false, |ext| ext == "dex"))
     {
-        let relative_lpf = file.strip_prefix(&apk_dir).unwrap();
-        let dst_file = alxr_client_build_dir.join(relative_lpf);
-        std::fs::create_dir_all(dst_file.parent().unwrap()).unwrap();
-        fs::copy(&file, &dst_file).unwrap();
+        fs::copy(&entry, base_module_dir.join("dex").join(entry.file_name().unwrap())).unwrap();
     }
+
+    if unpacked_dir.join("res").exists() {
+        command::run(&format!(
+            "cp -r {} {}",
+            unpacked_dir.join("res").to_string_lossy(),
+            base_module_dir.to_string_lossy()
+        ))
+        .unwrap();
+    }
+    if unpacked_dir.join("resources.pb").exists() {
+        fs::copy(
+            unpacked_dir.join("resources.pb"),
+            base_module_dir.join("resources.pb"),
+        )
+        .unwrap();
+    }
+
+    for (abi, apk_dir) in &per_abi_apk_dirs {
+        let lib_target_dir = base_module_dir.join("lib").join(abi.jniabi());
+        fs::create_dir_all(&lib_target_dir).unwrap();
+        for lib_name in FAT_APK_NATIVE_LIBS {
+            fs::copy(
+                find_native_lib(apk_dir, lib_name),
+                lib_target_dir.join(lib_name),
+            )
+            .unwrap();
+        }
+    }
+
+    let module_zip_path = bundle_staging_dir.join("base.zip");
+    command::run_in(
+        &base_module_dir,
+        &format!("zip -r {} .", module_zip_path.to_string_lossy()),
+    )
+    .unwrap();
+
+    let aab_path = alxr_client_build_dir.join("alxr-client.aab");
+    command::run(&format!(
+        "bundletool build-bundle --modules={} --output={}",
+        module_zip_path.to_string_lossy(),
+        aab_path.to_string_lossy()
+    ))
+    .unwrap();
+    assert!(aab_path.exists(), "bundletool did not produce a .aab");
+
+    let apks_path = alxr_client_build_dir.join("alxr-client.apks");
+    let mut apks_cmd = format!(
+        "bundletool build-apks --bundle={} --output={} --overwrite",
+        aab_path.to_string_lossy(),
+        apks_path.to_string_lossy()
+    );
+    if let Some(sign_args) = &sign_args {
+        apks_cmd.push_str(&format!(
+            " --ks={} --ks-pass=pass:{} --ks-key-alias={}",
+            sign_args.keystore, sign_args.ks_pass, sign_args.ks_alias
+        ));
+    }
+    command::run(&apks_cmd).unwrap();
+    assert!(apks_path.exists(), "bundletool did not produce a .apks set");
+}
+
+/// Fans the UWP x64/arm64 clients and the Android client out across `jobserver::run_units`
+/// instead of building them one at a time, capped at `jobs` concurrent units (0 = available
+/// cores). Every unit's own build function still reports errors by panicking, so each is run
+/// behind `catch_unwind` and turned into a `Result` the jobserver can collect.
+fn package_client_all(root: Option<String>, jobs: usize) {
+    fn as_unit<F>(f: F, label: &'static str) -> Box<dyn FnOnce() -> Result<(), String> + Send>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Box::new(move || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+                .map_err(|_| format!("{label} build failed"))
+        })
+    }
+
+    let units = vec![
+        as_unit(
+            {
+                let root = root.clone();
+                move || build_alxr_uwp(root, UWPArch::X86_64, AlxBuildFlags::default())
+            },
+            "UWP x64",
+        ),
+        as_unit(
+            {
+                let root = root.clone();
+                move || build_alxr_uwp(root, UWPArch::Aarch64, AlxBuildFlags::default())
+            },
+            "UWP arm64",
+        ),
+        as_unit(
+            move || build::build_android_client(Profile::Distribution),
+            "Android client",
+        ),
+    ];
+
+    match jobserver::run_units(jobs, units) {
+        Ok(_) => println!("package-client-all: all build units finished successfully"),
+        Err(e) => panic!("package-client-all: {e}"),
+    }
+}
+
+// Name `build-client-lib`'s C-ABI shared library is linked under, and the subdirectory its header
+// is installed to; kept as consts so the soname, symlink and pkg-config file below can't drift
+// out of sync with each other.
+const CLIENT_LIB_SONAME: &str = "alxr_client";
+const CLIENT_LIB_INCLUDE_SUBDIR: &str = "alxr";
+const CLIENT_LIB_PC_NAME: &str = "alxr-client";
+
+/// cargo-c-style install layout for `build-client-lib`'s output: the shared library under a
+/// versioned soname (`lib<name>.so.MAJOR.MINOR`) with an unversioned `lib<name>.so` symlink
+/// pointing at it, the generated header under `include/alxr/`, and an `alxr-client.pc` pkg-config
+/// file, so the library is consumable with plain `pkg-config --cflags --libs alxr-client` instead
+/// of manual path wrangling. `prefix` defaults to a `client_lib_install` directory next to
+/// `lib_path` and `libdir` to `<prefix>/lib`; both can be overridden to match a distro's FHS layout.
+fn stage_client_lib_pkgconfig(
+    lib_path: &Path,
+    header_path: &Path,
+    version: &str,
+    prefix: Option<String>,
+    libdir: Option<String>,
+) {
+    let prefix = prefix
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| lib_path.parent().unwrap().join("client_lib_install"));
+    let libdir = libdir
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| prefix.join("lib"));
+    let includedir = prefix.join("include").join(CLIENT_LIB_INCLUDE_SUBDIR);
+    let pkgconfig_dir = libdir.join("pkgconfig");
+    fs::create_dir_all(&libdir).unwrap();
+    fs::create_dir_all(&includedir).unwrap();
+    fs::create_dir_all(&pkgconfig_dir).unwrap();
+
+    let mut version_parts = version.splitn(3, '.');
+    let major = version_parts.next().unwrap_or("0");
+    let minor = version_parts.next().unwrap_or("0");
+    let soname = format!("lib{CLIENT_LIB_SONAME}.so.{major}.{minor}");
+    let versioned_path = libdir.join(&soname);
+    fs::copy(lib_path, &versioned_path).unwrap();
+
+    let unversioned_path = libdir.join(format!("lib{CLIENT_LIB_SONAME}.so"));
+    fs::remove_file(&unversioned_path).ok();
+    std::os::unix::fs::symlink(&soname, &unversioned_path).unwrap();
+
+    fs::copy(
+        header_path,
+        includedir.join(header_path.file_name().unwrap()),
+    )
+    .unwrap();
+
+    let prefix_str = prefix.display();
+    let libdir_str = libdir.display();
+    let pc_contents = format!(
+        "prefix={prefix_str}\nlibdir={libdir_str}\nincludedir=${{prefix}}/include\n\n\
+         Name: {CLIENT_LIB_PC_NAME}\n\
+         Description: ALVR C-ABI client library\n\
+         Version: {version}\n\
+         Cflags: -I${{includedir}}/{CLIENT_LIB_INCLUDE_SUBDIR}\n\
+         Libs: -L${{libdir}} -l{CLIENT_LIB_SONAME}\n"
+    );
+    fs::write(
+        pkgconfig_dir.join(format!("{CLIENT_LIB_PC_NAME}.pc")),
+        pc_contents,
+    )
+    .unwrap();
+
+    println!(
+        "staged {CLIENT_LIB_SONAME} under {} (pkg-config: {})",
+        prefix.display(),
+        pkgconfig_dir.join(format!("{CLIENT_LIB_PC_NAME}.pc")).display()
+    );
 }
 
 // Avoid Oculus link popups when debugging the client
@@ -745,8 +1503,20 @@ fn main() {
     if args.contains(["-h", "--help"]) {
         println!("{HELP_STR}");
     } else if let Ok(Some(subcommand)) = args.subcommand() {
-        let no_nvidia = args.contains("--no-nvidia");
-        let is_release = args.contains("--release");
+        // Fills in whatever the CLI didn't specify; see `config::BuildConfig` for the file's
+        // layout and `HELP_STR` for the opt-in contract (CLI flags always win over the file).
+        let build_config = config::BuildConfig::load_default().unwrap_or_default();
+
+        let no_nvidia = config::resolve_flag(
+            args.contains("--no-nvidia"),
+            build_config.flags.no_nvidia,
+            false,
+        );
+        let is_release = config::resolve_flag(
+            args.contains("--release"),
+            build_config.flags.release,
+            false,
+        );
         let profile = if is_release {
             Profile::Release
         } else {
@@ -758,19 +1528,42 @@ fn main() {
         let for_ci = args.contains("--ci");
         let keep_config = args.contains("--keep-config");
         let appimage = args.contains("--appimage");
+        let aab = args.contains("--aab");
         let zsync = args.contains("--zsync");
         let link_stdcpp = !args.contains("--no-stdcpp");
+        let jobs: usize = args.opt_value_from_str("--jobs").unwrap().unwrap_or(0);
+        let lib_prefix: Option<String> = args.opt_value_from_str("--prefix").unwrap();
+        let lib_libdir: Option<String> = args.opt_value_from_str("--libdir").unwrap();
+        let sign = args.contains("--sign");
+        let apk: Option<String> = args.opt_value_from_str("--apk").unwrap();
+        let keystore: Option<String> = args.opt_value_from_str("--keystore").unwrap();
+        let ks_pass: Option<String> = args.opt_value_from_str("--ks-pass").unwrap();
+        let ks_alias: Option<String> = args.opt_value_from_str("--ks-alias").unwrap();
+        let sign_args = match (keystore, ks_pass, ks_alias) {
+            (Some(keystore), Some(ks_pass), Some(ks_alias)) => Some(SignClientArgs {
+                keystore,
+                ks_pass,
+                ks_alias,
+            }),
+            _ => None,
+        };
 
-        let platform: Option<String> = args.opt_value_from_str("--platform").unwrap();
+        let platform: Option<String> = config::resolve_value(
+            args.opt_value_from_str("--platform").unwrap(),
+            build_config.platform.clone(),
+        );
         let version: Option<String> = args.opt_value_from_str("--version").unwrap();
-        let root: Option<String> = args.opt_value_from_str("--root").unwrap();
+        let root: Option<String> =
+            config::resolve_value(args.opt_value_from_str("--root").unwrap(), build_config.root);
 
         let default_var = String::from("release/6.0");
-        let mut ffmpeg_version: String =
-            args.opt_value_from_str("--ffmpeg-version").unwrap().map_or(
-                default_var.clone(),
-                |s: String| if s.is_empty() { default_var } else { s },
-            );
+        let ffmpeg_version_arg: Option<String> =
+            args.opt_value_from_str("--ffmpeg-version").unwrap();
+        let mut ffmpeg_version: String = config::resolve_value(
+            ffmpeg_version_arg,
+            build_config.ffmpeg.version.clone(),
+        )
+        .map_or(default_var.clone(), |s| if s.is_empty() { default_var } else { s });
         assert!(!ffmpeg_version.is_empty());
 
         if args.finish().is_empty() {
@@ -803,8 +1596,54 @@ fn main() {
                     run_streamer();
                 }
                 "package-streamer" => packaging::package_streamer(gpl, root, appimage, zsync),
-                "package-client" => build::build_android_client(Profile::Distribution),
-                "package-client-lib" => packaging::package_client_lib(link_stdcpp),
+                "package-client" => {
+                    build::build_android_client(Profile::Distribution);
+                    if sign {
+                        let sign_args = sign_args
+                            .clone()
+                            .unwrap_or_else(|| panic!(
+                                "package-client --sign requires --keystore, --ks-pass and --ks-alias"
+                            ));
+                        let apk_path = find_built_apk(&afs::alxr_android_build_dir("release"));
+                        sign_client_apk(&apk_path, &sign_args);
+                    }
+                    if aab {
+                        build_android_app_bundle(
+                            root.clone(),
+                            AndroidFlavor::Generic,
+                            vec![AndroidAbi::Arm64V8a, AndroidAbi::ArmeabiV7a],
+                            AlxBuildFlags::default(),
+                            sign_args.clone(),
+                        );
+                    }
+                }
+                "package-client-lib" => {
+                    packaging::package_client_lib(link_stdcpp);
+
+                    let build_type = if is_release { "release" } else { "debug" };
+                    let lib_dir = afs::alxr_client_lib_build_dir(build_type);
+                    let lib_path = lib_dir.join(format!("lib{CLIENT_LIB_SONAME}.so"));
+                    let header_path = lib_dir.join(format!("{CLIENT_LIB_SONAME}.h"));
+                    let alxr_client_dir =
+                        afs::workspace_dir().join("alvr/openxr-client/alxr-client");
+                    let client_lib_version = command::crate_version(&alxr_client_dir);
+                    stage_client_lib_pkgconfig(
+                        &lib_path,
+                        &header_path,
+                        &client_lib_version,
+                        lib_prefix,
+                        lib_libdir,
+                    );
+                }
+                "package-client-all" => package_client_all(root, jobs),
+                "sign-client" => {
+                    let apk = apk.unwrap_or_else(|| panic!("sign-client requires --apk <PATH>"));
+                    let sign_args = sign_args
+                        .unwrap_or_else(|| panic!(
+                            "sign-client requires --keystore, --ks-pass and --ks-alias"
+                        ));
+                    sign_client_apk(Path::new(&apk), &sign_args);
+                }
                 "clean" => clean(),
                 "bump" => version::bump_version(version, is_nightly),
                 "clippy" => clippy(),